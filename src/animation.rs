@@ -7,6 +7,8 @@ use std::io::{self, Read};
 use std::mem;
 use std::slice;
 
+use crate::codec::Norm;
+
 /// Animation.
 #[derive(Debug)]
 pub struct Animation {
@@ -40,8 +42,9 @@ impl Animation {
 }
 
 /// Key-frame i/o data.
-// TODO: Decode normalized integers at build time
-// and only keep the floating-point variants.
+///
+/// Normalized integers are decoded at build time, so only
+/// floating-point variants exist.
 #[derive(Debug)]
 enum KfData {
     SecondsF64(Box<[f64]>),
@@ -49,17 +52,19 @@ enum KfData {
     TranslationF64x3(Box<[[f64; 3]]>),
     TranslationF32x3(Box<[[f32; 3]]>),
     RotationF32x4(Box<[[f32; 4]]>),
-    RotationI16x4(Box<[[i16; 4]]>),
-    RotationU16x4(Box<[[u16; 4]]>),
-    RotationI8x4(Box<[[i8; 4]]>),
-    RotationU8x4(Box<[[u8; 4]]>),
     ScaleF32x3(Box<[[f32; 3]]>),
     WeightsF64(Box<[f64]>),
     WeightsF32(Box<[f32]>),
-    WeightsI16(Box<[i16]>),
-    WeightsU16(Box<[u16]>),
-    WeightsI8(Box<[i8]>),
-    WeightsU8(Box<[u8]>),
+}
+
+/// Decodes normalized key-frame data.
+fn decode<T: Norm>(data: &[T]) -> Box<[f32]> {
+    data.iter().map(|x| x.decode()).collect()
+}
+
+/// Decodes normalized key-frame data.
+fn decode_n<T: Norm, const N: usize>(data: &[[T; N]]) -> Box<[[f32; N]]> {
+    data.iter().map(|x| x.map(T::decode)).collect()
 }
 
 /// Key-frame input types.
@@ -218,6 +223,9 @@ impl Builder {
     /// Data is assumed to be tightly packed as defined by
     /// `output_type.layout()`.
     ///
+    /// Integer outputs are interpreted as normalized integers
+    /// (see [`Norm`]) and decoded into `f32`s.
+    ///
     /// The order which this method is called defines the slot
     /// occupied by the pushed output source.
     /// The first pushed output occupies the slot `0`.
@@ -257,18 +265,18 @@ impl Builder {
                 KfOutput::RotationF32x4 => KfData::RotationF32x4(unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
                 }),
-                KfOutput::RotationI16x4 => KfData::RotationI16x4(unsafe {
+                KfOutput::RotationI16x4 => KfData::RotationF32x4(decode_n::<i16, 4>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::RotationU16x4 => KfData::RotationU16x4(unsafe {
+                })),
+                KfOutput::RotationU16x4 => KfData::RotationF32x4(decode_n::<u16, 4>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::RotationI8x4 => KfData::RotationI8x4(unsafe {
+                })),
+                KfOutput::RotationI8x4 => KfData::RotationF32x4(decode_n::<i8, 4>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::RotationU8x4 => KfData::RotationU8x4(unsafe {
+                })),
+                KfOutput::RotationU8x4 => KfData::RotationF32x4(decode_n::<u8, 4>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
+                })),
                 KfOutput::ScaleF32x3 => KfData::ScaleF32x3(unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
                 }),
@@ -278,18 +286,18 @@ impl Builder {
                 KfOutput::WeightsF32 => KfData::WeightsF32(unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
                 }),
-                KfOutput::WeightsI16 => KfData::WeightsI16(unsafe {
+                KfOutput::WeightsI16 => KfData::WeightsF32(decode::<i16>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::WeightsU16 => KfData::WeightsU16(unsafe {
+                })),
+                KfOutput::WeightsU16 => KfData::WeightsF32(decode::<u16>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::WeightsI8 => KfData::WeightsI8(unsafe {
+                })),
+                KfOutput::WeightsI8 => KfData::WeightsF32(decode::<i8>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
-                KfOutput::WeightsU8 => KfData::WeightsU8(unsafe {
+                })),
+                KfOutput::WeightsU8 => KfData::WeightsF32(decode::<u8>(&unsafe {
                     Box::from_raw(slice::from_raw_parts_mut(ptr.cast(), sample_count))
-                }),
+                })),
             };
             self.outputs.push(data);
             Ok(self)
@@ -350,3 +358,44 @@ impl Default for Builder {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_outputs() {
+        let rot: [[i16; 4]; 2] = [[0, 0, 0, 32767], [-32768, 16384, 0, 0]];
+        let wgt: [u8; 3] = [0, 51, 255];
+        let mut bld = Builder::new();
+        bld.push_input(&[0u8; 8][..], KfInput::SecondsF32, 2)
+            .unwrap()
+            .push_output(
+                rot.iter()
+                    .flatten()
+                    .flat_map(|x| x.to_ne_bytes())
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+                KfOutput::RotationI16x4,
+                2,
+            )
+            .unwrap()
+            .push_output(&wgt[..], KfOutput::WeightsU8, 3)
+            .unwrap()
+            .push_action(Interpolation::Linear, 0, 0, "rot")
+            .push_action(Interpolation::Step, 0, 1, "wgt");
+        let anim = bld.create().unwrap();
+
+        let KfData::RotationF32x4(ref rot) = anim.outputs[0] else {
+            panic!("expected RotationF32x4");
+        };
+        assert_eq!(rot[0], [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(rot[1][0], -1.0);
+        assert!((rot[1][1] - 0.5).abs() <= 1.0 / 32767.0);
+
+        let KfData::WeightsF32(ref wgt) = anim.outputs[1] else {
+            panic!("expected WeightsF32");
+        };
+        assert_eq!(wgt[..], [0.0, 0.2, 1.0]);
+    }
+}
@@ -2,8 +2,12 @@
 
 use std::fmt;
 use std::ops::{
-    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Shl, ShlAssign, Shr,
-    ShrAssign,
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not, Range, Shl, ShlAssign,
+    Shr, ShrAssign,
+};
+use std::sync::atomic::{
+    AtomicU16, AtomicU32, AtomicU64, AtomicU8, AtomicUsize,
+    Ordering::{AcqRel, Acquire, Relaxed},
 };
 
 pub trait Unsigned:
@@ -24,6 +28,12 @@ pub trait Unsigned:
     const ZERO: Self;
     const ONE: Self;
     const BITS: usize;
+
+    /// Returns the number of trailing zeros.
+    fn trailing_zeros(self) -> usize;
+
+    /// Returns the number of ones.
+    fn count_ones(self) -> usize;
 }
 
 macro_rules! def_unsigned {
@@ -32,6 +42,14 @@ macro_rules! def_unsigned {
             const ZERO: Self = 0;
             const ONE: Self = 1;
             const BITS: usize = Self::BITS as _;
+
+            fn trailing_zeros(self) -> usize {
+                <$t>::trailing_zeros(self) as _
+            }
+
+            fn count_ones(self) -> usize {
+                <$t>::count_ones(self) as _
+            }
         }
     )*)
 }
@@ -284,6 +302,110 @@ impl<T: Unsigned> BitVec<T> {
         }
     }
 
+    /// Sets every bit in a given range.
+    pub fn set_range(&mut self, range: Range<usize>) {
+        self.update_range(range, |x, m| x | m);
+    }
+
+    /// Unsets every bit in a given range.
+    pub fn unset_range(&mut self, range: Range<usize>) {
+        self.update_range(range, |x, m| x & !m);
+    }
+
+    /// Applies `f` to every element overlapping `range`,
+    /// passing the mask of bits in the range.
+    fn update_range(&mut self, range: Range<usize>, f: impl Fn(T, T) -> T) {
+        if range.start >= range.end {
+            return;
+        }
+        assert!(range.end <= self.len(), "range out of bounds");
+        let beg = range.start / T::BITS;
+        let end = (range.end - 1) / T::BITS;
+        for i in beg..=end {
+            let x = self.vec[i];
+            let y = f(x, range_mask(&range, i));
+            self.rem = self.rem + x.count_ones() - y.count_ones();
+            self.vec[i] = y;
+        }
+    }
+
+    /// Searches for an unset bit at or after `bit_idx`.
+    /// It returns the index of the first unset bit found, or
+    /// `None` if every bit from `bit_idx` onwards is set.
+    /// The bit is not set by this method.
+    pub fn find_from(&self, bit_idx: usize) -> Option<usize> {
+        if self.rem == 0 || bit_idx >= self.len() {
+            return None;
+        }
+        let idx = bit_idx / T::BITS;
+        // Treat the bits before `bit_idx` as set.
+        let first = !self.vec[idx] & (!T::ZERO << (bit_idx & (T::BITS - 1)));
+        if first != T::ZERO {
+            return Some(idx * T::BITS + first.trailing_zeros());
+        }
+        self.vec[idx + 1..]
+            .iter()
+            .position(|&x| x != !T::ZERO)
+            .map(|i| (idx + 1 + i) * T::BITS + (!self.vec[idx + 1 + i]).trailing_zeros())
+    }
+
+    /// Returns an iterator over the indices of set bits.
+    pub fn iter_set(&self) -> Iter<'_, T> {
+        Iter::new(self, false)
+    }
+
+    /// Returns an iterator over the indices of unset bits.
+    pub fn iter_unset(&self) -> Iter<'_, T> {
+        Iter::new(self, true)
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.len() - self.rem
+    }
+
+    /// Computes the bitwise AND with `other` in place.
+    ///
+    /// NOTE: One must ensure that both vectors have the
+    /// same length.
+    pub fn and(&mut self, other: &Self) {
+        self.combine(other, |x, y| x & y);
+    }
+
+    /// Computes the bitwise OR with `other` in place.
+    ///
+    /// NOTE: One must ensure that both vectors have the
+    /// same length.
+    pub fn or(&mut self, other: &Self) {
+        self.combine(other, |x, y| x | y);
+    }
+
+    /// Computes the bitwise XOR with `other` in place.
+    ///
+    /// NOTE: One must ensure that both vectors have the
+    /// same length.
+    pub fn xor(&mut self, other: &Self) {
+        self.combine(other, |x, y| x ^ y);
+    }
+
+    /// Flips every bit in place.
+    pub fn not(&mut self) {
+        self.vec.iter_mut().for_each(|x| *x = !*x);
+        self.rem = self.len() - self.rem;
+    }
+
+    /// Combines each element with the respective element
+    /// of `other`.
+    fn combine(&mut self, other: &Self, f: impl Fn(T, T) -> T) {
+        assert_eq!(self.len(), other.len(), "length mismatch");
+        let mut ones = 0;
+        for (x, &y) in self.vec.iter_mut().zip(&other.vec) {
+            *x = f(*x, y);
+            ones += x.count_ones();
+        }
+        self.rem = self.len() - ones;
+    }
+
     /// Returns the vector's length in number of bits.
     pub fn len(&self) -> usize {
         self.vec.len() * T::BITS
@@ -295,6 +417,65 @@ impl<T: Unsigned> BitVec<T> {
     }
 }
 
+/// Returns the mask of bits of the `i`th element that
+/// lie within `range`.
+///
+/// NOTE: One must ensure that `range` is not empty.
+fn range_mask<T: Unsigned>(range: &Range<usize>, i: usize) -> T {
+    let mut mask = !T::ZERO;
+    if i == range.start / T::BITS {
+        mask &= !T::ZERO << (range.start & (T::BITS - 1));
+    }
+    if i == (range.end - 1) / T::BITS {
+        mask &= !T::ZERO >> (T::BITS - 1 - ((range.end - 1) & (T::BITS - 1)));
+    }
+    mask
+}
+
+/// Iterator over the indices of either set or unset bits
+/// of a [`BitVec`].
+pub struct Iter<'a, T: Unsigned> {
+    vec: &'a [T],
+    // Whether to iterate over unset bits.
+    inv: bool,
+    // Index of the current element.
+    idx: usize,
+    // Bits of the current element yet to be visited.
+    cur: T,
+}
+
+impl<'a, T: Unsigned> Iter<'a, T> {
+    fn new(bv: &'a BitVec<T>, inv: bool) -> Self {
+        let cur = match bv.vec.first() {
+            Some(&x) if inv => !x,
+            Some(&x) => x,
+            None => T::ZERO,
+        };
+        Self {
+            vec: &bv.vec,
+            inv,
+            idx: 0,
+            cur,
+        }
+    }
+}
+
+impl<T: Unsigned> Iterator for Iter<'_, T> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.cur == T::ZERO {
+            self.idx += 1;
+            let x = *self.vec.get(self.idx)?;
+            self.cur = if self.inv { !x } else { x };
+        }
+        let bit = self.cur.trailing_zeros();
+        // Clear the lowest bit.
+        self.cur &= !(T::ONE << bit);
+        Some(self.idx * T::BITS + bit)
+    }
+}
+
 impl<T: Unsigned> Default for BitVec<T> {
     /// Calls [`BitVec::new`].
     fn default() -> Self {
@@ -329,6 +510,325 @@ impl<T: Unsigned + fmt::Binary> fmt::Display for BitVec<T> {
     }
 }
 
+pub trait AtomicUnsigned: Send + Sync {
+    type Word: Unsigned;
+
+    fn new(x: Self::Word) -> Self;
+    fn load(&self) -> Self::Word;
+    fn get_mut(&mut self) -> &mut Self::Word;
+    fn compare_exchange_weak(
+        &self,
+        cur: Self::Word,
+        new: Self::Word,
+    ) -> Result<Self::Word, Self::Word>;
+    fn fetch_and(&self, x: Self::Word) -> Self::Word;
+    fn fetch_or(&self, x: Self::Word) -> Self::Word;
+}
+
+macro_rules! def_atomic_unsigned {
+    ($($t:ty, $w:ty)*) => ($(
+        impl AtomicUnsigned for $t {
+            type Word = $w;
+
+            fn new(x: $w) -> Self {
+                <$t>::new(x)
+            }
+
+            fn load(&self) -> $w {
+                <$t>::load(self, Acquire)
+            }
+
+            fn get_mut(&mut self) -> &mut $w {
+                <$t>::get_mut(self)
+            }
+
+            fn compare_exchange_weak(&self, cur: $w, new: $w) -> Result<$w, $w> {
+                <$t>::compare_exchange_weak(self, cur, new, AcqRel, Acquire)
+            }
+
+            fn fetch_and(&self, x: $w) -> $w {
+                <$t>::fetch_and(self, x, AcqRel)
+            }
+
+            fn fetch_or(&self, x: $w) -> $w {
+                <$t>::fetch_or(self, x, AcqRel)
+            }
+        }
+    )*)
+}
+
+def_atomic_unsigned!(AtomicU8, u8 AtomicU16, u16 AtomicU32, u32 AtomicU64, u64 AtomicUsize, usize);
+
+/// Bit vector type that can be updated concurrently.
+///
+/// Resizing requires exclusive access, whereas setting,
+/// unsetting and searching can be done through shared
+/// references from several threads.
+#[derive(Debug)]
+pub struct AtomicBitVec<T: AtomicUnsigned> {
+    vec: Vec<T>,
+    rem: AtomicUsize,
+}
+
+impl<T: AtomicUnsigned> AtomicBitVec<T> {
+    const BITS: usize = T::Word::BITS;
+
+    /// Creates an empty bit vector.
+    pub fn new() -> Self {
+        Self {
+            vec: vec![],
+            rem: AtomicUsize::new(0),
+        }
+    }
+
+    /// Creates a bit vector with an initial number of words.
+    /// The total number of bits in the vector will be equal
+    /// to `n * T::Word::BITS`.
+    /// All bits are initially unset.
+    pub fn with_count_words(n: usize) -> Self {
+        let mut bv = Self::new();
+        bv.grow(n);
+        bv
+    }
+
+    /// Increment the vector by `inc` words.
+    /// It returns the index of the first bit in the pushed
+    /// range, or `None` if `inc` was `0`.
+    pub fn grow(&mut self, inc: usize) -> Option<usize> {
+        let bit_idx = self.len();
+        self.vec.extend((0..inc).map(|_| T::new(T::Word::ZERO)));
+        *self.rem.get_mut() += inc * Self::BITS;
+        (inc != 0).then_some(bit_idx)
+    }
+
+    /// Decrements the vector by `dec` words.
+    pub fn shrink(&mut self, dec: usize) {
+        let n = usize::min(dec, self.vec.len());
+        for _ in 0..n {
+            let x = *self.vec.pop().unwrap().get_mut();
+            *self.rem.get_mut() -= Self::BITS - x.count_ones();
+        }
+    }
+
+    /// Trims the vector to the minimum possible length.
+    /// This method pops words from the vector until one
+    /// with a bit set is found.
+    /// It returns the number of words popped.
+    pub fn trim(&mut self) -> usize {
+        let mut n = 0;
+        while let Some(x) = self.vec.last_mut() {
+            if *x.get_mut() != T::Word::ZERO {
+                break;
+            }
+            self.vec.pop();
+            n += 1;
+        }
+        *self.rem.get_mut() -= n * Self::BITS;
+        n
+    }
+
+    /// Sets a given bit.
+    pub fn set(&self, bit_idx: usize) {
+        let bit = T::Word::ONE << (bit_idx & (Self::BITS - 1));
+        if self.vec[bit_idx / Self::BITS].fetch_or(bit) & bit == T::Word::ZERO {
+            self.rem.fetch_sub(1, Relaxed);
+        }
+    }
+
+    /// Unsets a given bit.
+    pub fn unset(&self, bit_idx: usize) {
+        let bit = T::Word::ONE << (bit_idx & (Self::BITS - 1));
+        if self.vec[bit_idx / Self::BITS].fetch_and(!bit) & bit != T::Word::ZERO {
+            self.rem.fetch_add(1, Relaxed);
+        }
+    }
+
+    /// Sets every bit in a given range.
+    pub fn set_range(&self, range: Range<usize>) {
+        if range.start >= range.end {
+            return;
+        }
+        assert!(range.end <= self.len(), "range out of bounds");
+        for i in range.start / Self::BITS..=(range.end - 1) / Self::BITS {
+            let mask = range_mask::<T::Word>(&range, i);
+            let x = self.vec[i].fetch_or(mask);
+            self.rem.fetch_sub((!x & mask).count_ones(), Relaxed);
+        }
+    }
+
+    /// Unsets every bit in a given range.
+    pub fn unset_range(&self, range: Range<usize>) {
+        if range.start >= range.end {
+            return;
+        }
+        assert!(range.end <= self.len(), "range out of bounds");
+        for i in range.start / Self::BITS..=(range.end - 1) / Self::BITS {
+            let mask = range_mask::<T::Word>(&range, i);
+            let x = self.vec[i].fetch_and(!mask);
+            self.rem.fetch_add((x & mask).count_ones(), Relaxed);
+        }
+    }
+
+    /// Checks whether a given bit is set.
+    pub fn is_set(&self, bit_idx: usize) -> bool {
+        let bit = T::Word::ONE << (bit_idx & (Self::BITS - 1));
+        self.vec[bit_idx / Self::BITS].load() & bit == bit
+    }
+
+    /// Searches for an unset bit at or after `bit_idx`.
+    /// It returns the index of the first unset bit found, or
+    /// `None` if every bit from `bit_idx` onwards is set.
+    pub fn find_from(&self, bit_idx: usize) -> Option<usize> {
+        let len = self.len();
+        let idx = self.scan(bit_idx, true, len);
+        (idx < len).then_some(idx)
+    }
+
+    /// Searches for a set bit at or after `bit_idx`.
+    /// It returns the index of the first set bit found, or
+    /// `None` if every bit from `bit_idx` onwards is unset.
+    pub fn find_set_from(&self, bit_idx: usize) -> Option<usize> {
+        let len = self.len();
+        let idx = self.scan(bit_idx, false, len);
+        (idx < len).then_some(idx)
+    }
+
+    /// Searches for an unset bit and sets it.
+    /// It returns the index of the bit, or `None` if every
+    /// bit in the vector is set.
+    pub fn find_and_set(&self) -> Option<usize> {
+        if self.rem.load(Relaxed) == 0 {
+            return None;
+        }
+        for (i, x) in self.vec.iter().enumerate() {
+            let mut cur = x.load();
+            while cur != !T::Word::ZERO {
+                let bit = (!cur).trailing_zeros();
+                match x.compare_exchange_weak(cur, cur | (T::Word::ONE << bit)) {
+                    Ok(_) => {
+                        self.rem.fetch_sub(1, Relaxed);
+                        return Some(i * Self::BITS + bit);
+                    }
+                    Err(x) => cur = x,
+                }
+            }
+        }
+        None
+    }
+
+    /// Searches for a contiguous range of unset bits and
+    /// sets them.
+    /// It returns the index of the first bit in the range,
+    /// or `None` if there is no contiguous, unset range of
+    /// size `n`.
+    ///
+    /// NOTE: Ranges spanning several words are claimed one
+    /// word at a time and released if a concurrent update
+    /// gets in the way, so this method may fail to find a
+    /// range that would only be free momentarily.
+    pub fn find_contiguous_and_set(&self, n: usize) -> Option<usize> {
+        self.find_aligned_and_set(n, 1)
+    }
+
+    /// Same as `find_contiguous_and_set`, except that the
+    /// index of the first bit must be a multiple of `align`.
+    pub fn find_aligned_and_set(&self, n: usize, align: usize) -> Option<usize> {
+        match n {
+            0..=1 if align <= 1 => self.find_and_set(),
+            x if x > self.rem.load(Relaxed) => None,
+            _ => {
+                let n = n.max(1);
+                let align = align.max(1);
+                let len = self.len();
+                let mut pos = 0;
+                loop {
+                    let beg = self.scan(pos, true, len).next_multiple_of(align);
+                    if beg + n > len {
+                        break None;
+                    }
+                    let end = self.scan(beg, false, beg + n);
+                    if end == beg + n {
+                        if self.claim(beg..end) {
+                            self.rem.fetch_sub(n, Relaxed);
+                            break Some(beg);
+                        }
+                        // Someone else got there first, so
+                        // search this region again.
+                        pos = beg;
+                    } else {
+                        pos = end;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the index of the first bit at or after `pos`
+    /// that is unset (if `zeros`) or set (otherwise), or
+    /// `end` if there is none before `end`.
+    fn scan(&self, pos: usize, zeros: bool, end: usize) -> usize {
+        let mut i = pos / Self::BITS;
+        let mut mask = !T::Word::ZERO << (pos & (Self::BITS - 1));
+        while i * Self::BITS < end {
+            let mut x = self.vec[i].load();
+            if zeros {
+                x = !x;
+            }
+            x &= mask;
+            if x != T::Word::ZERO {
+                return usize::min(i * Self::BITS + x.trailing_zeros(), end);
+            }
+            mask = !T::Word::ZERO;
+            i += 1;
+        }
+        end
+    }
+
+    /// Sets every bit in `range` if all of them are unset,
+    /// leaving the vector unchanged otherwise.
+    fn claim(&self, range: Range<usize>) -> bool {
+        let beg = range.start / Self::BITS;
+        let end = (range.end - 1) / Self::BITS;
+        for i in beg..=end {
+            let mask = range_mask(&range, i);
+            let mut cur = self.vec[i].load();
+            loop {
+                if cur & mask != T::Word::ZERO {
+                    for j in beg..i {
+                        self.vec[j].fetch_and(!range_mask::<T::Word>(&range, j));
+                    }
+                    return false;
+                }
+                match self.vec[i].compare_exchange_weak(cur, cur | mask) {
+                    Ok(_) => break,
+                    Err(x) => cur = x,
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns the vector's length in number of bits.
+    pub fn len(&self) -> usize {
+        self.vec.len() * Self::BITS
+    }
+
+    /// Returns the number of unset bits.
+    ///
+    /// NOTE: This value is only exact when no other thread
+    /// is updating the vector.
+    pub fn rem(&self) -> usize {
+        self.rem.load(Relaxed)
+    }
+}
+
+impl<T: AtomicUnsigned> Default for AtomicBitVec<T> {
+    /// Calls [`AtomicBitVec::new`].
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1057,6 +1557,227 @@ mod tests {
         assert_eq!(v.find_contiguous(160), Some(0));
     }
 
+    #[test]
+    fn find_from() {
+        let mut v = BitVec::<u8>::new();
+        assert!(v.find_from(0).is_none());
+        v.grow(3);
+        assert_eq!(v.find_from(0), Some(0));
+        assert_eq!(v.find_from(5), Some(5));
+        assert_eq!(v.find_from(23), Some(23));
+        assert!(v.find_from(24).is_none());
+        v.set_range(3..20);
+        assert_eq!(v.find_from(0), Some(0));
+        assert_eq!(v.find_from(3), Some(20));
+        assert_eq!(v.find_from(8), Some(20));
+        assert_eq!(v.find_from(21), Some(21));
+        v.set_range(20..24);
+        assert!(v.find_from(3).is_none());
+        assert_eq!(v.find_from(2), Some(2));
+        v.unset(17);
+        assert_eq!(v.find_from(3), Some(17));
+        assert!(v.find_from(18).is_none());
+    }
+
+    #[test]
+    fn set_unset_range() {
+        let mut v = BitVec::<u8>::new();
+        v.set_range(0..0);
+        v.grow(3);
+        v.set_range(2..5);
+        v.assert(24, 21, &[(0, 0b11100), (1, 0), (2, 0)]);
+        v.set_range(6..19);
+        v.assert(24, 8, &[(0, 0b11011100), (1, 0xff), (2, 0b111)]);
+        v.set_range(0..24);
+        v.assert(24, 0, &[(0, 0xff), (1, 0xff), (2, 0xff)]);
+        v.unset_range(7..9);
+        v.assert(24, 2, &[(0, 0x7f), (1, 0xfe), (2, 0xff)]);
+        v.unset_range(16..24);
+        v.assert(24, 10, &[(0, 0x7f), (1, 0xfe), (2, 0)]);
+        v.unset_range(1..1);
+        v.assert(24, 10, &[(0, 0x7f), (1, 0xfe), (2, 0)]);
+        v.unset_range(0..24);
+        v.assert(24, 24, &[(0, 0), (1, 0), (2, 0)]);
+
+        let mut v = BitVec::<u64>::with_count_words(2);
+        v.set_range(63..65);
+        v.assert(128, 126, &[(0, 1 << 63), (1, 1)]);
+        v.set_range(0..128);
+        assert_eq!(v.count_ones(), 128);
+    }
+
+    #[test]
+    fn iter() {
+        let mut v = BitVec::<u16>::new();
+        assert_eq!(v.iter_set().next(), None);
+        assert_eq!(v.iter_unset().next(), None);
+        v.grow(3);
+        assert_eq!(v.iter_set().count(), 0);
+        assert!(v.iter_unset().eq(0..48));
+        let bits = [0, 1, 15, 16, 31, 40, 47];
+        for i in bits {
+            v.set(i);
+        }
+        assert!(v.iter_set().eq(bits));
+        assert!(v.iter_unset().eq((0..48).filter(|x| !bits.contains(x))));
+        assert_eq!(v.count_ones(), bits.len());
+        v.set_range(0..48);
+        assert!(v.iter_set().eq(0..48));
+        assert_eq!(v.iter_unset().next(), None);
+    }
+
+    #[test]
+    fn bitwise() {
+        let mut v = BitVec::<u8>::with_count_words(2);
+        let mut w = BitVec::<u8>::with_count_words(2);
+        v.set_range(0..10);
+        w.set_range(6..16);
+        let mut x = BitVec::<u8>::with_count_words(2);
+        x.or(&v);
+        x.assert(16, 6, &[(0, 0xff), (1, 0b11)]);
+        x.and(&w);
+        x.assert(16, 12, &[(0, 0b11000000), (1, 0b11)]);
+        x.xor(&v);
+        x.assert(16, 10, &[(0, 0b00111111), (1, 0)]);
+        x.or(&w);
+        x.assert(16, 0, &[(0, 0xff), (1, 0xff)]);
+        x.xor(&w);
+        x.assert(16, 10, &[(0, 0b00111111), (1, 0)]);
+        x.not();
+        x.assert(16, 6, &[(0, 0b11000000), (1, 0xff)]);
+        assert_eq!(x.count_ones(), 10);
+        assert_eq!(x.find(), Some(0));
+        let mut e = BitVec::<u8>::new();
+        e.not();
+        e.assert(0, 0, &[]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bitwise_mismatch() {
+        let mut v = BitVec::<u8>::with_count_words(2);
+        v.and(&BitVec::with_count_words(1));
+    }
+
+    #[test]
+    fn atomic() {
+        let mut v = AtomicBitVec::<AtomicU8>::new();
+        assert_eq!(v.len(), 0);
+        assert!(v.find_and_set().is_none());
+        assert!(v.find_contiguous_and_set(2).is_none());
+        assert_eq!(v.grow(3), Some(0));
+        assert_eq!(v.grow(0), None);
+        assert_eq!((v.len(), v.rem()), (24, 24));
+        assert_eq!(v.find_and_set(), Some(0));
+        assert_eq!(v.find_contiguous_and_set(3), Some(1));
+        v.set(5);
+        assert_eq!(v.find_contiguous_and_set(2), Some(6));
+        assert_eq!(v.find_contiguous_and_set(9), Some(8));
+        assert_eq!(v.find_and_set(), Some(4));
+        assert_eq!(v.rem(), 7);
+        assert!(v.find_contiguous_and_set(8).is_none());
+        assert_eq!(v.find_contiguous_and_set(7), Some(17));
+        assert!(v.find_and_set().is_none());
+        v.unset_range(2..11);
+        v.unset(20);
+        assert_eq!(v.rem(), 10);
+        assert!((0..24).all(|i| v.is_set(i) == (!(2..11).contains(&i) && i != 20)));
+        assert_eq!(v.find_contiguous_and_set(10), None);
+        assert_eq!(v.find_contiguous_and_set(9), Some(2));
+        assert_eq!(v.find_from(0), Some(20));
+        assert_eq!(v.find_from(21), None);
+        assert_eq!(v.find_set_from(20), Some(21));
+        v.unset_range(3..12);
+        assert_eq!(v.rem(), 10);
+        v.set_range(5..9);
+        assert_eq!(v.rem(), 6);
+        assert_eq!(v.find_from(0), Some(3));
+        assert_eq!(v.find_from(5), Some(9));
+        assert_eq!(v.find_set_from(3), Some(5));
+        v.set_range(0..24);
+        assert_eq!(v.rem(), 0);
+        assert_eq!(v.find_from(0), None);
+        v.unset_range(3..7);
+        v.unset_range(9..14);
+        assert_eq!(v.find_aligned_and_set(3, 4), Some(4));
+        assert_eq!(v.find_aligned_and_set(1, 4), Some(12));
+        assert_eq!(v.find_aligned_and_set(2, 2), Some(10));
+        assert_eq!(v.find_aligned_and_set(2, 2), None);
+        assert_eq!(v.find_aligned_and_set(1, 1), Some(3));
+        assert_eq!(v.rem(), 2);
+        v.shrink(1);
+        assert_eq!((v.len(), v.rem()), (16, 2));
+        v.grow(1);
+        v.set_range(0..24);
+        assert_eq!(v.trim(), 0);
+        v.unset_range(0..24);
+        assert_eq!(v.rem(), 24);
+        v.set(9);
+        assert_eq!(v.trim(), 1);
+        assert_eq!((v.len(), v.rem()), (16, 15));
+        v.unset(9);
+        assert_eq!(v.trim(), 2);
+        assert_eq!((v.len(), v.rem()), (0, 0));
+    }
+
+    #[test]
+    fn atomic_stress() {
+        const THREADS: usize = 8;
+        const ITERS: usize = 2000;
+        let v = AtomicBitVec::<AtomicU32>::with_count_words(16);
+        // Each thread repeatedly claims ranges, checks that
+        // nobody else claimed the same bits and frees them.
+        let owner: Vec<_> = (0..v.len()).map(|_| AtomicUsize::new(0)).collect();
+        std::thread::scope(|s| {
+            for t in 1..=THREADS {
+                let (v, owner) = (&v, &owner);
+                s.spawn(move || {
+                    let mut held = vec![];
+                    for i in 0..ITERS {
+                        let n = 1 + (i * 7 + t * 13) % 40;
+                        if let Some(x) = v.find_contiguous_and_set(n) {
+                            for o in &owner[x..x + n] {
+                                assert_eq!(o.swap(t, Relaxed), 0);
+                            }
+                            held.push(x..x + n);
+                        }
+                        if !held.is_empty() && (held.len() > 3 || i % 5 == 0) {
+                            let r = held.remove(0);
+                            for o in &owner[r.clone()] {
+                                assert_eq!(o.swap(0, Relaxed), t);
+                            }
+                            v.unset_range(r);
+                        }
+                    }
+                    for r in held {
+                        for o in &owner[r.clone()] {
+                            assert_eq!(o.swap(0, Relaxed), t);
+                        }
+                        v.unset_range(r);
+                    }
+                });
+            }
+        });
+        assert_eq!(v.rem(), v.len());
+        assert!((0..v.len()).all(|i| !v.is_set(i)));
+    }
+
+    #[test]
+    fn atomic_stress_single() {
+        const THREADS: usize = 8;
+        let v = AtomicBitVec::<AtomicU64>::with_count_words(4);
+        let found: Vec<_> = std::thread::scope(|s| {
+            let hs: Vec<_> = (0..THREADS)
+                .map(|_| s.spawn(|| std::iter::from_fn(|| v.find_and_set()).collect::<Vec<_>>()))
+                .collect();
+            hs.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        let mut found = found;
+        found.sort_unstable();
+        assert!(found.into_iter().eq(0..256));
+        assert_eq!(v.rem(), 0);
+    }
+
     #[test]
     #[ignore]
     fn fmt() {
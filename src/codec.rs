@@ -0,0 +1,300 @@
+//! Compact encodings for numeric data.
+//!
+//! This module provides half-precision floats, normalized
+//! integers (SNORM/UNORM) and octahedral unit vectors.
+
+use crate::linear::{Vec2, Vec3};
+
+/// Converts an `f32` into an IEEE 754 binary16 value.
+///
+/// Rounds to nearest, ties to even. Values whose magnitude is
+/// too large become infinities, and NaNs are kept as NaNs.
+pub fn f32_to_f16(x: f32) -> u16 {
+    let bits = x.to_bits();
+    let sign = (bits >> 16 & 0x8000) as u16;
+    let exp = (bits >> 23 & 0xff) as i32;
+    let man = bits & 0x7f_ffff;
+
+    if exp == 0xff {
+        let nan = if man != 0 {
+            0x200 | (man >> 13) as u16
+        } else {
+            0
+        };
+        return sign | 0x7c00 | nan;
+    }
+    let exp = exp - 127 + 15;
+    if exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    let (half, rem, tie) = if exp <= 0 {
+        // Subnormal (or zero) in binary16.
+        if exp < -10 {
+            return sign;
+        }
+        let man = man | 0x80_0000;
+        let shift = (14 - exp) as u32;
+        (man >> shift, man & ((1 << shift) - 1), 1 << (shift - 1))
+    } else {
+        ((exp as u32) << 10 | man >> 13, man & 0x1fff, 0x1000)
+    };
+    // NOTE: A carry out of the mantissa correctly
+    // increments the exponent (possibly into infinity).
+    let half = half + (rem > tie || rem == tie && half & 1 != 0) as u32;
+    sign | half as u16
+}
+
+/// Converts an IEEE 754 binary16 value into an `f32`.
+///
+/// This conversion is exact.
+pub fn f16_to_f32(x: u16) -> f32 {
+    let sign = (x as u32 & 0x8000) << 16;
+    let exp = (x >> 10 & 0x1f) as u32;
+    let man = (x & 0x3ff) as u32;
+    match exp {
+        0 => {
+            let x = man as f32 / (1 << 24) as f32;
+            f32::from_bits(sign | x.to_bits())
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | man << 13),
+        _ => f32::from_bits(sign | (exp + 112) << 23 | man << 13),
+    }
+}
+
+/// Normalized integer.
+///
+/// Signed integers are SNORM values in the range `[-1, 1]`.
+/// Unsigned integers are UNORM values in the range `[0, 1]`.
+pub trait Norm: Copy {
+    /// Whether this is a SNORM type.
+    const SIGNED: bool;
+
+    /// Encodes `x`, clamping it to the representable range.
+    fn encode(x: f32) -> Self;
+
+    /// Decodes `self`.
+    fn decode(self) -> f32;
+}
+
+// NOTE: Primitives only.
+macro_rules! snorm_impl {
+    ($($t:ty),*) => {$(
+        impl Norm for $t {
+            const SIGNED: bool = true;
+
+            fn encode(x: f32) -> Self {
+                (x.clamp(-1.0, 1.0) * <$t>::MAX as f32).round() as $t
+            }
+
+            fn decode(self) -> f32 {
+                // NOTE: `MIN` and `MIN + 1` both map to `-1`.
+                (self as f32 / <$t>::MAX as f32).max(-1.0)
+            }
+        }
+    )*};
+}
+
+snorm_impl!(i8, i16);
+
+// NOTE: Primitives only.
+macro_rules! unorm_impl {
+    ($($t:ty),*) => {$(
+        impl Norm for $t {
+            const SIGNED: bool = false;
+
+            fn encode(x: f32) -> Self {
+                (x.clamp(0.0, 1.0) * <$t>::MAX as f32).round() as $t
+            }
+
+            fn decode(self) -> f32 {
+                self as f32 / <$t>::MAX as f32
+            }
+        }
+    )*};
+}
+
+unorm_impl!(u8, u16);
+
+/// Encodes a direction using an octahedral mapping.
+///
+/// The result is in the range `[-1, 1]`.
+///
+/// NOTE: One must ensure that `dir` is not the zero vector.
+pub fn oct_encode(dir: &Vec3<f32>) -> Vec2<f32> {
+    let sign = |x: f32| if x >= 0.0 { 1.0 } else { -1.0 };
+    let l1 = dir[0].abs() + dir[1].abs() + dir[2].abs();
+    let (x, y) = (dir[0] / l1, dir[1] / l1);
+    if dir[2] >= 0.0 {
+        Vec2::new(x, y)
+    } else {
+        Vec2::new((1.0 - y.abs()) * sign(x), (1.0 - x.abs()) * sign(y))
+    }
+}
+
+/// Decodes a direction from an octahedral mapping.
+///
+/// The result is normalized.
+pub fn oct_decode(enc: &Vec2<f32>) -> Vec3<f32> {
+    let z = 1.0 - enc[0].abs() - enc[1].abs();
+    let t = (-z).max(0.0);
+    let x = enc[0] + if enc[0] >= 0.0 { -t } else { t };
+    let y = enc[1] + if enc[1] >= 0.0 { -t } else { t };
+    Vec3::new(x, y, z).normalize()
+}
+
+/// Encodes a direction using an octahedral mapping that is
+/// stored as normalized integers.
+///
+/// UNORM types map the range `[-1, 1]` into `[0, 1]`.
+///
+/// NOTE: One must ensure that `dir` is not the zero vector.
+pub fn oct_encode_norm<T: Norm>(dir: &Vec3<f32>) -> [T; 2] {
+    let enc = oct_encode(dir);
+    if T::SIGNED {
+        [T::encode(enc[0]), T::encode(enc[1])]
+    } else {
+        [T::encode(enc[0] * 0.5 + 0.5), T::encode(enc[1] * 0.5 + 0.5)]
+    }
+}
+
+/// Decodes a direction from an octahedral mapping that is
+/// stored as normalized integers.
+pub fn oct_decode_norm<T: Norm>(enc: [T; 2]) -> Vec3<f32> {
+    let enc = if T::SIGNED {
+        Vec2::new(enc[0].decode(), enc[1].decode())
+    } else {
+        Vec2::new(enc[0].decode() * 2.0 - 1.0, enc[1].decode() * 2.0 - 1.0)
+    };
+    oct_decode(&enc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f16() {
+        assert_eq!(f32_to_f16(0.0), 0);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(65520.0), 0x7c00);
+        assert_eq!(f32_to_f16(1e9), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+        assert_eq!(f32_to_f16(2f32.powi(-14)), 0x0400);
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(2f32.powi(-25)), 0);
+        assert_eq!(f32_to_f16(2f32.powi(-25) * 1.5), 0x0001);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+
+        // Ties to even.
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+
+        for x in 0..=u16::MAX {
+            let y = f16_to_f32(x);
+            if y.is_nan() {
+                assert!(f16_to_f32(f32_to_f16(y)).is_nan());
+            } else {
+                assert_eq!(f32_to_f16(y), x);
+            }
+        }
+    }
+
+    #[test]
+    fn f16_error() {
+        let mut x = 2f32.powi(-14);
+        while x < 65504.0 {
+            let y = f16_to_f32(f32_to_f16(x));
+            assert!((x - y).abs() <= x * 2f32.powi(-11));
+            x *= 1.0123;
+        }
+    }
+
+    fn norm_round_trip<T: Norm + PartialEq + std::fmt::Debug>(
+        values: impl Iterator<Item = T>,
+        step: f32,
+    ) {
+        for x in values {
+            assert_eq!(T::encode(x.decode()), x);
+        }
+        let lo = if T::SIGNED { -1.0 } else { 0.0 };
+        let mut x = lo;
+        while x <= 1.0 {
+            let y = T::encode(x).decode();
+            assert!((x - y).abs() <= step / 2.0 + f32::EPSILON);
+            x += 0.001;
+        }
+        assert_eq!(T::encode(1.5).decode(), 1.0);
+        assert_eq!(T::encode(-1.5).decode(), lo);
+    }
+
+    #[test]
+    fn snorm() {
+        norm_round_trip(-127..=127i8, 1.0 / 127.0);
+        norm_round_trip(-32767..=32767i16, 1.0 / 32767.0);
+        assert_eq!(i8::MIN.decode(), -1.0);
+        assert_eq!(i16::MIN.decode(), -1.0);
+        assert_eq!(i8::encode(0.5), 64);
+        assert_eq!(i16::encode(-1.0), -32767);
+    }
+
+    #[test]
+    fn unorm() {
+        norm_round_trip(0..=u8::MAX, 1.0 / 255.0);
+        norm_round_trip(0..=u16::MAX, 1.0 / 65535.0);
+        assert_eq!(u8::encode(0.5), 128);
+        assert_eq!(u16::encode(1.0), u16::MAX);
+        assert_eq!(u8::encode(f32::NAN), 0);
+    }
+
+    fn directions() -> impl Iterator<Item = Vec3<f32>> {
+        (0..32).flat_map(|i| {
+            (0..64).map(move |j| {
+                let theta = std::f32::consts::PI * i as f32 / 31.0;
+                let phi = std::f32::consts::TAU * j as f32 / 64.0;
+                Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                )
+            })
+        })
+    }
+
+    #[test]
+    fn oct() {
+        for d in directions() {
+            let e = oct_encode(&d);
+            assert!(e[0].abs() <= 1.0 && e[1].abs() <= 1.0);
+            let r = oct_decode(&e);
+            assert!(d.dot(&r) >= 1.0 - 1e-6);
+        }
+        for (d, e) in [
+            (Vec3::new(0.0, 0.0, 1.0), Vec2::new(0.0, 0.0)),
+            (Vec3::new(1.0, 0.0, 0.0), Vec2::new(1.0, 0.0)),
+            (Vec3::new(0.0, -1.0, 0.0), Vec2::new(0.0, -1.0)),
+            (Vec3::new(0.0, 0.0, -1.0), Vec2::new(1.0, 1.0)),
+        ] {
+            assert_eq!(oct_encode(&d), e);
+            assert_eq!(oct_decode(&e), d);
+        }
+    }
+
+    fn oct_norm_error<T: Norm>(max_deg: f32) {
+        for d in directions() {
+            let r = oct_decode_norm(oct_encode_norm::<T>(&d));
+            assert!((d - r).length() <= max_deg.to_radians());
+        }
+    }
+
+    #[test]
+    fn oct_norm() {
+        oct_norm_error::<i8>(1.5);
+        oct_norm_error::<u8>(1.5);
+        oct_norm_error::<i16>(0.01);
+        oct_norm_error::<u16>(0.01);
+    }
+}
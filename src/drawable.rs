@@ -2,8 +2,9 @@
 
 use std::sync::Arc;
 
+use crate::linear::Mat4;
 use crate::mesh::Mesh;
-use crate::shape::{Bbox, Sphere};
+use crate::shape::{Bbox, Capsule, Frustum, Intersection, Obb, Sphere};
 use crate::skin::Skin;
 
 /// Drawable.
@@ -20,12 +21,72 @@ pub struct Drawable {
 pub enum Shape {
     Bbox(Bbox),
     Sphere(Sphere),
+    Obb(Obb),
+    Capsule(Capsule),
     None,
 }
 
+impl Shape {
+    /// Transforms the shape by `world`.
+    pub fn transform(self, world: &Mat4<f32>) -> Self {
+        match self {
+            Shape::Bbox(x) => Shape::Bbox(x.transform(world)),
+            Shape::Sphere(x) => Shape::Sphere(x.transform(world)),
+            Shape::Obb(x) => Shape::Obb(x.transform(world)),
+            Shape::Capsule(x) => Shape::Capsule(x.transform(world)),
+            Shape::None => Shape::None,
+        }
+    }
+
+    /// Checks whether the shape intersects a frustum after
+    /// being transformed by `world`.
+    ///
+    /// [`Shape::None`] is assumed to intersect any frustum,
+    /// so it is never culled.
+    pub fn intersects_frustum(&self, world: &Mat4<f32>, frustum: &Frustum) -> Intersection {
+        match self.transform(world) {
+            Shape::Bbox(x) => frustum.intersects_bbox(x),
+            Shape::Sphere(x) => frustum.intersects_sphere(x),
+            Shape::Obb(x) => frustum.intersects_obb(x),
+            Shape::Capsule(x) => frustum.intersects_capsule(x),
+            Shape::None => Intersection::Intersecting,
+        }
+    }
+}
+
+impl Shape {
+    /// Computes a shape from the [`Mesh`]'s positions.
+    ///
+    /// This chooses whichever of [`Shape::Bbox`] and
+    /// [`Shape::Sphere`] has the smaller volume.
+    /// It returns [`Shape::None`] if the bounds cannot
+    /// be computed.
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        match mesh.bounds() {
+            Some((bbox, sphere)) => {
+                let e = bbox.half_extent();
+                let r = sphere.radius();
+                if 4.0 / 3.0 * std::f32::consts::PI * r * r * r < 8.0 * e[0] * e[1] * e[2] {
+                    Shape::Sphere(sphere)
+                } else {
+                    Shape::Bbox(bbox)
+                }
+            }
+            None => Shape::None,
+        }
+    }
+}
+
 impl Drawable {
     /// Creates a new drawable.
+    ///
+    /// If `shape` is [`Shape::None`], it is computed from
+    /// the mesh using [`Shape::from_mesh`].
     pub fn new(mesh: Arc<Mesh>, shape: Shape) -> Self {
+        let shape = match shape {
+            Shape::None => Shape::from_mesh(&mesh),
+            x => x,
+        };
         Self {
             mesh,
             shape,
@@ -34,6 +95,10 @@ impl Drawable {
     }
 
     /// Creates a new drawable with a [`Skin`].
+    ///
+    /// NOTE: `shape` is not computed from the mesh when it
+    /// is [`Shape::None`], since skinning deforms the
+    /// mesh's positions.
     pub fn new_skinned(mesh: Arc<Mesh>, shape: Shape, skin: Arc<Skin>) -> Self {
         Self {
             mesh,
@@ -64,6 +129,7 @@ mod tests {
     use super::*;
     use crate::linear::{Mat4, Vec3};
     use crate::mesh::{self, DataType, Semantic, Topology};
+    use crate::shape::DepthRange;
     use crate::skin;
     use std::io;
 
@@ -82,7 +148,13 @@ mod tests {
         crate::init();
         mesh::Builder::new()
             .set_vertex_count(3)
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .push_primitive(Topology::Triangle)
             .unwrap()
@@ -110,7 +182,8 @@ mod tests {
 
         d1.check(&mesh, &bbox, None);
         d2.check(&mesh, &sphere, None);
-        d3.check(&mesh, &Shape::None, None);
+        d3.check(&mesh, &Shape::from_mesh(&mesh), None);
+        assert!(matches!(d3.shape(), Shape::Bbox(_)));
     }
 
     #[test]
@@ -128,4 +201,41 @@ mod tests {
         d2.check(&mesh, &sphere, Some(&skin));
         d3.check(&mesh, &Shape::None, Some(&skin));
     }
+
+    #[test]
+    fn shape_frustum() {
+        let proj = Mat4::perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.5, 50.0);
+        let frustum = Frustum::new(&proj, DepthRange::ZeroToOne);
+        let bbox = Shape::Bbox(Bbox::new(Vec3::from(0.0), Vec3::from(1.0)));
+        let sphere = Shape::Sphere(Sphere::new(Vec3::from(0.0), 1.0));
+        let obb = Shape::Obb(Obb::from(Bbox::new(Vec3::from(0.0), Vec3::from(1.0))));
+        let capsule = Shape::Capsule(Capsule::new(
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(0.0, 0.5, 0.0),
+            0.5,
+        ));
+
+        let world = Mat4::translation(0.0, 0.0, 10.0);
+        for x in [bbox, sphere, obb, capsule] {
+            assert_eq!(x.intersects_frustum(&world, &frustum), Intersection::Inside);
+        }
+        let world = Mat4::translation(0.0, 0.0, -10.0);
+        for x in [bbox, sphere, obb, capsule] {
+            assert_eq!(
+                x.intersects_frustum(&world, &frustum),
+                Intersection::Outside
+            );
+        }
+        let world = Mat4::translation(0.0, 0.0, 50.0) * Mat4::scale(2.0, 2.0, 2.0);
+        for x in [bbox, sphere, obb, capsule] {
+            assert_eq!(
+                x.intersects_frustum(&world, &frustum),
+                Intersection::Intersecting
+            );
+        }
+        assert_eq!(
+            Shape::None.intersects_frustum(&world, &frustum),
+            Intersection::Intersecting
+        );
+    }
 }
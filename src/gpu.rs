@@ -66,6 +66,13 @@ pub enum Id {
     Invalid,
 }
 
+// SAFETY: `Id`s are opaque handles that are only dereferenced
+// by the back-end, which is `Send + Sync` (see `Gpu`). Taking
+// ownership of an `Id` is required to drop it, so it cannot
+// be destroyed while another thread is using it.
+unsafe impl Send for Id {}
+unsafe impl Sync for Id {}
+
 /// GPU texture.
 #[derive(Debug)]
 pub struct TexId(Id);
@@ -111,7 +118,11 @@ pub struct BufOptions {
 ///
 /// NOTE: Keeping this trait private allow us to change it
 /// without breaking non-`gpu` code.
-trait Gpu: fmt::Display + fmt::Debug {
+///
+/// Implementations must be thread-safe, since resources
+/// are created and dropped from any thread (e.g., by mesh
+/// builders that run concurrently).
+trait Gpu: Send + Sync + fmt::Display + fmt::Debug {
     /// Creates a 2D texture.
     ///
     /// This texture must support sampling in shaders and
@@ -187,6 +198,10 @@ trait Gpu: fmt::Display + fmt::Debug {
 /// to be valid if retrieved after a call to [`init`] and before
 /// a call to [`shutdown`]. Attempts to use the `Gpu` outside of
 /// this scope will lead to undefined behavior.
+///
+/// [`IMPL`] is only written by `init` and `shutdown`, so
+/// concurrent calls to this function within that scope only
+/// ever read it.
 fn get<'a>() -> &'a GpuImpl {
     unsafe {
         debug_assert!(IMPL.is_some());
@@ -2,6 +2,10 @@
 //!
 //! NOTE: These layouts are ongoing work and may change at any time.
 
+use crate::linear::{Mat3, Mat4, Vec2};
+use crate::mesh::{Primitive, Semantic};
+use crate::shape::{DepthRange, Ray};
+
 /// Frame-global uniforms.
 ///
 /// These values may differ between frames.
@@ -23,6 +27,37 @@ pub struct FrameU {
     pub _pad: [f32; 8],
 }
 
+impl FrameU {
+    /// Sets the view and projection matrices for camera-relative
+    /// rendering.
+    ///
+    /// `view`'s translation is discarded, since world transforms
+    /// are expected to be relative to the camera's position (see
+    /// [`Graph::world_rel`](crate::node::Graph::world_rel)).
+    pub fn set_view_proj_rel(&mut self, view: &Mat4<f64>, proj: &Mat4<f32>) {
+        let view = Mat4::from(Mat3::from(view.to_f32()));
+        self.view = to_array(&view);
+        self.proj = to_array(proj);
+        self.view_proj = to_array(&(proj * &view));
+    }
+
+    /// Creates a ray from the camera through a point in screen
+    /// coordinates.
+    ///
+    /// `x` and `y` are in pixels, relative to the top-left corner
+    /// of the viewport defined by `self.x`, `self.y`, `self.width`
+    /// and `self.height`. As in Vulkan's clip space, `y` points
+    /// downwards.
+    pub fn unproject(&self, x: f32, y: f32, depth: DepthRange) -> Ray {
+        let ndc = Vec2::new(
+            (x - self.x) / self.width * 2.0 - 1.0,
+            (y - self.y) / self.height * 2.0 - 1.0,
+        );
+        let [c0, c1, c2, c3] = self.view_proj;
+        Ray::unproject(ndc, &Mat4::new(c0, c1, c2, c3).invert(), depth)
+    }
+}
+
 /// Light source uniforms.
 ///
 /// The actual uniform is an array of [`LightU`].
@@ -85,6 +120,25 @@ pub struct DrawableU {
     pub _pad: [f32; 30],
 }
 
+impl DrawableU {
+    /// Creates new drawable uniforms.
+    ///
+    /// The normal matrix is computed from `world`.
+    ///
+    /// NOTE: When rendering relative to the camera, `world`
+    /// must be rebased before being passed here.
+    pub fn new(world: &Mat4<f32>, id: u32, flags: u32) -> Self {
+        let normal = Mat4::from(Mat3::from(world).invert().transpose());
+        Self {
+            world: to_array(world),
+            normal: to_array(&normal),
+            id,
+            flags,
+            _pad: Default::default(),
+        }
+    }
+}
+
 /// Material uniforms.
 ///
 /// These values may differ between draw calls.
@@ -141,10 +195,64 @@ pub struct JointU {
     pub normal: [[f32; 4]; 4],
 }
 
+/// Morph target uniforms.
+///
+/// The actual uniform is an array of [`MorphU`], with one
+/// element for each morph target that is blended.
+///
+/// These values may differ between draw calls.
+#[derive(Copy, Clone, Debug)]
+#[repr(C, align(16))]
+pub struct MorphU {
+    pub weight: f32,
+    /// Offsets of the target's displacements in the vertex
+    /// buffer, in `f32`s.
+    pub position: u32,
+    pub normal: u32,
+    pub tangent: u32,
+}
+
+impl MorphU {
+    /// [`MorphU`] offset value identifying a semantic
+    /// that the target does not displace.
+    pub const NONE: u32 = u32::MAX;
+
+    /// Creates morph target uniforms for blending the
+    /// targets of `prim` with the given `weights`.
+    ///
+    /// Targets whose weight is zero are skipped, and so
+    /// are targets beyond the length of `weights`.
+    pub fn new(prim: &Primitive, weights: &[f32]) -> Vec<Self> {
+        (weights.iter().take(prim.morph_target_count()))
+            .enumerate()
+            .filter(|(_, &w)| w != 0.0)
+            .map(|(i, &weight)| {
+                let offset = |sem| {
+                    prim.morph_data(i, sem)
+                        .map_or(Self::NONE, |x| (x.offset() / 4) as u32)
+                };
+                Self {
+                    weight,
+                    position: offset(Semantic::Position),
+                    normal: offset(Semantic::Normal),
+                    tangent: offset(Semantic::Tangent),
+                }
+            })
+            .collect()
+    }
+}
+
+fn to_array(m: &Mat4<f32>) -> [[f32; 4]; 4] {
+    [m[0].into(), m[1].into(), m[2].into(), m[3].into()]
+}
+
 #[cfg(test)]
 mod tests {
     use std::mem;
 
+    use crate::linear::Vec3;
+    use crate::mesh::{Builder, DataType, Topology};
+
     use super::*;
 
     #[test]
@@ -166,5 +274,107 @@ mod tests {
 
         assert_eq!(mem::size_of::<JointU>(), 128);
         assert_eq!(mem::align_of::<JointU>(), 16);
+
+        assert_eq!(mem::size_of::<MorphU>(), 16);
+        assert_eq!(mem::align_of::<MorphU>(), 16);
+    }
+
+    #[test]
+    fn drawable_new() {
+        let w = Mat4::translation(1.0, 2.0, 3.0) * Mat4::scale(2.0, 4.0, 0.5);
+        let u = DrawableU::new(&w, 7, 1);
+        assert_eq!(u.world, to_array(&w));
+        assert_eq!(
+            u.normal,
+            [
+                [0.5, 0.0, 0.0, 0.0],
+                [0.0, 0.25, 0.0, 0.0],
+                [0.0, 0.0, 2.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+            ]
+        );
+        assert_eq!((u.id, u.flags), (7, 1));
+    }
+
+    #[test]
+    fn frame_unproject() {
+        let mut u: FrameU = unsafe { mem::zeroed() };
+        let proj = Mat4::perspective(std::f32::consts::FRAC_PI_2, 2.0, 0.5, 100.0);
+        u.view_proj = to_array(&(proj * Mat4::translation(0.0, 0.0, -1.0)));
+        (u.x, u.y, u.width, u.height) = (10.0, 20.0, 200.0, 100.0);
+
+        let ray = u.unproject(110.0, 70.0, DepthRange::ZeroToOne);
+        assert!((ray.origin() - Vec3::new(0.0, 0.0, 1.5)).length() <= 1e-5);
+        assert!((ray.dir() - Vec3::new(0.0, 0.0, 1.0)).length() <= 1e-5);
+
+        let ray = u.unproject(10.0, 120.0, DepthRange::ZeroToOne);
+        let dir = Vec3::new(-2.0, 1.0, 1.0).normalize();
+        assert!((ray.dir() - dir).length() <= 1e-5);
+    }
+
+    #[test]
+    fn frame_view_proj_rel() {
+        let mut u: FrameU = unsafe { mem::zeroed() };
+        let view = Mat4::<f64>::rotation_y(0.5) * Mat4::translation(-1e7, 2e6, 0.0);
+        let proj = Mat4::perspective(1.0, 1.5, 0.1, 100.0);
+        u.set_view_proj_rel(&view, &proj);
+        let r = Mat4::<f64>::rotation_y(0.5).to_f32();
+        assert_eq!(u.view, to_array(&r));
+        assert_eq!(u.proj, to_array(&proj));
+        assert_eq!(u.view_proj, to_array(&(proj * r)));
+    }
+
+    #[test]
+    fn morph_new() {
+        crate::init();
+        let bytes = |x: &[f32]| -> Vec<u8> { x.iter().flat_map(|x| x.to_ne_bytes()).collect() };
+        let pos = bytes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        let d = bytes(&[0.0, 0.0, 1.0].repeat(3));
+        let mut bld = Builder::new();
+        bld.set_vertex_count(3)
+            .set_semantic(&pos[..], Semantic::Position, DataType::F32x3, false, None)
+            .unwrap()
+            .set_morph_target(&d[..], Semantic::Position, DataType::F32x3, false)
+            .unwrap()
+            .push_morph_target()
+            .unwrap()
+            .set_morph_target(&d[..], Semantic::Normal, DataType::F32x3, false)
+            .unwrap()
+            .set_morph_target(&d[..], Semantic::Tangent, DataType::F32x3, false)
+            .unwrap()
+            .push_morph_target()
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap();
+        let mesh = bld.create().unwrap();
+        let prim = &mesh.primitives()[0];
+        let offset = |i, sem| (prim.morph_data(i, sem).unwrap().offset() / 4) as u32;
+
+        let u = MorphU::new(prim, &[0.5, -2.0]);
+        assert_eq!(u.len(), 2);
+        assert_eq!(u[0].weight, 0.5);
+        assert_eq!(u[0].position, offset(0, Semantic::Position));
+        assert_eq!((u[0].normal, u[0].tangent), (MorphU::NONE, MorphU::NONE));
+        assert_eq!(u[1].weight, -2.0);
+        assert_eq!(u[1].position, MorphU::NONE);
+        assert_eq!(u[1].normal, offset(1, Semantic::Normal));
+        assert_eq!(u[1].tangent, offset(1, Semantic::Tangent));
+
+        // Zero weights are skipped.
+        let u = MorphU::new(prim, &[0.0, 1.0]);
+        assert_eq!(u.len(), 1);
+        assert_eq!(
+            (u[0].weight, u[0].normal),
+            (1.0, offset(1, Semantic::Normal))
+        );
+        assert!(MorphU::new(prim, &[0.0, 0.0]).is_empty());
+
+        // So are targets beyond the length of `weights`,
+        // and weights beyond the number of targets.
+        let u = MorphU::new(prim, &[1.0]);
+        assert_eq!(u.len(), 1);
+        assert_eq!(u[0].position, offset(0, Semantic::Position));
+        assert_eq!(MorphU::new(prim, &[1.0, 1.0, 1.0]).len(), 2);
+        assert!(MorphU::new(prim, &[]).is_empty());
     }
 }
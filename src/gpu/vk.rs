@@ -54,6 +54,14 @@ pub(super) struct Impl {
     queue: (Queue, u32),
 }
 
+// SAFETY: `Impl` is not mutated after creation. Its methods
+// only call Vulkan commands whose externally synchronized
+// parameters are objects owned by the `Id` given to or
+// returned from the method. `queue`, which would need
+// external synchronization, is not used by any of them.
+unsafe impl Send for Impl {}
+unsafe impl Sync for Impl {}
+
 impl Impl {
     /// Creates a new `Impl`.
     pub fn new() -> Option<Self> {
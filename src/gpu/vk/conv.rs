@@ -205,6 +205,10 @@ pub(super) fn from_compare_fn(compare: Compare) -> CompareOp {
 
 /// Converts from a [`DataType`] into a [`vk_sys::Format`].
 ///
+/// If `normalized` is `true`, 8- and 16-bit integer types
+/// are converted into `*_SNORM`/`*_UNORM` formats.
+/// It has no effect on other types.
+///
 /// NOTE: The following conversions generate [`vk_sys::Format`]s that
 /// may not support vertex buffer usage:
 ///
@@ -212,7 +216,7 @@ pub(super) fn from_compare_fn(compare: Compare) -> CompareOp {
 /// - [`DataType::U16x3`]
 /// - [`DataType::I8x3`]
 /// - [`DataType::U8x3`]
-pub(super) fn from_data_type(data_type: DataType) -> vk_sys::Format {
+pub(super) fn from_data_type(data_type: DataType, normalized: bool) -> vk_sys::Format {
     match data_type {
         DataType::F32 => vk_sys::FORMAT_R32_SFLOAT,
         DataType::F32x2 => vk_sys::FORMAT_R32G32_SFLOAT,
@@ -226,6 +230,22 @@ pub(super) fn from_data_type(data_type: DataType) -> vk_sys::Format {
         DataType::U32x2 => vk_sys::FORMAT_R32G32_UINT,
         DataType::U32x3 => vk_sys::FORMAT_R32G32B32_UINT,
         DataType::U32x4 => vk_sys::FORMAT_R32G32B32A32_UINT,
+        DataType::I16 if normalized => vk_sys::FORMAT_R16_SNORM,
+        DataType::I16x2 if normalized => vk_sys::FORMAT_R16G16_SNORM,
+        DataType::I16x3 if normalized => vk_sys::FORMAT_R16G16B16_SNORM,
+        DataType::I16x4 if normalized => vk_sys::FORMAT_R16G16B16A16_SNORM,
+        DataType::U16 if normalized => vk_sys::FORMAT_R16_UNORM,
+        DataType::U16x2 if normalized => vk_sys::FORMAT_R16G16_UNORM,
+        DataType::U16x3 if normalized => vk_sys::FORMAT_R16G16B16_UNORM,
+        DataType::U16x4 if normalized => vk_sys::FORMAT_R16G16B16A16_UNORM,
+        DataType::I8 if normalized => vk_sys::FORMAT_R8_SNORM,
+        DataType::I8x2 if normalized => vk_sys::FORMAT_R8G8_SNORM,
+        DataType::I8x3 if normalized => vk_sys::FORMAT_R8G8B8_SNORM,
+        DataType::I8x4 if normalized => vk_sys::FORMAT_R8G8B8A8_SNORM,
+        DataType::U8 if normalized => vk_sys::FORMAT_R8_UNORM,
+        DataType::U8x2 if normalized => vk_sys::FORMAT_R8G8_UNORM,
+        DataType::U8x3 if normalized => vk_sys::FORMAT_R8G8B8_UNORM,
+        DataType::U8x4 if normalized => vk_sys::FORMAT_R8G8B8A8_UNORM,
         DataType::I16 => vk_sys::FORMAT_R16_SINT,
         DataType::I16x2 => vk_sys::FORMAT_R16G16_SINT,
         DataType::I16x3 => vk_sys::FORMAT_R16G16B16_SINT,
@@ -6,6 +6,7 @@ use std::hint;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub mod animation;
+pub mod codec;
 pub mod drawable;
 pub mod light;
 pub mod linear;
@@ -78,6 +79,16 @@ pub fn shutdown() {
     }
 }
 
+/// Creates a pseudo-random generator of values in `[0, 1)`
+/// for tests.
+#[cfg(test)]
+fn rand_gen(mut seed: u32) -> impl FnMut() -> f32 {
+    move || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::Ordering;
@@ -11,7 +11,16 @@ mod matn;
 pub use crate::linear::matn::{Mat2, Mat3, Mat4};
 
 mod quat;
-pub use crate::linear::quat::Quat;
+pub use crate::linear::quat::{EulerOrder, Quat};
+
+mod affine;
+pub use crate::linear::affine::Affine3;
+
+#[cfg(any(
+    all(target_arch = "x86_64", target_feature = "sse"),
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
+mod simd;
 
 #[cfg(test)]
 mod tests;
@@ -34,6 +43,30 @@ pub trait Scalar:
 {
     const ZERO: Self;
     const ONE: Self;
+
+    // NOTE: The `mat4_*` functions let a primitive replace the
+    // generic `Mat4` code with explicit SIMD instructions.
+    // They are not meant to be called directly.
+
+    #[doc(hidden)]
+    fn mat4_mul(a: &Mat4<Self>, b: &Mat4<Self>) -> Mat4<Self> {
+        a.mul_generic(b)
+    }
+
+    #[doc(hidden)]
+    fn mat4_mul_vec(m: &Mat4<Self>, v: &Vec4<Self>) -> Vec4<Self> {
+        m.mul_vec_generic(v)
+    }
+
+    #[doc(hidden)]
+    fn mat4_transpose(m: &Mat4<Self>) -> Mat4<Self> {
+        m.transpose_generic()
+    }
+
+    #[doc(hidden)]
+    fn mat4_det(m: &Mat4<Self>) -> Self {
+        m.det_generic()
+    }
 }
 
 // NOTE: Primitives only.
@@ -61,19 +94,71 @@ pub trait Float: Scalar + Neg<Output = Self> {
     fn cos(self) -> Self;
     fn sin(self) -> Self;
     fn tan(self) -> Self;
+    fn acos(self) -> Self;
+    fn asin(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+
+    #[doc(hidden)]
+    fn mat4_invert(m: &Mat4<Self>) -> Mat4<Self> {
+        m.invert_generic()
+    }
+}
+
+// NOTE: Overrides the `mat4_*` hooks for primitives that
+// have a `simd` implementation.
+#[cfg(any(
+    all(target_arch = "x86_64", target_feature = "sse"),
+    all(target_arch = "aarch64", target_feature = "neon")
+))]
+macro_rules! simd_hooks {
+    (Scalar, f32) => {
+        fn mat4_mul(a: &Mat4<f32>, b: &Mat4<f32>) -> Mat4<f32> {
+            simd::mul(a, b)
+        }
+
+        fn mat4_mul_vec(m: &Mat4<f32>, v: &Vec4<f32>) -> Vec4<f32> {
+            simd::mul_vec(m, v)
+        }
+
+        fn mat4_transpose(m: &Mat4<f32>) -> Mat4<f32> {
+            simd::transpose(m)
+        }
+
+        fn mat4_det(m: &Mat4<f32>) -> f32 {
+            simd::det(m)
+        }
+    };
+    (Float, f32) => {
+        fn mat4_invert(m: &Mat4<f32>) -> Mat4<f32> {
+            simd::invert(m)
+        }
+    };
+    ($_:ident, $__:ident) => {};
+}
+
+#[cfg(not(any(
+    all(target_arch = "x86_64", target_feature = "sse"),
+    all(target_arch = "aarch64", target_feature = "neon")
+)))]
+macro_rules! simd_hooks {
+    ($_:ident, $__:ident) => {};
 }
 
 // NOTE: Primitives only.
 macro_rules! noninteger_impl {
-    ($($t:ty),*) => {$(
+    ($($t:ident),*) => {$(
         impl Scalar for $t {
             const ZERO: $t = 0.0;
             const ONE: $t = 1.0;
+
+            simd_hooks!(Scalar, $t);
         }
 
         impl Float for $t {
             const EPSILON: $t = <$t>::EPSILON;
 
+            simd_hooks!(Float, $t);
+
             fn round(self) -> Self {
                 self.round()
             }
@@ -109,6 +194,18 @@ macro_rules! noninteger_impl {
             fn tan(self) -> Self {
                 self.tan()
             }
+
+            fn acos(self) -> Self {
+                self.acos()
+            }
+
+            fn asin(self) -> Self {
+                self.asin()
+            }
+
+            fn atan2(self, other: Self) -> Self {
+                self.atan2(other)
+            }
         }
     )*};
 }
@@ -0,0 +1,204 @@
+use std::ops::{Index, IndexMut, Mul, MulAssign};
+
+use crate::linear::{Float, Mat3, Mat4, Scalar, Vec3};
+
+/// Column-major 3x4 affine transform.
+///
+/// This is equivalent to a `Mat4` whose last row is `[0, 0, 0, 1]`.
+/// The first three columns are the linear part and the last
+/// column is the translation.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct Affine3<T>([Vec3<T>; 4]);
+
+impl<T> Affine3<T> {
+    /// Creates a new `Affine3`.
+    pub fn new(col0: [T; 3], col1: [T; 3], col2: [T; 3], col3: [T; 3]) -> Self {
+        Self([
+            Vec3::from(col0),
+            Vec3::from(col1),
+            Vec3::from(col2),
+            Vec3::from(col3),
+        ])
+    }
+}
+
+impl<T> Index<usize> for Affine3<T> {
+    type Output = Vec3<T>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl<T> IndexMut<usize> for Affine3<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl<T: Scalar> Affine3<T> {
+    /// Computes `self * other`.
+    fn compose(&self, other: &Self) -> Self {
+        let mut m = Self::default();
+        for i in 0..4 {
+            for j in 0..3 {
+                m[i][j] =
+                    self[0][j] * other[i][0] + self[1][j] * other[i][1] + self[2][j] * other[i][2];
+            }
+        }
+        for j in 0..3 {
+            m[3][j] += self[3][j];
+        }
+        m
+    }
+
+    /// Transforms a point (i.e., a vector whose homogeneous
+    /// coordinate is one).
+    pub fn transform_point(&self, point: &Vec3<T>) -> Vec3<T> {
+        self.transform_vector(point) + self[3]
+    }
+
+    /// Transforms a direction (i.e., a vector whose homogeneous
+    /// coordinate is zero).
+    pub fn transform_vector(&self, vector: &Vec3<T>) -> Vec3<T> {
+        let mut v = Vec3::default();
+        for i in 0..3 {
+            v[i] = self[0][i] * vector[0] + self[1][i] * vector[1] + self[2][i] * vector[2];
+        }
+        v
+    }
+
+    /// Returns the linear part.
+    pub fn linear(&self) -> Mat3<T> {
+        Mat3::new(self[0].into(), self[1].into(), self[2].into())
+    }
+
+    /// Returns the translation.
+    pub fn translation(&self) -> Vec3<T> {
+        self[3]
+    }
+}
+
+impl<T: Float> Affine3<T> {
+    /// Computes the inverse.
+    ///
+    /// This is cheaper than inverting the equivalent `Mat4`.
+    ///
+    /// NOTE: One must ensure that `self` is invertible.
+    #[must_use]
+    pub fn invert(&self) -> Self {
+        let l = self.linear().invert();
+        let t = -(l * self[3]);
+        Self([l[0], l[1], l[2], t])
+    }
+}
+
+impl Affine3<f32> {
+    /// Converts into a double-precision transform.
+    pub fn to_f64(&self) -> Affine3<f64> {
+        Affine3(self.0.map(|x| x.to_f64()))
+    }
+}
+
+impl Affine3<f64> {
+    /// Converts into a single-precision transform.
+    ///
+    /// NOTE: This conversion is lossy.
+    pub fn to_f32(&self) -> Affine3<f32> {
+        Affine3(self.0.map(|x| x.to_f32()))
+    }
+}
+
+impl<T: Scalar> Mul for &Affine3<T> {
+    type Output = Affine3<T>;
+
+    fn mul(self, other: Self) -> Self::Output {
+        self.compose(other)
+    }
+}
+
+impl<T: Scalar> Mul for Affine3<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        self.compose(&other)
+    }
+}
+
+impl<T: Scalar> MulAssign<&Affine3<T>> for Affine3<T> {
+    fn mul_assign(&mut self, other: &Self) {
+        *self = self.compose(other);
+    }
+}
+
+impl<T: Scalar> MulAssign for Affine3<T> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = self.compose(&other);
+    }
+}
+
+// NOTE: `Scalar` bounded due to type inference.
+impl<T: Scalar> From<T> for Affine3<T> {
+    /// Converts a scalar into a transform whose linear part is
+    /// a diagonal matrix containing copies of such scalar.
+    /// The translation is set to zero.
+    ///
+    /// This conversion can be used to create identity transforms
+    /// with the convenience of type inference.
+    fn from(diag: T) -> Self {
+        Self::from(&Mat3::from(diag))
+    }
+}
+
+impl<T: Scalar> From<&Mat3<T>> for Affine3<T> {
+    /// Converts a `&Mat3<T>` into an `Affine3<T>` with no translation.
+    fn from(linear: &Mat3<T>) -> Self {
+        Self([linear[0], linear[1], linear[2], Vec3::default()])
+    }
+}
+
+impl<T: Scalar> From<Mat3<T>> for Affine3<T> {
+    /// Converts a `Mat3<T>` into an `Affine3<T>` with no translation.
+    fn from(linear: Mat3<T>) -> Self {
+        Self::from(&linear)
+    }
+}
+
+impl<T: Copy> From<&Mat4<T>> for Affine3<T> {
+    /// Converts a `&Mat4<T>` into an `Affine3<T>`, discarding the last row.
+    fn from(m: &Mat4<T>) -> Self {
+        Self([
+            Vec3::from(m[0]),
+            Vec3::from(m[1]),
+            Vec3::from(m[2]),
+            Vec3::from(m[3]),
+        ])
+    }
+}
+
+impl<T: Copy> From<Mat4<T>> for Affine3<T> {
+    /// Converts a `Mat4<T>` into an `Affine3<T>`, discarding the last row.
+    fn from(m: Mat4<T>) -> Self {
+        Self::from(&m)
+    }
+}
+
+impl<T: Scalar> From<&Affine3<T>> for Mat4<T> {
+    /// Converts a `&Affine3<T>` into an homogeneous `Mat4<T>`.
+    fn from(m: &Affine3<T>) -> Self {
+        let col = |v: &Vec3<T>, w| [v[0], v[1], v[2], w];
+        Mat4::new(
+            col(&m[0], T::ZERO),
+            col(&m[1], T::ZERO),
+            col(&m[2], T::ZERO),
+            col(&m[3], T::ONE),
+        )
+    }
+}
+
+impl<T: Scalar> From<Affine3<T>> for Mat4<T> {
+    /// Converts an `Affine3<T>` into an homogeneous `Mat4<T>`.
+    fn from(m: Affine3<T>) -> Self {
+        Self::from(&m)
+    }
+}
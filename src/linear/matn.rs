@@ -1,5 +1,3 @@
-// TODO: These could use explicit SIMD instructions.
-
 use std::mem;
 use std::ops::{Add, AddAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
 
@@ -271,7 +269,83 @@ macro_rules! mul_impl {
 
 mul_impl!(Mat2<T>, Vec2<T>, 2);
 mul_impl!(Mat3<T>, Vec3<T>, 3);
-mul_impl!(Mat4<T>, Vec4<T>, 4);
+
+// NOTE: `Mat4` products go through `Scalar` so primitives
+// can provide explicit SIMD code.
+
+impl<T: Scalar> Mul for &Mat4<T> {
+    type Output = Mat4<T>;
+
+    fn mul(self, other: Self) -> Self::Output {
+        T::mat4_mul(self, other)
+    }
+}
+
+impl<T: Scalar> Mul for Mat4<T> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        T::mat4_mul(&self, &other)
+    }
+}
+
+impl<T: Scalar> Mul<&Vec4<T>> for &Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, vector: &Vec4<T>) -> Self::Output {
+        T::mat4_mul_vec(self, vector)
+    }
+}
+
+impl<T: Scalar> Mul<Vec4<T>> for &Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, vector: Vec4<T>) -> Self::Output {
+        T::mat4_mul_vec(self, &vector)
+    }
+}
+
+impl<T: Scalar> Mul<&Vec4<T>> for Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, vector: &Vec4<T>) -> Self::Output {
+        T::mat4_mul_vec(&self, vector)
+    }
+}
+
+impl<T: Scalar> Mul<Vec4<T>> for Mat4<T> {
+    type Output = Vec4<T>;
+
+    fn mul(self, vector: Vec4<T>) -> Self::Output {
+        T::mat4_mul_vec(&self, &vector)
+    }
+}
+
+impl<T: Scalar> Mat4<T> {
+    /// Generic `Mat4` product.
+    pub(super) fn mul_generic(&self, other: &Self) -> Self {
+        let mut m = Self::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                for k in 0..4 {
+                    m[i][j] += self[k][j] * other[i][k];
+                }
+            }
+        }
+        m
+    }
+
+    /// Generic `Mat4`/`Vec4` product.
+    pub(super) fn mul_vec_generic(&self, vector: &Vec4<T>) -> Vec4<T> {
+        let mut v = Vec4::default();
+        for i in 0..4 {
+            for j in 0..4 {
+                v[i] += self[j][i] * vector[j];
+            }
+        }
+        v
+    }
+}
 
 macro_rules! mul_assign_impl {
     ($t:ty) => {
@@ -293,7 +367,18 @@ macro_rules! mul_assign_impl {
 
 mul_assign_impl!(Mat2<T>);
 mul_assign_impl!(Mat3<T>);
-mul_assign_impl!(Mat4<T>);
+
+impl<T: Scalar> MulAssign<&Mat4<T>> for Mat4<T> {
+    fn mul_assign(&mut self, other: &Self) {
+        *self = &*self * other;
+    }
+}
+
+impl<T: Scalar> MulAssign for Mat4<T> {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
 
 macro_rules! neg_impl {
     ($t:ty) => {
@@ -349,7 +434,27 @@ macro_rules! transpose_impl {
 
 transpose_impl!(Mat2<T>, 2);
 transpose_impl!(Mat3<T>, 3);
-transpose_impl!(Mat4<T>, 4);
+
+impl<T: Scalar> Mat4<T> {
+    /// Computes the transpose.
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        T::mat4_transpose(self)
+    }
+
+    /// Generic `Mat4::transpose`.
+    pub(super) fn transpose_generic(&self) -> Self {
+        let mut m = Self::default();
+        for i in 0..4 {
+            m[i][i] = self[i][i];
+            for j in i + 1..4 {
+                m[i][j] = self[j][i];
+                m[j][i] = self[i][j];
+            }
+        }
+        m
+    }
+}
 
 impl<T: Scalar> Mat2<T> {
     /// Computes the determinant.
@@ -378,6 +483,11 @@ impl<T: Scalar> Mat3<T> {
 impl<T: Scalar> Mat4<T> {
     /// Computes the determinant.
     pub fn det(&self) -> T {
+        T::mat4_det(self)
+    }
+
+    /// Generic `Mat4::det`.
+    pub(super) fn det_generic(&self) -> T {
         let m00 = self[0][0];
         let m01 = self[0][1];
         let m02 = self[0][2];
@@ -465,6 +575,11 @@ impl<T: Float> Mat4<T> {
     /// NOTE: One must ensure that `self` is invertible.
     #[must_use]
     pub fn invert(&self) -> Self {
+        T::mat4_invert(self)
+    }
+
+    /// Generic `Mat4::invert`.
+    pub(super) fn invert_generic(&self) -> Self {
         let m00 = self[0][0];
         let m01 = self[0][1];
         let m02 = self[0][2];
@@ -843,6 +958,28 @@ conv_impl!(Mat2<T>, Vec2<T>, 2);
 conv_impl!(Mat3<T>, Vec3<T>, 3);
 conv_impl!(Mat4<T>, Vec4<T>, 4);
 
+macro_rules! precision_impl {
+    ($($m:ident),*) => {$(
+        impl $m<f32> {
+            /// Converts into a double-precision matrix.
+            pub fn to_f64(&self) -> $m<f64> {
+                $m(self.0.map(|x| x.to_f64()))
+            }
+        }
+
+        impl $m<f64> {
+            /// Converts into a single-precision matrix.
+            ///
+            /// NOTE: This conversion is lossy.
+            pub fn to_f32(&self) -> $m<f32> {
+                $m(self.0.map(|x| x.to_f32()))
+            }
+        }
+    )*};
+}
+
+precision_impl!(Mat2, Mat3, Mat4);
+
 impl<T: Scalar> From<&Mat3<T>> for Mat4<T> {
     /// Converts a `&Mat3<T>` into an homogeneous `Mat4<T>`.
     fn from(upper_left: &Mat3<T>) -> Self {
@@ -931,6 +1068,49 @@ impl<T: Float> Mat4<T> {
         Self::scale(s[0], s[1], s[2])
     }
 
+    /// Decomposes a matrix into TRS properties.
+    ///
+    /// Negative scale is reported as a negation of all three
+    /// scale factors (a reflection through the origin).
+    ///
+    /// It returns `None` if the matrix cannot be expressed as
+    /// a TRS transform, which is the case when it is singular,
+    /// has shear or is not affine.
+    pub fn decompose(&self) -> Option<(Vec3<T>, Quat<T>, Vec3<T>)> {
+        if self[0][3] != T::ZERO
+            || self[1][3] != T::ZERO
+            || self[2][3] != T::ZERO
+            || self[3][3] != T::ONE
+        {
+            return None;
+        }
+        let mut ul = Mat3::from(self);
+        let mut s = Vec3::new(ul[0].length(), ul[1].length(), ul[2].length());
+        if s[0] <= T::EPSILON || s[1] <= T::EPSILON || s[2] <= T::EPSILON {
+            return None;
+        }
+        let det = ul.det();
+        if det.abs() <= T::EPSILON * s[0] * s[1] * s[2] {
+            return None;
+        }
+        if det < T::ZERO {
+            s = -s;
+        }
+        ul[0] /= s[0];
+        ul[1] /= s[1];
+        ul[2] /= s[2];
+        // NOTE: This tolerance must account for error
+        // accumulated while composing the matrix.
+        let tol = T::EPSILON.sqrt();
+        if ul[0].dot(&ul[1]).abs() > tol
+            || ul[0].dot(&ul[2]).abs() > tol
+            || ul[1].dot(&ul[2]).abs() > tol
+        {
+            return None;
+        }
+        Some((Vec3::from(self[3]), Quat::rotation_m(&ul), s))
+    }
+
     /// Decomposes a matrix into TRS properties.
     pub fn into_trs(&self) -> (Vec3<T>, Quat<T>, Vec3<T>) {
         let mut ul = Mat3::from(self);
@@ -1,4 +1,4 @@
-use std::ops::{Add, Mul, MulAssign, Sub};
+use std::ops::{Add, Mul, MulAssign, Neg, Sub};
 
 use crate::linear::{Float, Mat3, Vec3, Vec4};
 
@@ -6,6 +6,36 @@ use crate::linear::{Float, Mat3, Vec3, Vec4};
 #[derive(Copy, Clone, Default, PartialEq, Debug)]
 pub struct Quat<T>(Vec3<T>, T);
 
+/// Order in which Euler angles are applied.
+///
+/// Rotations are about the fixed axes. `Xyz` rotates about the
+/// x axis first, then about the y axis and lastly about the z
+/// axis (i.e., `rz * ry * rx`).
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EulerOrder {
+    Xyz,
+    Xzy,
+    Yxz,
+    Yzx,
+    Zxy,
+    Zyx,
+}
+
+impl EulerOrder {
+    /// Returns the axes in the order they are applied, and
+    /// whether such order is a cyclic permutation of `xyz`.
+    fn axes(self) -> (usize, usize, usize, bool) {
+        match self {
+            EulerOrder::Xyz => (0, 1, 2, true),
+            EulerOrder::Xzy => (0, 2, 1, false),
+            EulerOrder::Yxz => (1, 0, 2, false),
+            EulerOrder::Yzx => (1, 2, 0, true),
+            EulerOrder::Zxy => (2, 0, 1, true),
+            EulerOrder::Zyx => (2, 1, 0, false),
+        }
+    }
+}
+
 impl<T: Copy> Quat<T> {
     /// Creates a new quaternion from a vector (imaginary part) and
     /// a value (real part).
@@ -79,6 +109,37 @@ where
     }
 }
 
+impl<T: Copy + Neg<Output = T>> Neg for &Quat<T> {
+    type Output = Quat<T>;
+
+    fn neg(self) -> Self::Output {
+        Quat(-self.0, -self.1)
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Neg for Quat<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0, -self.1)
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Quat<T> {
+    /// Computes the conjugate.
+    #[must_use]
+    pub fn conjugate(&self) -> Self {
+        Self(-self.0, self.1)
+    }
+}
+
+impl<T: Copy + Default + Add<Output = T> + Mul<Output = T>> Quat<T> {
+    /// Computes the dot product.
+    pub fn dot(&self, other: &Self) -> T {
+        self.0.dot(&other.0) + self.1 * other.1
+    }
+}
+
 impl<T: Float> Quat<T> {
     /// Creates a new quaternion encoding a rotation about an arbitrary axis.
     pub fn rotation(angle: T, axis: &Vec3<T>) -> Self {
@@ -104,23 +165,12 @@ impl<T: Float> Quat<T> {
                 )
             }
             _ => {
-                let (i, j, k) = if diag[0] > diag[1] {
-                    if diag[1] > diag[2] {
-                        (0, 1, 2)
-                    } else if diag[2] > diag[0] {
-                        (2, 0, 1)
-                    } else {
-                        (0, 2, 1)
-                    }
-                } else if diag[1] > diag[2] {
-                    if diag[2] > diag[0] {
-                        (1, 2, 0)
-                    } else {
-                        (1, 0, 2)
-                    }
-                } else {
-                    (2, 1, 0)
-                };
+                // NOTE: `(i, j, k)` must be a cyclic permutation,
+                // otherwise the real part changes sign.
+                let i = if diag[1] > diag[0] { 1 } else { 0 };
+                let i = if diag[2] > diag[i] { 2 } else { i };
+                let j = (i + 1) % 3;
+                let k = (i + 2) % 3;
                 let s = (diag[i] - (diag[j] + diag[k]) + T::ONE).sqrt();
                 debug_assert!(s.abs() > T::EPSILON);
                 let is = T::ONE / (s + s);
@@ -158,6 +208,150 @@ impl<T: Float> Quat<T> {
     }
 }
 
+impl<T: Float> Quat<T> {
+    /// Computes the quaternion's length.
+    pub fn length(&self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns a new unit quaternion.
+    ///
+    /// NOTE: One must ensure that `self.length()` is greater than zero.
+    #[must_use]
+    pub fn normalize(&self) -> Self {
+        let ilen = T::ONE / self.length();
+        Self(self.0 * ilen, self.1 * ilen)
+    }
+
+    /// Computes the inverse.
+    ///
+    /// NOTE: One must ensure that `self.length()` is greater than zero.
+    #[must_use]
+    pub fn invert(&self) -> Self {
+        let inorm = T::ONE / self.dot(self);
+        Self(-self.0 * inorm, self.1 * inorm)
+    }
+
+    /// Rotates a vector.
+    ///
+    /// NOTE: `self` is expected to be a unit quaternion.
+    pub fn rotate(&self, vector: &Vec3<T>) -> Vec3<T> {
+        let t = self.0.cross(vector) * (T::ONE + T::ONE);
+        *vector + t * self.1 + self.0.cross(&t)
+    }
+
+    /// Interpolates linearly between `self` and `other`, then normalizes
+    /// the result.
+    ///
+    /// The interpolation takes the shortest path.
+    #[must_use]
+    pub fn nlerp(&self, other: &Self, t: T) -> Self {
+        let other = if self.dot(other) < T::ZERO {
+            -other
+        } else {
+            *other
+        };
+        let s = T::ONE - t;
+        Self(self.0 * s + other.0 * t, self.1 * s + other.1 * t).normalize()
+    }
+
+    /// Interpolates spherically between `self` and `other`.
+    ///
+    /// The interpolation takes the shortest path.
+    ///
+    /// NOTE: `self` and `other` are expected to be unit quaternions.
+    #[must_use]
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        let (cos, other) = match self.dot(other) {
+            x if x < T::ZERO => (-x, -other),
+            x => (x, *other),
+        };
+        // NOTE: `sin` vanishes as the quaternions get closer,
+        // in which case `nlerp` gives a better result.
+        if cos >= T::ONE - T::EPSILON.sqrt() {
+            return self.nlerp(&other, t);
+        }
+        let ang = cos.acos();
+        let isin = T::ONE / ang.sin();
+        let s = ((T::ONE - t) * ang).sin() * isin;
+        let t = (t * ang).sin() * isin;
+        Self(self.0 * s + other.0 * t, self.1 * s + other.1 * t)
+    }
+
+    /// Creates a new quaternion encoding the shortest rotation
+    /// from one direction to another.
+    ///
+    /// NOTE: One must ensure that neither vector has zero length.
+    pub fn from_to(from: &Vec3<T>, to: &Vec3<T>) -> Self {
+        let from = from.normalize();
+        let to = to.normalize();
+        let cos = from.dot(&to);
+        if cos + T::ONE > T::EPSILON {
+            return Self(from.cross(&to), T::ONE + cos).normalize();
+        }
+        // Any axis orthogonal to `from` will do.
+        let x = Vec3::new(T::ONE, T::ZERO, T::ZERO);
+        let axis = match from.cross(&x) {
+            v if v.dot(&v) > T::EPSILON => v,
+            _ => from.cross(&Vec3::new(T::ZERO, T::ONE, T::ZERO)),
+        };
+        Self(axis.normalize(), T::ZERO)
+    }
+
+    /// Creates a new quaternion encoding a rotation that aligns the
+    /// z axis with `forward` and the y axis with `up`, as closely
+    /// as possible (left-handed).
+    ///
+    /// This is the inverse of the rotation in `Mat4::look_at`.
+    ///
+    /// NOTE: One must ensure that `forward` and `up` are not parallel.
+    pub fn look_rotation(forward: &Vec3<T>, up: &Vec3<T>) -> Self {
+        let fwd = forward.normalize();
+        let side = up.cross(&fwd).normalize();
+        let up = fwd.cross(&side);
+        Self::rotation_m(&Mat3::new(side.into(), up.into(), fwd.into()))
+    }
+
+    /// Creates a new quaternion from Euler angles.
+    ///
+    /// `angles` contains the rotation about the x, y and z axes,
+    /// in this order, regardless of `order`.
+    pub fn from_euler(angles: &Vec3<T>, order: EulerOrder) -> Self {
+        let rot = |axis| match axis {
+            0 => Self::rotation_x(angles[0]),
+            1 => Self::rotation_y(angles[1]),
+            _ => Self::rotation_z(angles[2]),
+        };
+        let (i, j, k, _) = order.axes();
+        rot(k) * rot(j) * rot(i)
+    }
+
+    /// Converts the quaternion into Euler angles.
+    ///
+    /// The returned vector contains the rotation about the x, y and
+    /// z axes, in this order, regardless of `order`. The angle of
+    /// the second rotation is in `[-pi/2, pi/2]`; the others are in
+    /// `[-pi, pi]`. At the singularities, the third angle is zero.
+    pub fn to_euler(&self, order: EulerOrder) -> Vec3<T> {
+        let m = Mat3::rotation_q(self);
+        let r = |row: usize, col: usize| m[col][row];
+        let (i, j, k, cyclic) = order.axes();
+        let s = if cyclic { T::ONE } else { -T::ONE };
+        let sin = -s * r(k, i);
+        let mut v = Vec3::default();
+        if sin.abs() >= T::ONE - T::EPSILON {
+            let one = if sin > T::ZERO { T::ONE } else { -T::ONE };
+            v[j] = one.asin();
+            v[i] = (-s * r(j, k)).atan2(r(j, j));
+        } else {
+            v[j] = sin.asin();
+            v[i] = (s * r(k, j)).atan2(r(k, k));
+            v[k] = (s * r(j, i)).atan2(r(i, i));
+        }
+        v
+    }
+}
+
 impl<T: Copy> From<&Vec4<T>> for Quat<T> {
     /// Converts a `&Vec4<T>` into a `Quat<T>`.
     ///
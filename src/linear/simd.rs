@@ -0,0 +1,268 @@
+//! Explicit SIMD paths for `Mat4<f32>` and `Vec4<f32>`.
+//!
+//! The generic code in `matn` is the reference implementation.
+//! The functions here must compute the same expressions, in the
+//! same order, so results match it (modulo the sign of zero).
+
+use crate::linear::{Mat4, Vec4};
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse"))]
+mod arch {
+    use std::arch::x86_64::*;
+
+    pub type F32x4 = __m128;
+
+    pub fn load(x: [f32; 4]) -> F32x4 {
+        unsafe { _mm_loadu_ps(x.as_ptr()) }
+    }
+
+    pub fn store(x: F32x4) -> [f32; 4] {
+        let mut a = [0f32; 4];
+        unsafe { _mm_storeu_ps(a.as_mut_ptr(), x) };
+        a
+    }
+
+    pub fn splat(x: f32) -> F32x4 {
+        unsafe { _mm_set1_ps(x) }
+    }
+
+    pub fn add(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { _mm_add_ps(a, b) }
+    }
+
+    pub fn sub(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { _mm_sub_ps(a, b) }
+    }
+
+    pub fn mul(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { _mm_mul_ps(a, b) }
+    }
+
+    /// Selects `[a[I & 3], a[I >> 2 & 3], b[I >> 4 & 3], b[I >> 6 & 3]]`.
+    pub fn shuffle<const I: i32>(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { _mm_shuffle_ps::<I>(a, b) }
+    }
+
+    pub fn transpose(c: [F32x4; 4]) -> [F32x4; 4] {
+        unsafe {
+            let t0 = _mm_unpacklo_ps(c[0], c[1]);
+            let t1 = _mm_unpacklo_ps(c[2], c[3]);
+            let t2 = _mm_unpackhi_ps(c[0], c[1]);
+            let t3 = _mm_unpackhi_ps(c[2], c[3]);
+            [
+                _mm_movelh_ps(t0, t1),
+                _mm_movehl_ps(t1, t0),
+                _mm_movelh_ps(t2, t3),
+                _mm_movehl_ps(t3, t2),
+            ]
+        }
+    }
+
+    /// Computes `a * b` using 256-bit registers.
+    #[cfg(target_feature = "avx")]
+    pub fn mul_mat(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+        let mut m = [[0f32; 4]; 4];
+        unsafe {
+            let a = [
+                _mm256_broadcast_ps(&load(a[0])),
+                _mm256_broadcast_ps(&load(a[1])),
+                _mm256_broadcast_ps(&load(a[2])),
+                _mm256_broadcast_ps(&load(a[3])),
+            ];
+            for i in (0..4).step_by(2) {
+                let f = |k: usize| _mm256_set_m128(splat(b[i + 1][k]), splat(b[i][k]));
+                let mut x = _mm256_mul_ps(a[0], f(0));
+                x = _mm256_add_ps(x, _mm256_mul_ps(a[1], f(1)));
+                x = _mm256_add_ps(x, _mm256_mul_ps(a[2], f(2)));
+                x = _mm256_add_ps(x, _mm256_mul_ps(a[3], f(3)));
+                _mm256_storeu_ps(m.as_mut_ptr().add(i).cast(), x);
+            }
+        }
+        m
+    }
+}
+
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+mod arch {
+    use std::arch::aarch64::*;
+
+    pub type F32x4 = float32x4_t;
+
+    pub fn load(x: [f32; 4]) -> F32x4 {
+        unsafe { vld1q_f32(x.as_ptr()) }
+    }
+
+    pub fn store(x: F32x4) -> [f32; 4] {
+        let mut a = [0f32; 4];
+        unsafe { vst1q_f32(a.as_mut_ptr(), x) };
+        a
+    }
+
+    pub fn splat(x: f32) -> F32x4 {
+        unsafe { vdupq_n_f32(x) }
+    }
+
+    pub fn add(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { vaddq_f32(a, b) }
+    }
+
+    pub fn sub(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { vsubq_f32(a, b) }
+    }
+
+    pub fn mul(a: F32x4, b: F32x4) -> F32x4 {
+        unsafe { vmulq_f32(a, b) }
+    }
+
+    /// Selects `[a[I & 3], a[I >> 2 & 3], b[I >> 4 & 3], b[I >> 6 & 3]]`.
+    // NOTE: NEON has no general two-source shuffle; the compiler
+    // lowers this into lane moves.
+    pub fn shuffle<const I: i32>(a: F32x4, b: F32x4) -> F32x4 {
+        let (a, b) = (store(a), store(b));
+        let i = I as usize;
+        load([a[i & 3], a[i >> 2 & 3], b[i >> 4 & 3], b[i >> 6 & 3]])
+    }
+
+    pub fn transpose(c: [F32x4; 4]) -> [F32x4; 4] {
+        unsafe {
+            let t0 = vzip1q_f32(c[0], c[2]);
+            let t1 = vzip1q_f32(c[1], c[3]);
+            let t2 = vzip2q_f32(c[0], c[2]);
+            let t3 = vzip2q_f32(c[1], c[3]);
+            [
+                vzip1q_f32(t0, t1),
+                vzip2q_f32(t0, t1),
+                vzip1q_f32(t2, t3),
+                vzip2q_f32(t2, t3),
+            ]
+        }
+    }
+}
+
+use arch::F32x4;
+
+/// Equivalent to `_MM_SHUFFLE(w, z, y, x)`.
+const fn sel(x: i32, y: i32, z: i32, w: i32) -> i32 {
+    x | y << 2 | z << 4 | w << 6
+}
+
+fn load_mat(m: &Mat4<f32>) -> [F32x4; 4] {
+    [
+        arch::load(m[0].into()),
+        arch::load(m[1].into()),
+        arch::load(m[2].into()),
+        arch::load(m[3].into()),
+    ]
+}
+
+fn store_mat(c: [F32x4; 4]) -> Mat4<f32> {
+    Mat4::new(
+        arch::store(c[0]),
+        arch::store(c[1]),
+        arch::store(c[2]),
+        arch::store(c[3]),
+    )
+}
+
+/// Computes `a[0] * f[0] + a[1] * f[1] + a[2] * f[2] + a[3] * f[3]`.
+fn combine(a: &[F32x4; 4], f: &Vec4<f32>) -> F32x4 {
+    let mut x = arch::mul(a[0], arch::splat(f[0]));
+    x = arch::add(x, arch::mul(a[1], arch::splat(f[1])));
+    x = arch::add(x, arch::mul(a[2], arch::splat(f[2])));
+    arch::add(x, arch::mul(a[3], arch::splat(f[3])))
+}
+
+/// Computes `a * b`.
+#[cfg(not(all(target_arch = "x86_64", target_feature = "avx")))]
+pub fn mul(a: &Mat4<f32>, b: &Mat4<f32>) -> Mat4<f32> {
+    let a = load_mat(a);
+    store_mat([
+        combine(&a, &b[0]),
+        combine(&a, &b[1]),
+        combine(&a, &b[2]),
+        combine(&a, &b[3]),
+    ])
+}
+
+/// Computes `a * b`.
+#[cfg(all(target_arch = "x86_64", target_feature = "avx"))]
+pub fn mul(a: &Mat4<f32>, b: &Mat4<f32>) -> Mat4<f32> {
+    let cols = |m: &Mat4<f32>| [m[0].into(), m[1].into(), m[2].into(), m[3].into()];
+    let m = arch::mul_mat(&cols(a), &cols(b));
+    Mat4::new(m[0], m[1], m[2], m[3])
+}
+
+/// Computes `m * v`.
+pub fn mul_vec(m: &Mat4<f32>, v: &Vec4<f32>) -> Vec4<f32> {
+    Vec4::from(arch::store(combine(&load_mat(m), v)))
+}
+
+/// Computes the transpose of `m`.
+pub fn transpose(m: &Mat4<f32>) -> Mat4<f32> {
+    store_mat(arch::transpose(load_mat(m)))
+}
+
+/// Computes the 2x2 sub-determinants of a pair of columns.
+///
+/// Returns `[s0, s1, s2, s3]` and `[s4, s5, s4, s5]`, as named
+/// in the generic `Mat4::invert`.
+fn minors(c0: F32x4, c1: F32x4) -> (F32x4, F32x4) {
+    let x = arch::shuffle::<{ sel(0, 0, 0, 1) }>(c0, c0);
+    let y = arch::shuffle::<{ sel(1, 2, 3, 2) }>(c1, c1);
+    let z = arch::shuffle::<{ sel(1, 2, 3, 2) }>(c0, c0);
+    let w = arch::shuffle::<{ sel(0, 0, 0, 1) }>(c1, c1);
+    let lo = arch::sub(arch::mul(x, y), arch::mul(z, w));
+    let x = arch::shuffle::<{ sel(1, 2, 1, 2) }>(c0, c0);
+    let y = arch::shuffle::<{ sel(3, 3, 3, 3) }>(c1, c1);
+    let z = arch::shuffle::<{ sel(3, 3, 3, 3) }>(c0, c0);
+    let w = arch::shuffle::<{ sel(1, 2, 1, 2) }>(c1, c1);
+    let hi = arch::sub(arch::mul(x, y), arch::mul(z, w));
+    (lo, hi)
+}
+
+fn det_of(s: (F32x4, F32x4), c: (F32x4, F32x4)) -> f32 {
+    let (s03, s45) = (arch::store(s.0), arch::store(s.1));
+    let (c03, c45) = (arch::store(c.0), arch::store(c.1));
+    s03[0] * c45[1] - s03[1] * c45[0] + s03[2] * c03[3] + s03[3] * c03[2] - s45[0] * c03[1]
+        + s45[1] * c03[0]
+}
+
+/// Computes the determinant of `m`.
+pub fn det(m: &Mat4<f32>) -> f32 {
+    let c = load_mat(m);
+    det_of(minors(c[0], c[1]), minors(c[2], c[3]))
+}
+
+/// Computes the inverse of `m`.
+pub fn invert(m: &Mat4<f32>) -> Mat4<f32> {
+    let c = load_mat(m);
+    let s = minors(c[0], c[1]);
+    let k = minors(c[2], c[3]);
+    let idet = 1.0 / det_of(s, k);
+
+    // `q[x]` is `[cx, cx, sx, sx]`.
+    let q = [
+        arch::shuffle::<{ sel(0, 0, 0, 0) }>(k.0, s.0),
+        arch::shuffle::<{ sel(1, 1, 1, 1) }>(k.0, s.0),
+        arch::shuffle::<{ sel(2, 2, 2, 2) }>(k.0, s.0),
+        arch::shuffle::<{ sel(3, 3, 3, 3) }>(k.0, s.0),
+        arch::shuffle::<{ sel(0, 0, 0, 0) }>(k.1, s.1),
+        arch::shuffle::<{ sel(1, 1, 1, 1) }>(k.1, s.1),
+    ];
+
+    // `t[i]` is `[m1i, m0i, m3i, m2i]`.
+    let t = arch::transpose([c[1], c[0], c[3], c[2]]);
+
+    let cofactor = |a: usize, b: usize, c: usize, d: usize, e: usize, f: usize| {
+        let x = arch::sub(arch::mul(q[a], t[b]), arch::mul(q[c], t[d]));
+        arch::add(x, arch::mul(q[e], t[f]))
+    };
+    let pos = arch::load([idet, -idet, idet, -idet]);
+    let neg = arch::load([-idet, idet, -idet, idet]);
+    store_mat([
+        arch::mul(cofactor(5, 1, 4, 2, 3, 3), pos),
+        arch::mul(cofactor(5, 0, 2, 2, 1, 3), neg),
+        arch::mul(cofactor(4, 0, 2, 1, 0, 3), pos),
+        arch::mul(cofactor(3, 0, 1, 1, 0, 2), neg),
+    ])
+}
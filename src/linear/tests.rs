@@ -1,4 +1,4 @@
-use crate::linear::{Mat2, Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
+use crate::linear::{Affine3, Mat2, Mat3, Mat4, Quat, Vec2, Vec3, Vec4};
 
 #[test]
 fn vec_index() {
@@ -436,6 +436,89 @@ fn mat_invert() {
     assert1(o[3][3]);
 }
 
+// NOTE: `Mat4<f32>` may use explicit SIMD instructions,
+// so it is checked against the generic code.
+fn mat4_samples() -> Vec<Mat4<f32>> {
+    let mut x = 0x2545f491u32;
+    let mut next = || {
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        (x % 2001) as f32 / 100.0 - 10.0
+    };
+    let mut v = vec![
+        Mat4::from(1.0),
+        Mat4::translation(-1.0, 2.0, 3.5),
+        Mat4::rotation(1.25, &Vec3::new(0.5, -1.0, 2.0)) * Mat4::scale(2.0, 0.5, -3.0),
+        Mat4::perspective(1.0, 1.5, 0.01, 100.0),
+        Mat4::look_at(
+            &Vec3::new(0.0, 1.0, 0.0),
+            &Vec3::new(3.0, -2.0, 5.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+        ),
+    ];
+    for _ in 0..32 {
+        v.push(Mat4::new(
+            [next(), next(), next(), next()],
+            [next(), next(), next(), next()],
+            [next(), next(), next(), next()],
+            [next(), next(), next(), next()],
+        ));
+    }
+    v
+}
+
+#[test]
+fn mat4_simd_mul() {
+    let v = mat4_samples();
+    for m in &v {
+        for n in &v {
+            let o = m.mul_generic(n);
+            assert_eq!(m * n, o);
+            assert_eq!(*m * *n, o);
+            let mut p = *m;
+            p *= n;
+            assert_eq!(p, o);
+            let u = n[2];
+            assert_eq!(m * u, m.mul_vec_generic(&u));
+            assert_eq!(*m * u, m.mul_vec_generic(&u));
+        }
+    }
+}
+
+#[test]
+fn mat4_simd_transpose() {
+    for m in mat4_samples() {
+        assert_eq!(m.transpose(), m.transpose_generic());
+        assert_eq!(m.transpose().transpose(), m);
+    }
+}
+
+#[test]
+fn mat4_simd_det() {
+    for m in mat4_samples() {
+        assert_eq!(m.det(), m.det_generic());
+    }
+}
+
+#[test]
+fn mat4_simd_invert() {
+    for m in mat4_samples() {
+        if m.det().abs() <= f32::EPSILON {
+            continue;
+        }
+        let n = m.invert();
+        assert_eq!(n, m.invert_generic());
+        let o = m * n;
+        for i in 0..4 {
+            for j in 0..4 {
+                let x = if i == j { 1.0 } else { 0.0 };
+                assert!((o[i][j] - x).abs() <= 1e-3);
+            }
+        }
+    }
+}
+
 #[test]
 fn quat_mul() {
     let q = Quat::new([0.0; 3], 1f32);
@@ -806,3 +889,426 @@ fn mat_trs() {
     assert(&m, trs.0, trs.1, trs.2);
     assert(&m, m.into_t(), m.into_r(), m.into_s());
 }
+
+#[test]
+fn quat_conjugate_invert() {
+    let q = Quat::rotation(1.0, &Vec3::new(1f64, -2.0, 0.5));
+    let c = q.conjugate();
+    assert_eq!(c.imag(), -q.imag());
+    assert_eq!(c.real(), q.real());
+    let p = q * c;
+    assert!(p.imag().length() <= f64::EPSILON);
+    assert!((p.real() - 1.0).abs() <= f64::EPSILON);
+
+    let q = Quat::new([1.0, 2.0, -3.0], 4f64);
+    let i = q.invert();
+    for p in [q * i, i * q] {
+        assert!(p.imag().length() <= f64::EPSILON);
+        assert!((p.real() - 1.0).abs() <= f64::EPSILON);
+    }
+    assert!((q.length() - 30f64.sqrt()).abs() <= f64::EPSILON);
+    assert!((q.normalize().length() - 1.0).abs() <= f64::EPSILON);
+}
+
+#[test]
+fn quat_rotate() {
+    let assert = |v: Vec3<f64>, u: Vec3<f64>| {
+        for i in 0..3 {
+            assert!((v[i] - u[i]).abs() <= 1e-12);
+        }
+    };
+
+    let v = Vec3::new(1.0, -2.0, 3.0);
+    let q = Quat::rotation_x(std::f64::consts::FRAC_PI_2);
+    assert(q.rotate(&v), Vec3::new(1.0, -3.0, -2.0));
+    assert(q.rotate(&v), Mat3::rotation_q(&q) * v);
+
+    let q = Quat::rotation(2.5, &Vec3::new(-1.0, 0.5, 0.25));
+    assert(q.rotate(&v), Mat3::rotation_q(&q) * v);
+    assert(q.conjugate().rotate(&q.rotate(&v)), v);
+    let p = Quat::rotation_z(-0.75);
+    assert((p * q).rotate(&v), p.rotate(&q.rotate(&v)));
+}
+
+#[test]
+fn quat_lerp() {
+    let assert = |q: Quat<f64>, p: Quat<f64>, eps: f64| {
+        // NOTE: `q` and `-q` encode the same rotation.
+        let s = if q.dot(&p) < 0.0 { -1.0 } else { 1.0 };
+        for i in 0..3 {
+            assert!((q.imag()[i] - s * p.imag()[i]).abs() <= eps);
+        }
+        assert!((q.real() - s * p.real()).abs() <= eps);
+    };
+    let axis = Vec3::new(0.0, 1.0, -1.0);
+    let q = Quat::rotation(0.5, &axis);
+    let p = Quat::rotation(2.0, &axis);
+
+    assert(q.slerp(&p, 0.0), q, 4.0 * f64::EPSILON);
+    assert(q.slerp(&p, 1.0), p, 4.0 * f64::EPSILON);
+    for t in [0.1, 0.25, 0.5, 0.9] {
+        let r = Quat::rotation(0.5 + 1.5 * t, &axis);
+        assert(q.slerp(&p, t), r, 4.0 * f64::EPSILON);
+        assert!((q.nlerp(&p, t).length() - 1.0).abs() <= f64::EPSILON);
+    }
+    assert(q.nlerp(&p, 0.5), q.slerp(&p, 0.5), 4.0 * f64::EPSILON);
+    assert(q.nlerp(&p, 0.0), q, 4.0 * f64::EPSILON);
+    assert(q.nlerp(&p, 1.0), p, 4.0 * f64::EPSILON);
+
+    // Shortest path.
+    let n = -p;
+    assert(q.slerp(&n, 0.5), q.slerp(&p, 0.5), 4.0 * f64::EPSILON);
+    assert(q.nlerp(&n, 0.5), q.nlerp(&p, 0.5), 4.0 * f64::EPSILON);
+
+    // Nearly equal.
+    let r = Quat::rotation(0.5 + 1e-9, &axis);
+    assert(q.slerp(&r, 0.5), Quat::rotation(0.5 + 5e-10, &axis), 1e-12);
+
+    // Single precision.
+    let q = Quat::rotation_y(-1f32);
+    let p = Quat::rotation_y(1f32);
+    let r = q.slerp(&p, 0.75);
+    let s = Quat::rotation_y(0.5f32);
+    assert!((r.imag()[1] - s.imag()[1]).abs() <= 4.0 * f32::EPSILON);
+    assert!((r.real() - s.real()).abs() <= 4.0 * f32::EPSILON);
+}
+
+#[test]
+fn quat_from_to() {
+    let assert = |from: Vec3<f64>, to: Vec3<f64>| {
+        let q = Quat::from_to(&from, &to);
+        assert!((q.length() - 1.0).abs() <= 4.0 * f64::EPSILON);
+        let v = q.rotate(&from.normalize());
+        let u = to.normalize();
+        for i in 0..3 {
+            assert!((v[i] - u[i]).abs() <= 1e-12);
+        }
+        // Shortest arc: the axis is orthogonal to both vectors.
+        assert!(q.imag().dot(&from).abs() <= 1e-12);
+        assert!(q.imag().dot(&to).abs() <= 1e-12);
+    };
+    assert(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert(Vec3::new(1.0, 2.0, 3.0), Vec3::new(-3.0, 0.5, 2.0));
+    assert(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 2.0));
+    assert(Vec3::new(1.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+    assert(Vec3::new(0.0, -1.0, 1.0), Vec3::new(0.0, 1.0, -1.0));
+
+    let q = Quat::from_to(&Vec3::new(0.0, 0.0, 1.0), &Vec3::new(1.0, 0.0, 0.0));
+    let p = Quat::rotation_y(std::f64::consts::FRAC_PI_2);
+    assert!((q.imag() - p.imag()).length() <= f64::EPSILON);
+    assert!((q.real() - p.real()).abs() <= f64::EPSILON);
+}
+
+#[test]
+fn quat_look_rotation() {
+    let assert = |fwd: Vec3<f64>, up: Vec3<f64>| {
+        let q = Quat::look_rotation(&fwd, &up);
+        let z = q.rotate(&Vec3::new(0.0, 0.0, 1.0));
+        let y = q.rotate(&Vec3::new(0.0, 1.0, 0.0));
+        assert!((z - fwd.normalize()).length() <= 1e-12);
+        assert!(y.dot(&fwd).abs() <= 1e-12);
+        assert!(y.dot(&up) > 0.0);
+        assert!(y.cross(&up).dot(&fwd).abs() <= 1e-12);
+
+        let eye = Vec3::new(3.0, -1.0, 2.0);
+        let v = Mat4::look_at(&(eye + fwd), &eye, &up);
+        let m = Mat4::from(&Mat3::rotation_q(&q));
+        let n = v * m;
+        for i in 0..3 {
+            for j in 0..3 {
+                let x = if i == j { 1.0 } else { 0.0 };
+                assert!((n[i][j] - x).abs() <= 1e-12);
+            }
+        }
+    };
+    assert(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+    assert(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert(Vec3::new(1.0, -2.0, 0.5), Vec3::new(0.0, 1.0, 0.0));
+    assert(Vec3::new(0.0, 3.0, 0.0), Vec3::new(1.0, 0.0, 1.0));
+}
+
+#[test]
+fn quat_euler() {
+    use crate::linear::EulerOrder;
+
+    let orders = [
+        EulerOrder::Xyz,
+        EulerOrder::Xzy,
+        EulerOrder::Yxz,
+        EulerOrder::Yzx,
+        EulerOrder::Zxy,
+        EulerOrder::Zyx,
+    ];
+    let assert = |q: Quat<f64>, p: Quat<f64>| {
+        assert!((q.dot(&p).abs() - 1.0).abs() <= 1e-12);
+    };
+
+    let a = Vec3::new(0.3, -1.2, 2.5);
+    let (x, y, z) = (
+        Quat::rotation_x(a[0]),
+        Quat::rotation_y(a[1]),
+        Quat::rotation_z(a[2]),
+    );
+    assert(Quat::from_euler(&a, EulerOrder::Xyz), z * y * x);
+    assert(Quat::from_euler(&a, EulerOrder::Xzy), y * z * x);
+    assert(Quat::from_euler(&a, EulerOrder::Yxz), z * x * y);
+    assert(Quat::from_euler(&a, EulerOrder::Yzx), x * z * y);
+    assert(Quat::from_euler(&a, EulerOrder::Zxy), y * x * z);
+    assert(Quat::from_euler(&a, EulerOrder::Zyx), x * y * z);
+
+    const PI: f64 = std::f64::consts::PI;
+    const PI_2: f64 = std::f64::consts::FRAC_PI_2;
+    let angles = [
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.3, -1.2, 2.5),
+        Vec3::new(-3.0, 1.5, -0.1),
+        Vec3::new(1.0, 0.25, -1.0),
+        Vec3::new(-0.5, -1.5, 1.5),
+    ];
+    for order in orders {
+        let j = match order {
+            EulerOrder::Xyz | EulerOrder::Zyx => 1,
+            EulerOrder::Yxz | EulerOrder::Zxy => 0,
+            EulerOrder::Xzy | EulerOrder::Yzx => 2,
+        };
+        for a in &angles {
+            let q = Quat::from_euler(a, order);
+            let e = q.to_euler(order);
+            assert(Quat::from_euler(&e, order), q);
+            // Angles within range are recovered exactly.
+            if a[j].abs() < PI_2 && a[0].abs() < PI && a[1].abs() < PI && a[2].abs() < PI {
+                for i in 0..3 {
+                    assert!((e[i] - a[i]).abs() <= 1e-12);
+                }
+            }
+        }
+        // Gimbal lock.
+        for b in [PI_2, -PI_2] {
+            let mut a = Vec3::new(0.7, 0.7, 0.7);
+            a[j] = b;
+            let q = Quat::from_euler(&a, order);
+            let e = q.to_euler(order);
+            assert!((e[j] - b).abs() <= 1e-6);
+            assert(Quat::from_euler(&e, order), q);
+        }
+    }
+}
+
+#[test]
+fn quat_rotation_m() {
+    // NOTE: Negative traces take a different path, where
+    // any element of the diagonal may dominate.
+    for axis in [
+        Vec3::new(1f64, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(0.0, -1.0, 1.0),
+        Vec3::new(1.0, 0.5, -2.0),
+        Vec3::new(3.0, 2.0, 1.0),
+        Vec3::new(3.0, 1.0, 2.0),
+        Vec3::new(2.0, 3.0, 1.0),
+        Vec3::new(1.0, 3.0, 2.0),
+        Vec3::new(2.0, 1.0, 3.0),
+        Vec3::new(1.0, 2.0, 3.0),
+    ] {
+        for ang in [0.5, 2.5, -3.0] {
+            let q = Quat::rotation(ang, &axis);
+            let r = Quat::rotation_m(&Mat3::rotation_q(&q));
+            assert!((r.dot(&q).abs() - 1.0).abs() <= 1e-12);
+        }
+    }
+}
+
+#[test]
+fn mat_decompose() {
+    let assert = |m: &Mat4<f64>| {
+        let (t, r, s) = m.decompose().unwrap();
+        let n = Mat4::from_trs(&t, &r, &s);
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((m[i][j] - n[i][j]).abs() <= 1e-12);
+            }
+        }
+        (t, r, s)
+    };
+
+    let t = Vec3::new(1.0, -2.0, 30.0);
+    let r = Quat::rotation(0.75, &Vec3::new(1.0, 1.0, -0.5));
+    let s = Vec3::new(2.0, 0.5, 3.0);
+    let (u, q, v) = assert(&Mat4::from_trs(&t, &r, &s));
+    assert!((u - t).length() <= 1e-12);
+    assert!((q.dot(&r).abs() - 1.0).abs() <= 1e-12);
+    assert!((v - s).length() <= 1e-12);
+
+    let (_, _, v) = assert(&Mat4::from(1.0));
+    assert_eq!(v, Vec3::from(1.0));
+
+    // Negative scale.
+    for s in [
+        Vec3::new(-1.0, 1.0, 1.0),
+        Vec3::new(2.0, -3.0, 0.5),
+        Vec3::new(-1.0, -1.0, 4.0),
+        Vec3::new(-0.1, -2.0, -3.0),
+    ] {
+        let m = Mat4::from_trs(&t, &r, &s);
+        let (_, _, v) = assert(&m);
+        for i in 0..3 {
+            assert!((v[i].abs() - s[i].abs()).abs() <= 1e-12);
+        }
+        assert_eq!(v[0] * v[1] * v[2] < 0.0, s[0] * s[1] * s[2] < 0.0);
+    }
+
+    // Single precision.
+    let m = Mat4::from_trs(
+        &Vec3::new(100f32, 0.0, -5.0),
+        &Quat::rotation_z(2.0),
+        &Vec3::new(1.0, 4.0, -1.0),
+    );
+    let (t, r, s) = m.decompose().unwrap();
+    let n = Mat4::from_trs(&t, &r, &s);
+    for i in 0..4 {
+        for j in 0..4 {
+            assert!((m[i][j] - n[i][j]).abs() <= 1e-5);
+        }
+    }
+
+    // Shear.
+    let mut m = Mat4::from_trs(&t, &Quat::rotation_x(1.0), &Vec3::from(1.0));
+    m[1][0] += 0.25;
+    assert!(m.decompose().is_none());
+
+    // Singular.
+    assert!(Mat4::scale(1.0, 0.0, 1.0).decompose().is_none());
+    let m = Mat4::new(
+        [1.0, 2.0, 3.0, 0.0],
+        [2.0, 4.0, 6.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    );
+    assert!(m.decompose().is_none());
+
+    // Not affine.
+    assert!(Mat4::perspective(1.0, 1.0, 0.1, 100.0)
+        .decompose()
+        .is_none());
+}
+
+#[test]
+fn affine_conv() {
+    let m = Mat4::from_trs(
+        &Vec3::new(1.0, 2.0, 3.0),
+        &Quat::rotation_y(0.5),
+        &Vec3::new(2.0, 1.0, -1.0),
+    );
+    let a = Affine3::from(&m);
+    for i in 0..4 {
+        for j in 0..3 {
+            assert_eq!(a[i][j], m[i][j]);
+        }
+    }
+    assert_eq!(Mat4::from(a), m);
+    assert_eq!(a.translation(), Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(a.linear(), Mat3::from(&m));
+
+    let a = Affine3::from(1);
+    assert_eq!(Mat4::from(&a), Mat4::from(1));
+    let a = Affine3::from(Mat3::from([2; 9]));
+    assert_eq!(a[3], Vec3::default());
+    assert_eq!(a[1], Vec3::from(2));
+}
+
+#[test]
+fn affine_mul() {
+    let m = Mat4::from_trs(
+        &Vec3::new(-5.0, 2.0, 0.5),
+        &Quat::rotation(1.0, &Vec3::new(1.0, 1.0, 0.0)),
+        &Vec3::new(2.0, 3.0, 0.5),
+    );
+    let n = Mat4::from_trs(
+        &Vec3::new(10.0, -20.0, 1.0),
+        &Quat::rotation_z(-2.0),
+        &Vec3::new(-1.0, 1.0, 1.0),
+    );
+    let a = Affine3::from(&m);
+    let b = Affine3::from(&n);
+    let o = Affine3::from(m * n);
+    let (x, y) = (&a, &b);
+    assert_eq!(x * y, o);
+    assert_eq!(a * b, o);
+    let mut c = a;
+    c *= &b;
+    assert_eq!(c, o);
+    let mut c = a;
+    c *= b;
+    assert_eq!(c, o);
+
+    let m = Mat4::new([1, 2, 3, 0], [4, 5, 6, 0], [7, 8, 9, 0], [10, 11, 12, 1]);
+    let a = Affine3::from(&m);
+    assert_eq!(Mat4::from(a * a), m * m);
+}
+
+#[test]
+fn affine_transform() {
+    let m = Mat4::from_trs(
+        &Vec3::new(3.0, -1.0, 2.0),
+        &Quat::rotation(-0.5, &Vec3::new(0.0, 1.0, 1.0)),
+        &Vec3::new(1.0, 2.0, 4.0),
+    );
+    let a = Affine3::from(&m);
+    let v = Vec3::new(0.5, -8.0, 1.5);
+    let p = m * Vec4::new(v[0], v[1], v[2], 1.0);
+    let d = m * Vec4::new(v[0], v[1], v[2], 0.0);
+    assert_eq!(a.transform_point(&v), Vec3::from(p));
+    assert_eq!(a.transform_vector(&v), Vec3::from(d));
+}
+
+#[test]
+fn affine_invert() {
+    let assert = |a: Affine3<f64>| {
+        let b = a.invert();
+        let m = Mat4::from(&a).invert();
+        for i in 0..4 {
+            for j in 0..3 {
+                assert!((b[i][j] - m[i][j]).abs() <= 1e-12);
+            }
+        }
+        let c = a * b;
+        let d = b * a;
+        for i in 0..4 {
+            for j in 0..3 {
+                let x = if i == j { 1.0 } else { 0.0 };
+                assert!((c[i][j] - x).abs() <= 1e-12);
+                assert!((d[i][j] - x).abs() <= 1e-12);
+            }
+        }
+    };
+    assert(Affine3::from(1.0));
+    assert(Affine3::from(Mat4::translation(1.0, -2.0, 3.0)));
+    assert(Affine3::from(Mat4::from_trs(
+        &Vec3::new(100.0, 0.25, -8.0),
+        &Quat::rotation(2.0, &Vec3::new(1.0, -1.0, 1.0)),
+        &Vec3::new(0.5, 2.0, -3.0),
+    )));
+    assert(Affine3::new(
+        [1.0, 0.5, 0.0],
+        [0.0, 1.0, 0.25],
+        [2.0, 0.0, 1.0],
+        [-1.0, -1.0, -1.0],
+    ));
+}
+
+#[test]
+fn precision_conv() {
+    let v = Vec3::new(1.5f32, -2.25, 1e-3);
+    assert_eq!(v.to_f64().to_f32(), v);
+    assert_eq!(Vec4::new(1e7f64 + 0.5, 0.0, 0.0, 1.0).to_f32()[0], 1e7);
+
+    let m = Mat4::translation(1.0f32, 2.0, 3.0) * Mat4::rotation_x(0.75);
+    assert_eq!(m.to_f64().to_f32(), m);
+    assert_eq!(Mat3::from(&m.to_f64()).to_f32(), Mat3::from(&m));
+
+    let a = Affine3::from(&m);
+    assert_eq!(a.to_f64().to_f32(), a);
+    assert_eq!(Mat4::from(a.to_f64()), m.to_f64());
+}
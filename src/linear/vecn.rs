@@ -422,6 +422,28 @@ conv_impl!(Vec2<T>, Mat2<T>, 2);
 conv_impl!(Vec3<T>, Mat3<T>, 3);
 conv_impl!(Vec4<T>, Mat4<T>, 4);
 
+macro_rules! precision_impl {
+    ($($v:ident),*) => {$(
+        impl $v<f32> {
+            /// Converts into a double-precision vector.
+            pub fn to_f64(&self) -> $v<f64> {
+                $v(self.0.map(f64::from))
+            }
+        }
+
+        impl $v<f64> {
+            /// Converts into a single-precision vector.
+            ///
+            /// NOTE: This conversion is lossy.
+            pub fn to_f32(&self) -> $v<f32> {
+                $v(self.0.map(|x| x as f32))
+            }
+        }
+    )*};
+}
+
+precision_impl!(Vec2, Vec3, Vec4);
+
 impl<T: Copy> From<&Vec4<T>> for Vec3<T> {
     /// Converts a `&Vec4<T>` into a `Vec3<T>`, discarding the last component.
     fn from(v: &Vec4<T>) -> Self {
@@ -4,6 +4,7 @@ use std::io;
 use std::sync::Arc;
 
 use crate::gpu::layout::MaterialU;
+use crate::mesh::Semantic;
 use crate::sampler::Sampler;
 use crate::texture::Texture;
 
@@ -98,6 +99,29 @@ impl Material {
 pub enum UvSet {
     Set0,
     Set1,
+    Set2,
+    Set3,
+    Set4,
+    Set5,
+    Set6,
+    Set7,
+}
+
+impl UvSet {
+    /// Returns the [`Semantic`] that provides the UV set's
+    /// coordinates.
+    pub const fn semantic(&self) -> Semantic {
+        match self {
+            UvSet::Set0 => Semantic::TexCoord0,
+            UvSet::Set1 => Semantic::TexCoord1,
+            UvSet::Set2 => Semantic::TexCoord2,
+            UvSet::Set3 => Semantic::TexCoord3,
+            UvSet::Set4 => Semantic::TexCoord4,
+            UvSet::Set5 => Semantic::TexCoord5,
+            UvSet::Set6 => Semantic::TexCoord6,
+            UvSet::Set7 => Semantic::TexCoord7,
+        }
+    }
 }
 
 /// Reference to a texture and its sampler.
@@ -6,9 +6,18 @@ use std::mem::{self, MaybeUninit};
 use std::ptr::{self, NonNull};
 use std::sync::{Arc, RwLock};
 
+use crate::codec::{self, Norm};
 use crate::gpu::{self, BufId, BufOptions};
+use crate::linear::{Vec2, Vec3, Vec4};
 use crate::material::Material;
-use crate::var_buf::{VarAlloc, VarBuf, VarEntry};
+use crate::shape::{Bbox, Hull, Sphere};
+use crate::var_buf::{VarAlloc, VarBuf, VarEntry, VarMove, VarPolicy, VarStats};
+
+mod generate;
+mod meshlet;
+mod optimize;
+pub mod shapes;
+mod simplify;
 
 static mut VERT_BUF: Option<Arc<RwLock<VertBuf>>> = None;
 
@@ -88,6 +97,11 @@ impl VertAlloc {
 
 impl VarAlloc for VertAlloc {
     const STRIDE: usize = 512;
+    // NOTE: `VarPolicy::SizeClass` would waste less space
+    // on index buffers and small primitives, but it places
+    // entries under a lock, which would serialize builders
+    // that call `VarBuf::try_alloc` from several threads.
+    const POLICY: VarPolicy = VarPolicy::Linear;
 
     fn grow(&mut self, new_size: usize) -> io::Result<NonNull<()>> {
         if new_size <= self.size {
@@ -178,6 +192,13 @@ impl Drop for VertAlloc {
     }
 }
 
+// SAFETY: The mapped memory is only accessed through the
+// `VertBuf` that owns the allocation, which is in turn only
+// accessed under the `RwLock` returned by `vertex_buffer`.
+// Growing and dropping go through `gpu`, which is thread-safe.
+unsafe impl Send for VertAlloc {}
+unsafe impl Sync for VertAlloc {}
+
 /// Vertex buffer.
 pub(crate) type VertBuf = VarBuf<VertAlloc>;
 
@@ -192,14 +213,108 @@ fn vertex_buffer() -> Arc<RwLock<VertBuf>> {
     unsafe { Arc::clone(VERT_BUF.as_ref().unwrap()) }
 }
 
+/// Compacts the vertex buffer, patching the data entries
+/// of `meshes` and releasing unused memory.
+///
+/// It returns the number of bytes freed.
+///
+/// # Safety
+///
+/// `meshes` must contain every [`Mesh`] that is alive, and no
+/// [`Builder`] may hold data. The data of any other primitive
+/// may be moved or released, leaving it with entries that are
+/// out of bounds or that belong to someone else.
+///
+/// It also must not be called while the `gpu` may be reading
+/// from the vertex buffer.
+pub unsafe fn compact_vertex_buffer(meshes: &mut [&mut Mesh]) -> io::Result<usize> {
+    compact(&vertex_buffer(), meshes)
+}
+
+/// Compacts `vb`, patching the data entries of `meshes`.
+///
+/// The safety requirements of `compact_vertex_buffer` apply,
+/// with respect to `vb`.
+unsafe fn compact(vb: &Arc<RwLock<VertBuf>>, meshes: &mut [&mut Mesh]) -> io::Result<usize> {
+    let mut moves = vec![];
+    let res = vb.write().unwrap().compact(|x| moves.push(x));
+    if !moves.is_empty() {
+        for i in meshes.iter_mut().flat_map(|x| &mut x.primitives) {
+            debug_assert!(Arc::ptr_eq(vb, &i.vert_buf));
+            i.relocate(&moves);
+        }
+    }
+    res
+}
+
+/// Computes usage statistics of the vertex buffer.
+///
+/// Entries that do not [`fit`](VarStats::fits) will cause
+/// the vertex buffer to grow, which may require copying its
+/// whole contents.
+pub fn vertex_buffer_stats() -> VarStats {
+    vertex_buffer().read().unwrap().stats()
+}
+
 /// Mesh.
 #[derive(Debug)]
-pub struct Mesh(Vec<Primitive>);
+pub struct Mesh {
+    primitives: Vec<Primitive>,
+    weights: Vec<f32>,
+}
 
 impl Mesh {
     /// Returns a reference to the mesh's [`Primitive`]s.
     pub fn primitives(&self) -> &[Primitive] {
-        &self.0
+        &self.primitives
+    }
+
+    /// Returns the default morph target weights.
+    ///
+    /// There is one weight for each morph target of the
+    /// mesh's primitives, so this slice is empty if they
+    /// have no morph targets.
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    /// Computes bounding volumes from the mesh's
+    /// [`Semantic::Position`] data.
+    ///
+    /// Integer positions are decoded as normalized integers
+    /// only if they were set as such.
+    ///
+    /// Returns [`None`] if any primitive stores positions in a
+    /// [`DataType`] that cannot be decoded.
+    pub fn bounds(&self) -> Option<(Bbox, Sphere)> {
+        let points = self.positions()?;
+        if points.is_empty() {
+            None
+        } else {
+            Some((Bbox::from_points(&points), Sphere::from_points(&points)))
+        }
+    }
+
+    /// Creates the convex [`Hull`] of the mesh's
+    /// [`Semantic::Position`] data.
+    ///
+    /// Returns [`None`] under the same conditions as `bounds`.
+    pub fn hull(&self) -> Option<Hull> {
+        let points = self.positions()?;
+        if points.is_empty() {
+            None
+        } else {
+            Some(Hull::new(points))
+        }
+    }
+
+    /// Reads the [`Semantic::Position`] data of every primitive.
+    fn positions(&self) -> Option<Vec<Vec3<f32>>> {
+        let mut points = Vec::new();
+        for i in &self.primitives {
+            points.append(&mut i.positions()?);
+        }
+        Some(points)
     }
 }
 
@@ -209,8 +324,15 @@ pub struct Primitive {
     // NOTE: In case we decide (or need) to use
     // multiple vertex buffers.
     vert_buf: Arc<RwLock<VertBuf>>,
-    semantics: [Option<DataEntry>; SEMANTIC_N],
+    semantics: Semantics,
     indices: Option<DataEntry>,
+    // Coarser levels of detail, which share the
+    // vertex data.
+    lods: Vec<Lod>,
+    meshlets: Option<Meshlets>,
+    morphs: Vec<Morph>,
+    // Number of vertices stored for each semantic.
+    vert_count: usize,
     // Number of vertices to draw.
     // How to interpret this field depends on
     // whether the primitive has `indices`.
@@ -230,7 +352,7 @@ impl Primitive {
     /// semantic in memory, or [`None`] if such semantic is not
     /// present in this primitive.
     pub(crate) fn semantic_data(&self, sem: Semantic) -> Option<&DataEntry> {
-        self.semantics[sem as usize].as_ref()
+        self.semantics.get(sem)
     }
 
     /// Returns a reference to [`DataEntry`] representing the
@@ -244,7 +366,16 @@ impl Primitive {
     /// or [`None`] if such semantic is not present in this
     /// primitive.
     pub fn semantic_data_type(&self, sem: Semantic) -> Option<DataType> {
-        self.semantics[sem as usize].as_ref().map(|x| x.data_type)
+        self.semantics.get(sem).map(|x| x.data_type)
+    }
+
+    /// Returns an iterator over the semantics present in
+    /// this primitive.
+    ///
+    /// Built-in semantics come first, followed by
+    /// [`Semantic::Custom`] ones in ascending order.
+    pub fn semantics(&self) -> impl Iterator<Item = Semantic> + '_ {
+        self.semantics.keys()
     }
 
     /// Returns the [`DataType`] used to store vertex indices,
@@ -263,6 +394,175 @@ impl Primitive {
         self.count
     }
 
+    /// Returns the number of levels of detail, including
+    /// the primitive itself (level `0`).
+    pub fn lod_count(&self) -> usize {
+        1 + self.lods.len()
+    }
+
+    /// Returns a reference to [`DataEntry`] representing the
+    /// indices of a given level of detail in memory, or
+    /// [`None`] if it does not use an index buffer.
+    ///
+    /// Panics if `level` is not less than `lod_count`.
+    pub(crate) fn lod_index_data(&self, level: usize) -> Option<&DataEntry> {
+        match level {
+            0 => self.indices.as_ref(),
+            _ => Some(&self.lods[level - 1].indices),
+        }
+    }
+
+    /// Returns the number of vertices that are drawn when
+    /// drawing a given level of detail.
+    ///
+    /// Panics if `level` is not less than `lod_count`.
+    pub fn lod_vertex_count(&self, level: usize) -> usize {
+        match level {
+            0 => self.count,
+            _ => self.lods[level - 1].count,
+        }
+    }
+
+    /// Returns the geometric error of a given level of detail,
+    /// in object space.
+    ///
+    /// Panics if `level` is not less than `lod_count`.
+    pub fn lod_error(&self, level: usize) -> f32 {
+        match level {
+            0 => 0.0,
+            _ => self.lods[level - 1].error,
+        }
+    }
+
+    /// Returns the number of meshlets, which is zero if
+    /// meshlets have not been built.
+    pub fn meshlet_count(&self) -> usize {
+        self.meshlets.as_ref().map_or(0, |x| x.count)
+    }
+
+    /// Reads the meshlets.
+    pub fn meshlets(&self) -> Vec<Meshlet> {
+        self.meshlets.as_ref().map_or(vec![], |x| {
+            let vb = self.vert_buf.read().unwrap();
+            meshlet::decode(&vb.read(&x.meshlets)[..x.count * meshlet::MESHLET_SIZE])
+        })
+    }
+
+    /// Reads the meshlet vertex list, which holds the
+    /// primitive's vertex indices referenced by each meshlet.
+    pub fn meshlet_vertices(&self) -> Vec<u32> {
+        self.meshlets.as_ref().map_or(vec![], |x| {
+            let vb = self.vert_buf.read().unwrap();
+            decode_indices(vb.read(&x.vertices), DataType::U32, x.vert_count)
+        })
+    }
+
+    /// Reads the meshlet triangles, as indices into each
+    /// meshlet's range of the meshlet vertex list.
+    pub fn meshlet_triangles(&self) -> Vec<[u8; 3]> {
+        self.meshlets.as_ref().map_or(vec![], |x| {
+            let vb = self.vert_buf.read().unwrap();
+            (decode_indices(vb.read(&x.triangles), DataType::U32, x.tri_count))
+                .into_iter()
+                .map(meshlet::unpack)
+                .collect()
+        })
+    }
+
+    /// Returns a reference to the [`Meshlets`], or [`None`]
+    /// if meshlets have not been built.
+    pub(crate) fn meshlet_data(&self) -> Option<&Meshlets> {
+        self.meshlets.as_ref()
+    }
+
+    /// Returns the number of morph targets.
+    pub fn morph_target_count(&self) -> usize {
+        self.morphs.len()
+    }
+
+    /// Returns a reference to the [`VarEntry`] representing a
+    /// morph target's displacements of a given semantic, or
+    /// [`None`] if the target does not displace it.
+    ///
+    /// Displacements are stored as [`DataType::F32x3`].
+    ///
+    /// Panics if `target` is not less than `morph_target_count`.
+    pub(crate) fn morph_data(&self, target: usize, sem: Semantic) -> Option<&VarEntry> {
+        self.morphs[target].deltas[Morph::index(sem)?].as_ref()
+    }
+
+    /// Blends the morph targets on the CPU.
+    ///
+    /// Each target's displacements are scaled by the weight
+    /// at the same index in `weights`, and targets beyond
+    /// its length are ignored. Blended normals and tangents
+    /// are renormalized, and tangents keep their handedness.
+    ///
+    /// Returns [`None`] if [`Semantic::Position`] data cannot
+    /// be decoded.
+    pub fn morph(&self, weights: &[f32]) -> Option<Morphed> {
+        let vb = self.vert_buf.read().unwrap();
+        let n = self.vert_count;
+        let read = |sem: Semantic| {
+            let data = self.semantic_data(sem)?;
+            let size = data.data_type.layout().size() * n;
+            canonical_f32(
+                &vb.read(&data.entry)[..size],
+                sem,
+                data.data_type,
+                data.normalized,
+            )
+        };
+        let mut data = [
+            read(Semantic::Position),
+            read(Semantic::Normal),
+            read(Semantic::Tangent),
+        ];
+        data[0].as_ref()?;
+        for (x, &w) in self.morphs.iter().zip(weights) {
+            if w == 0.0 {
+                continue;
+            }
+            for ((data, delta), stride) in data.iter_mut().zip(&x.deltas).zip([3, 3, 4]) {
+                if let (Some(data), Some(delta)) = (data, delta) {
+                    let delta = decode_norm(&vb.read(delta)[..12 * n], DataType::F32x3).unwrap();
+                    blend(data, stride, &delta, w);
+                }
+            }
+        }
+        let [pos, nrm, tan] = data;
+        Some(Morphed {
+            positions: (pos.unwrap().chunks_exact(3))
+                .map(|x| Vec3::new(x[0], x[1], x[2]))
+                .collect(),
+            normals: nrm.map(|x| {
+                (x.chunks_exact(3))
+                    .map(|x| Vec3::new(x[0], x[1], x[2]).normalize())
+                    .collect()
+            }),
+            tangents: tan.map(|x| {
+                (x.chunks_exact(4))
+                    .map(|x| {
+                        let t = Vec3::new(x[0], x[1], x[2]).normalize();
+                        Vec4::new(t[0], t[1], t[2], x[3])
+                    })
+                    .collect()
+            }),
+        })
+    }
+
+    /// Selects the coarsest level of detail whose error does
+    /// not exceed `threshold` when multiplied by `scale`.
+    ///
+    /// `scale` is expected to convert object space distances
+    /// into screen space (e.g., pixels per unit at the
+    /// primitive's distance from the camera).
+    pub fn select_lod(&self, scale: f32, threshold: f32) -> usize {
+        (self.lods.iter())
+            .rposition(|x| x.error * scale <= threshold)
+            .map_or(0, |i| i + 1)
+    }
+
     /// Returns a reference to the reference-counted [`Material`],
     /// or [`None`] if this primitive has no material assigned.
     pub fn material(&self) -> Option<&Arc<Material>> {
@@ -273,17 +573,57 @@ impl Primitive {
     pub fn topology(&self) -> Topology {
         self.topology
     }
+
+    /// Patches the data entries after compaction.
+    fn relocate(&mut self, moves: &[VarMove]) {
+        for i in self
+            .semantics
+            .iter_mut()
+            .chain(&mut self.indices)
+            .chain(self.lods.iter_mut().map(|x| &mut x.indices))
+        {
+            i.entry.relocate(moves);
+        }
+        if let Some(x) = &mut self.meshlets {
+            for i in x.entries_mut() {
+                i.relocate(moves);
+            }
+        }
+        for i in self.morphs.iter_mut().flat_map(|x| &mut x.deltas).flatten() {
+            i.relocate(moves);
+        }
+    }
+
+    /// Reads the [`Semantic::Position`] data.
+    fn positions(&self) -> Option<Vec<Vec3<f32>>> {
+        let data = self.semantic_data(Semantic::Position)?;
+        let size = data.data_type.layout().size() * self.vert_count;
+        let vb = self.vert_buf.read().unwrap();
+        decode_positions(
+            &vb.read(&data.entry)[..size],
+            data.data_type,
+            data.normalized,
+        )
+    }
 }
 
 impl Drop for Primitive {
     fn drop(&mut self) {
-        for i in &mut self.semantics {
-            if let Some(x) = i.take() {
-                self.vert_buf.write().unwrap().dealloc(x.entry);
-            }
+        for x in self.semantics.drain() {
+            self.vert_buf.read().unwrap().dealloc(x.entry);
         }
         if let Some(x) = self.indices.take() {
-            self.vert_buf.write().unwrap().dealloc(x.entry);
+            self.vert_buf.read().unwrap().dealloc(x.entry);
+        }
+        for x in self.lods.drain(..) {
+            self.vert_buf.read().unwrap().dealloc(x.indices.entry);
+        }
+        if let Some(x) = self.meshlets.take() {
+            let vb = self.vert_buf.read().unwrap();
+            x.into_entries().into_iter().for_each(|x| vb.dealloc(x));
+        }
+        for x in self.morphs.drain(..) {
+            x.dealloc(&self.vert_buf.read().unwrap());
         }
     }
 }
@@ -297,6 +637,7 @@ impl Drop for Primitive {
 #[derive(Debug)]
 pub(crate) struct DataEntry {
     data_type: DataType,
+    normalized: bool,
     entry: VarEntry,
 }
 
@@ -306,12 +647,149 @@ impl DataEntry {
         self.data_type
     }
 
+    /// Returns whether integer data is normalized, i.e., read
+    /// as floating-point values in `[0, 1]` (unsigned) or
+    /// `[-1, 1]` (signed).
+    pub fn normalized(&self) -> bool {
+        self.normalized
+    }
+
     /// Returns a reference to the [`VarEntry`].
     pub fn entry(&self) -> &VarEntry {
         &self.entry
     }
 }
 
+/// Level of detail of a [`Primitive`].
+#[derive(Debug)]
+struct Lod {
+    indices: DataEntry,
+    count: usize,
+    error: f32,
+}
+
+/// Meshlet data of a [`Primitive`] in memory.
+///
+/// Meshlets are stored as `meshlet::MESHLET_SIZE`-byte
+/// records, while vertices and packed triangles are
+/// stored as `u32`s.
+#[derive(Debug)]
+pub(crate) struct Meshlets {
+    count: usize,
+    vert_count: usize,
+    tri_count: usize,
+    meshlets: VarEntry,
+    vertices: VarEntry,
+    triangles: VarEntry,
+}
+
+impl Meshlets {
+    /// Returns the number of meshlets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns a reference to the [`VarEntry`] of the meshlets.
+    pub fn meshlets(&self) -> &VarEntry {
+        &self.meshlets
+    }
+
+    /// Returns a reference to the [`VarEntry`] of the
+    /// meshlet vertex list.
+    pub fn vertices(&self) -> &VarEntry {
+        &self.vertices
+    }
+
+    /// Returns a reference to the [`VarEntry`] of the
+    /// packed meshlet triangles.
+    pub fn triangles(&self) -> &VarEntry {
+        &self.triangles
+    }
+
+    fn entries_mut(&mut self) -> [&mut VarEntry; 3] {
+        [&mut self.meshlets, &mut self.vertices, &mut self.triangles]
+    }
+
+    fn into_entries(self) -> [VarEntry; 3] {
+        [self.meshlets, self.vertices, self.triangles]
+    }
+}
+
+/// Morph target of a [`Primitive`] in memory.
+///
+/// Displacements of [`Semantic::Position`],
+/// [`Semantic::Normal`] and [`Semantic::Tangent`] are
+/// stored in this order, as [`DataType::F32x3`].
+#[derive(Default, Debug)]
+struct Morph {
+    deltas: [Option<VarEntry>; 3],
+}
+
+impl Morph {
+    /// Returns the index of a semantic's displacements, or
+    /// [`None`] if it cannot be displaced.
+    fn index(sem: Semantic) -> Option<usize> {
+        match sem {
+            Semantic::Position => Some(0),
+            Semantic::Normal => Some(1),
+            Semantic::Tangent => Some(2),
+            _ => None,
+        }
+    }
+
+    fn dealloc(self, vb: &VertBuf) {
+        self.deltas
+            .into_iter()
+            .flatten()
+            .for_each(|x| vb.dealloc(x));
+    }
+}
+
+/// Vertex data produced by [`Primitive::morph`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct Morphed {
+    pub positions: Vec<Vec3<f32>>,
+    /// Blended [`Semantic::Normal`] data, if present.
+    pub normals: Option<Vec<Vec3<f32>>>,
+    /// Blended [`Semantic::Tangent`] data, if present.
+    pub tangents: Option<Vec<Vec4<f32>>>,
+}
+
+/// Adds `weight` times tightly packed three-component
+/// displacements to `data`, whose elements have `stride`
+/// components.
+fn blend(data: &mut [f32], stride: usize, delta: &[f32], weight: f32) {
+    for (x, d) in data.chunks_exact_mut(stride).zip(delta.chunks_exact(3)) {
+        for i in 0..3 {
+            x[i] += d[i] * weight;
+        }
+    }
+}
+
+/// Cluster of triangles of a [`Primitive`].
+///
+/// A meshlet can be culled as backfacing when seen from
+/// `eye` if `(center - eye).dot(cone_axis)` is at least
+/// `cone_cutoff * (center - eye).length() + radius`, where
+/// `center` and `radius` are those of `bounds`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Meshlet {
+    /// Offset of the meshlet's first vertex in the
+    /// meshlet vertex list.
+    pub vertex_offset: u32,
+    pub vertex_count: u32,
+    /// Offset of the meshlet's first triangle in the
+    /// meshlet triangle list.
+    pub triangle_offset: u32,
+    pub triangle_count: u32,
+    pub bounds: Sphere,
+    /// Average direction of the triangles' normals.
+    pub cone_axis: Vec3<f32>,
+    /// Sine of the angle between `cone_axis` and the normal
+    /// farthest from it, or `1.0` if culling is not possible.
+    pub cone_cutoff: f32,
+}
+
 /// Data types.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum DataType {
@@ -363,22 +841,307 @@ impl DataType {
             DataType::I8x4 | DataType::U8x4 => Layout::new::<[i8; 4]>(),
         }
     }
+
+    /// Returns the [`DataType`] of a single component.
+    pub const fn scalar(&self) -> DataType {
+        match self {
+            DataType::F32 | DataType::F32x2 | DataType::F32x3 | DataType::F32x4 => DataType::F32,
+            DataType::I32 | DataType::I32x2 | DataType::I32x3 | DataType::I32x4 => DataType::I32,
+            DataType::U32 | DataType::U32x2 | DataType::U32x3 | DataType::U32x4 => DataType::U32,
+            DataType::I16 | DataType::I16x2 | DataType::I16x3 | DataType::I16x4 => DataType::I16,
+            DataType::U16 | DataType::U16x2 | DataType::U16x3 | DataType::U16x4 => DataType::U16,
+            DataType::I8 | DataType::I8x2 | DataType::I8x3 | DataType::I8x4 => DataType::I8,
+            DataType::U8 | DataType::U8x2 | DataType::U8x3 | DataType::U8x4 => DataType::U8,
+        }
+    }
+
+    /// Returns the number of components.
+    pub const fn components(&self) -> usize {
+        self.layout().size() / self.scalar().layout().size()
+    }
+}
+
+/// Decodes tightly packed data into `f32`s.
+///
+/// Integer types are interpreted as normalized integers.
+/// Fails for 32-bit integer types.
+fn decode_norm(buf: &[u8], data_type: DataType) -> Option<Vec<f32>> {
+    let scalar = data_type.scalar();
+    let dec: fn(&[u8]) -> f32 = match scalar {
+        DataType::F32 => |x| f32::from_ne_bytes([x[0], x[1], x[2], x[3]]),
+        DataType::I16 => |x| i16::from_ne_bytes([x[0], x[1]]).decode(),
+        DataType::U16 => |x| u16::from_ne_bytes([x[0], x[1]]).decode(),
+        DataType::I8 => |x| (x[0] as i8).decode(),
+        DataType::U8 => |x| x[0].decode(),
+        _ => return None,
+    };
+    Some(buf.chunks_exact(scalar.layout().size()).map(dec).collect())
+}
+
+/// Decodes tightly packed data into `f32`s.
+///
+/// Integer types are interpreted as normalized integers if
+/// `normalized` is `true`, and converted as they are
+/// otherwise. Fails for 32-bit integer types.
+fn decode(buf: &[u8], data_type: DataType, normalized: bool) -> Option<Vec<f32>> {
+    if normalized {
+        return decode_norm(buf, data_type);
+    }
+    let scalar = data_type.scalar();
+    let dec: fn(&[u8]) -> f32 = match scalar {
+        DataType::F32 => |x| f32::from_ne_bytes([x[0], x[1], x[2], x[3]]),
+        DataType::I16 => |x| i16::from_ne_bytes([x[0], x[1]]) as f32,
+        DataType::U16 => |x| u16::from_ne_bytes([x[0], x[1]]) as f32,
+        DataType::I8 => |x| x[0] as i8 as f32,
+        DataType::U8 => |x| x[0] as f32,
+        _ => return None,
+    };
+    Some(buf.chunks_exact(scalar.layout().size()).map(dec).collect())
+}
+
+/// Encodes `f32`s into tightly packed data.
+///
+/// Integer types are interpreted as normalized integers.
+/// Fails for 32-bit integer types.
+fn encode_norm(data: &[f32], data_type: DataType) -> Option<Vec<u8>> {
+    let scalar = data_type.scalar();
+    let mut buf = Vec::with_capacity(data.len() * scalar.layout().size());
+    let iter = data.iter().copied();
+    match scalar {
+        DataType::F32 => iter.for_each(|x| buf.extend(x.to_ne_bytes())),
+        DataType::I16 => iter.for_each(|x| buf.extend(i16::encode(x).to_ne_bytes())),
+        DataType::U16 => iter.for_each(|x| buf.extend(u16::encode(x).to_ne_bytes())),
+        DataType::I8 => iter.for_each(|x| buf.push(i8::encode(x) as u8)),
+        DataType::U8 => iter.for_each(|x| buf.push(u8::encode(x))),
+        _ => return None,
+    }
+    Some(buf)
+}
+
+/// Decodes tightly packed semantic data into `f32`s, in the
+/// semantic's canonical layout.
+///
+/// `normalized` is as in `convert`.
+fn canonical_f32(
+    buf: &[u8],
+    semantic: Semantic,
+    data_type: DataType,
+    normalized: bool,
+) -> Option<Vec<f32>> {
+    let to = VertexFormat::Canonical.store_as(buf, semantic, data_type);
+    if to != data_type {
+        decode_norm(&convert(buf, semantic, data_type, normalized, to)?, to)
+    } else {
+        decode(buf, to, normalized)
+    }
+}
+
+/// Converts tightly packed semantic data between data types.
+///
+/// Integer `from` types are interpreted as normalized integers
+/// if `normalized` is `true`, and converted as they are
+/// otherwise. Integer `to` types are always normalized, so
+/// integers that are not cannot be converted into them.
+/// [`Semantic::Normal`] can additionally be converted between
+/// three-component and (octahedral) two-component types.
+///
+/// Fails if the conversion is not supported.
+fn convert(
+    buf: &[u8],
+    semantic: Semantic,
+    from: DataType,
+    normalized: bool,
+    to: DataType,
+) -> Option<Vec<u8>> {
+    let int = |x: DataType| x.scalar() != DataType::F32;
+    if !semantic.converts()
+        || (int(from) && !normalized && (int(to) || from.components() != to.components()))
+    {
+        return None;
+    }
+    let data = decode(buf, from, normalized)?;
+    let signed = |x: DataType| !matches!(x.scalar(), DataType::U16 | DataType::U8);
+    let data = match (from.components(), to.components()) {
+        (m, n) if m == n => data,
+        (3, 2) if semantic == Semantic::Normal => {
+            let (a, b) = if signed(to) { (1.0, 0.0) } else { (0.5, 0.5) };
+            data.chunks_exact(3)
+                .flat_map(|x| {
+                    let e = codec::oct_encode(&Vec3::new(x[0], x[1], x[2]));
+                    [e[0] * a + b, e[1] * a + b]
+                })
+                .collect()
+        }
+        (2, 3) if semantic == Semantic::Normal => {
+            let (a, b) = if signed(from) {
+                (1.0, 0.0)
+            } else {
+                (2.0, -1.0)
+            };
+            data.chunks_exact(2)
+                .flat_map(|x| {
+                    <[f32; 3]>::from(codec::oct_decode(&Vec2::new(x[0] * a + b, x[1] * a + b)))
+                })
+                .collect()
+        }
+        _ => return None,
+    };
+    encode_norm(&data, to)
+}
+
+/// Decodes tightly packed position data.
+///
+/// `normalized` is as in `decode`.
+/// Fails for types that do not have three components.
+fn decode_positions(buf: &[u8], data_type: DataType, normalized: bool) -> Option<Vec<Vec3<f32>>> {
+    if data_type.components() != 3 {
+        return None;
+    }
+    let data = decode(buf, data_type, normalized)?;
+    Some(
+        data.chunks_exact(3)
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect(),
+    )
+}
+
+/// Decodes `count` indices as stored in the vertex buffer.
+///
+/// [`DataType::U8`] indices are expected to be stored as
+/// 16-bit.
+fn decode_indices(buf: &[u8], data_type: DataType, count: usize) -> Vec<u32> {
+    match data_type {
+        DataType::U32 => (buf.chunks_exact(4).take(count))
+            .map(|x| u32::from_ne_bytes([x[0], x[1], x[2], x[3]]))
+            .collect(),
+        _ => (buf.chunks_exact(2).take(count))
+            .map(|x| u16::from_ne_bytes([x[0], x[1]]) as u32)
+            .collect(),
+    }
+}
+
+/// Encodes indices to be stored in the vertex buffer.
+///
+/// [`DataType::U8`] indices are stored as 16-bit.
+fn encode_indices(indices: &[u32], data_type: DataType) -> Vec<u8> {
+    match data_type {
+        DataType::U32 => indices.iter().flat_map(|x| x.to_ne_bytes()).collect(),
+        _ => (indices.iter())
+            .flat_map(|&x| (x as u16).to_ne_bytes())
+            .collect(),
+    }
 }
 
 /// Semantics.
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+///
+/// [`Semantic::Custom`] identifies application-defined
+/// per-vertex data, which [`Builder`] always stores as
+/// given.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Semantic {
     Position,
     Normal,
     Tangent,
     TexCoord0,
     TexCoord1,
+    TexCoord2,
+    TexCoord3,
+    TexCoord4,
+    TexCoord5,
+    TexCoord6,
+    TexCoord7,
     Color0,
+    Color1,
     Joints0,
+    Joints1,
     Weights0,
+    Weights1,
+    Custom(u16),
+}
+
+impl Semantic {
+    /// Number of texture coordinate sets.
+    pub const TEX_COORD_N: usize = 8;
+
+    /// Returns the [`Semantic`] of a given texture coordinate
+    /// set, or [`None`] if `set` is not less than
+    /// `TEX_COORD_N`.
+    pub const fn tex_coord(set: usize) -> Option<Self> {
+        Some(match set {
+            0 => Semantic::TexCoord0,
+            1 => Semantic::TexCoord1,
+            2 => Semantic::TexCoord2,
+            3 => Semantic::TexCoord3,
+            4 => Semantic::TexCoord4,
+            5 => Semantic::TexCoord5,
+            6 => Semantic::TexCoord6,
+            7 => Semantic::TexCoord7,
+            _ => return None,
+        })
+    }
+
+    /// Returns whether this is a texture coordinate semantic.
+    pub const fn is_tex_coord(&self) -> bool {
+        matches!(self.index(), Some(i) if i >= 3 && i < 3 + Self::TEX_COORD_N)
+    }
+
+    /// Returns whether data of this semantic can be converted
+    /// between data types.
+    ///
+    /// Joints are indices, and [`Semantic::Custom`] data is
+    /// stored as given.
+    const fn converts(&self) -> bool {
+        !matches!(
+            self,
+            Semantic::Joints0 | Semantic::Joints1 | Semantic::Custom(_)
+        )
+    }
+
+    /// Returns the slot of a built-in semantic, or [`None`]
+    /// for [`Semantic::Custom`].
+    const fn index(&self) -> Option<usize> {
+        Some(match self {
+            Semantic::Position => 0,
+            Semantic::Normal => 1,
+            Semantic::Tangent => 2,
+            Semantic::TexCoord0 => 3,
+            Semantic::TexCoord1 => 4,
+            Semantic::TexCoord2 => 5,
+            Semantic::TexCoord3 => 6,
+            Semantic::TexCoord4 => 7,
+            Semantic::TexCoord5 => 8,
+            Semantic::TexCoord6 => 9,
+            Semantic::TexCoord7 => 10,
+            Semantic::Color0 => 11,
+            Semantic::Color1 => 12,
+            Semantic::Joints0 => 13,
+            Semantic::Joints1 => 14,
+            Semantic::Weights0 => 15,
+            Semantic::Weights1 => 16,
+            Semantic::Custom(_) => return None,
+        })
+    }
+
+    /// Returns the built-in semantic of a given slot.
+    const fn from_index(index: usize) -> Self {
+        match index {
+            0 => Semantic::Position,
+            1 => Semantic::Normal,
+            2 => Semantic::Tangent,
+            3..=10 => Self::tex_coord(index - 3).unwrap(),
+            11 => Semantic::Color0,
+            12 => Semantic::Color1,
+            13 => Semantic::Joints0,
+            14 => Semantic::Joints1,
+            15 => Semantic::Weights0,
+            16 => Semantic::Weights1,
+            _ => panic!("not a built-in semantic"),
+        }
+    }
 }
 
-pub(crate) const SEMANTIC_N: usize = Semantic::Weights0 as usize + 1;
+/// Number of built-in semantics.
+pub(crate) const SEMANTIC_N: usize = Semantic::Weights1.index().unwrap() + 1;
 
 /// Constructs an array of `SEMANTIC_N` `Option<DataEntry>`s
 /// where each element is `None`.
@@ -393,6 +1156,148 @@ fn none_semantics() -> [Option<DataEntry>; SEMANTIC_N] {
     }
 }
 
+/// Semantic data of a primitive.
+///
+/// Built-in semantics are stored in `SEMANTIC_N` slots,
+/// while [`Semantic::Custom`] data is kept sorted by
+/// identifier.
+#[derive(Debug)]
+struct Semantics {
+    builtin: [Option<DataEntry>; SEMANTIC_N],
+    custom: Vec<(u16, DataEntry)>,
+}
+
+impl Semantics {
+    /// Creates an empty [`Semantics`].
+    fn new() -> Self {
+        Self {
+            builtin: none_semantics(),
+            custom: vec![],
+        }
+    }
+
+    /// Returns a reference to the data of a given semantic.
+    fn get(&self, sem: Semantic) -> Option<&DataEntry> {
+        match (sem.index(), sem) {
+            (Some(i), _) => self.builtin[i].as_ref(),
+            (None, Semantic::Custom(id)) => (self.custom.binary_search_by_key(&id, |x| x.0))
+                .ok()
+                .map(|i| &self.custom[i].1),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Sets the data of a given semantic, returning the
+    /// previous data if any.
+    fn insert(&mut self, sem: Semantic, data: DataEntry) -> Option<DataEntry> {
+        match (sem.index(), sem) {
+            (Some(i), _) => self.builtin[i].replace(data),
+            (None, Semantic::Custom(id)) => match self.custom.binary_search_by_key(&id, |x| x.0) {
+                Ok(i) => Some(mem::replace(&mut self.custom[i].1, data)),
+                Err(i) => {
+                    self.custom.insert(i, (id, data));
+                    None
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Removes the data of a given semantic.
+    fn take(&mut self, sem: Semantic) -> Option<DataEntry> {
+        match (sem.index(), sem) {
+            (Some(i), _) => self.builtin[i].take(),
+            (None, Semantic::Custom(id)) => (self.custom.binary_search_by_key(&id, |x| x.0))
+                .ok()
+                .map(|i| self.custom.remove(i).1),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns an iterator over the semantics present.
+    fn keys(&self) -> impl Iterator<Item = Semantic> + '_ {
+        (self.builtin.iter().enumerate())
+            .filter(|(_, x)| x.is_some())
+            .map(|(i, _)| Semantic::from_index(i))
+            .chain(self.custom.iter().map(|x| Semantic::Custom(x.0)))
+    }
+
+    /// Returns an iterator over the data of every semantic.
+    fn iter(&self) -> impl Iterator<Item = &DataEntry> {
+        (self.builtin.iter().flatten()).chain(self.custom.iter().map(|x| &x.1))
+    }
+
+    /// Returns a mutable iterator over the data of every
+    /// semantic.
+    fn iter_mut(&mut self) -> impl Iterator<Item = &mut DataEntry> {
+        (self.builtin.iter_mut().flatten()).chain(self.custom.iter_mut().map(|x| &mut x.1))
+    }
+
+    /// Removes the data of every semantic.
+    fn drain(&mut self) -> impl Iterator<Item = DataEntry> + '_ {
+        (self.builtin.iter_mut().filter_map(Option::take)).chain(self.custom.drain(..).map(|x| x.1))
+    }
+}
+
+/// How [`Builder`] stores semantic data.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum VertexFormat {
+    /// Store data as given.
+    #[default]
+    AsIs,
+    /// Store data in the canonical layout of each semantic,
+    /// decoding integers into `F32` types.
+    ///
+    /// Joints and [`Semantic::Custom`] data are stored as
+    /// given.
+    Canonical,
+    /// Quantize `F32` data into normalized integers, in the
+    /// spirit of `KHR_mesh_quantization`.
+    ///
+    /// [`Semantic::Position`], joints and [`Semantic::Custom`]
+    /// data are stored as given, and so is data that does not
+    /// fit the range of the normalized type.
+    Quantized,
+}
+
+impl VertexFormat {
+    /// Returns the [`DataType`] that tightly packed
+    /// `data_type` elements in `buf` should be stored as.
+    fn store_as(&self, buf: &[u8], semantic: Semantic, data_type: DataType) -> DataType {
+        let n = data_type.components();
+        match self {
+            VertexFormat::AsIs => data_type,
+            VertexFormat::Canonical => match (semantic, n) {
+                (Semantic::Position, _) => DataType::F32x3,
+                (Semantic::Normal, 2 | 3) => DataType::F32x3,
+                (Semantic::Tangent, _) => DataType::F32x4,
+                (x, _) if x.is_tex_coord() => DataType::F32x2,
+                (Semantic::Color0 | Semantic::Color1, 3) => DataType::F32x3,
+                (Semantic::Color0 | Semantic::Color1, _) => DataType::F32x4,
+                (Semantic::Weights0 | Semantic::Weights1, _) => DataType::F32x4,
+                _ => data_type,
+            },
+            VertexFormat::Quantized => {
+                if data_type.scalar() != DataType::F32 {
+                    return data_type;
+                }
+                let data = decode_norm(buf, data_type).unwrap();
+                let unorm = data.iter().all(|x| (0.0..=1.0).contains(x));
+                let snorm = data.iter().all(|x| (-1.0..=1.0).contains(x));
+                match (semantic, n) {
+                    (Semantic::Normal, 3) if snorm => DataType::I16x3,
+                    (Semantic::Tangent, 4) if snorm => DataType::I16x4,
+                    (x, 2) if x.is_tex_coord() && unorm => DataType::U16x2,
+                    (Semantic::Color0 | Semantic::Color1, 3) if unorm => DataType::U8x3,
+                    (Semantic::Color0 | Semantic::Color1, 4) if unorm => DataType::U8x4,
+                    (Semantic::Weights0 | Semantic::Weights1, 4) if unorm => DataType::U8x4,
+                    _ => data_type,
+                }
+            }
+        }
+    }
+}
+
 /// Primitive topology values.
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Topology {
@@ -404,14 +1309,89 @@ pub enum Topology {
     TriangleFan,
 }
 
+/// How [`Builder`] weights face normals when generating
+/// vertex normals.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum NormalWeight {
+    /// Weight by face area.
+    #[default]
+    Area,
+    /// Weight by the angle of the face's corner.
+    Angle,
+}
+
+/// Data that [`Builder::push_primitive_with`] generates if
+/// missing from a triangle primitive.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Generate {
+    /// Generate smooth [`Semantic::Normal`] data.
+    pub normals: Option<NormalWeight>,
+    /// Angle, in radians, above which faces do not share
+    /// generated normals.
+    pub crease_angle: Option<f32>,
+    /// Generate [`Semantic::Tangent`] data following
+    /// MikkTSpace's conventions.
+    ///
+    /// This requires [`Semantic::TexCoord0`].
+    ///
+    /// NOTE: Unlike MikkTSpace, this never splits vertices,
+    /// so a vertex shared by mirrored faces (i.e., whose
+    /// texture coordinates wind the other way) takes the
+    /// tangent of the orientation with more weight.
+    pub tangents: bool,
+}
+
+/// Post-transform vertex cache statistics.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct CacheStats {
+    /// Average cache miss ratio (misses per triangle).
+    pub acmr: f32,
+    /// Average transform to vertex ratio (misses per
+    /// referenced vertex).
+    pub atvr: f32,
+}
+
+/// Result of [`Builder::optimize_primitive`].
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct CacheReport {
+    /// Statistics of the primitive as given.
+    pub before: CacheStats,
+    /// Statistics of the optimized primitive.
+    pub after: CacheStats,
+}
+
+/// Target of a level of detail created by
+/// [`Builder::simplify_primitive`].
+///
+/// Simplification stops as soon as either target is met.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LodTarget {
+    /// Number of triangles.
+    pub triangles: usize,
+    /// Geometric error, in object space.
+    pub max_error: f32,
+}
+
+/// Maximum number of vertices of a [`Meshlet`].
+pub const MESHLET_MAX_VERTICES: usize = 64;
+
+/// Maximum number of triangles of a [`Meshlet`].
+pub const MESHLET_MAX_TRIANGLES: usize = 124;
+
 /// Mesh builder.
 pub struct Builder {
     vert_buf: Arc<RwLock<VertBuf>>,
     // Data of the primitive being built,
     // which will be consumed by the next
     // `push_primitive` call.
-    semantics: [Option<DataEntry>; SEMANTIC_N],
+    semantics: Semantics,
     indices: Option<DataEntry>,
+    lods: Vec<Lod>,
+    meshlets: Option<Meshlets>,
+    // Morph target being set, which will be
+    // consumed by the next `push_morph_target` call.
+    morph: Morph,
+    morphs: Vec<Morph>,
     vert_count: usize,
     idx_count: usize,
     material: Option<Arc<Material>>,
@@ -419,7 +1399,11 @@ pub struct Builder {
     // Each new element pushed here consumes
     // the per-primitive fields above.
     primitives: Vec<Primitive>,
+    // Default morph target weights, which will be
+    // consumed by the next `create` call.
+    weights: Vec<f32>,
     mask: u32,
+    format: VertexFormat,
 }
 
 impl Builder {
@@ -431,17 +1415,31 @@ impl Builder {
         debug_assert!(unsafe { VERT_BUF.is_some() });
         Self {
             vert_buf: vertex_buffer(),
-            semantics: none_semantics(),
+            semantics: Semantics::new(),
             indices: None,
+            lods: vec![],
+            meshlets: None,
+            morph: Morph::default(),
+            morphs: vec![],
             vert_count: 0,
             idx_count: 0,
             material: None,
             // The (expected) common case.
             primitives: Vec::with_capacity(1),
+            weights: vec![],
             mask: 0,
+            format: VertexFormat::AsIs,
         }
     }
 
+    /// Sets how `set_semantic` stores data.
+    ///
+    /// The default is [`VertexFormat::AsIs`].
+    pub fn set_vertex_format(&mut self, format: VertexFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
     /// Sets the vertex count.
     ///
     /// This value indicates the number of data elements to fetch
@@ -469,53 +1467,136 @@ impl Builder {
     /// If `stride` is `None`, then data elements are assumed to be
     /// tightly packed.
     ///
+    /// `normalized` indicates whether integer data is normalized
+    /// (see [`Norm`]), as opposed to integer values. It has no
+    /// effect on floating-point data.
+    ///
     /// The number of [`DataType`] elements to read is defined by
     /// `set_vertex_count`.
+    ///
+    /// The data is stored as `set_vertex_format` dictates.
     pub fn set_semantic<T: Read>(
+        &mut self,
+        reader: T,
+        semantic: Semantic,
+        data_type: DataType,
+        normalized: bool,
+        stride: Option<usize>,
+    ) -> io::Result<&mut Self> {
+        self.set_semantic_with(reader, semantic, data_type, normalized, stride, None)
+    }
+
+    /// Sets semantic data, converting it into another [`DataType`].
+    ///
+    /// This method behaves like `set_semantic`, except that the
+    /// data is stored as `store_as` elements.
+    ///
+    /// Integer `store_as` types are always normalized, so this
+    /// can be used both to decode quantized data and to quantize
+    /// floating-point data.
+    /// [`Semantic::Normal`] can additionally be converted between
+    /// three-component and (octahedral) two-component types.
+    ///
+    /// Fails if the conversion is not supported. In particular,
+    /// 32-bit integer types, joints and [`Semantic::Custom`]
+    /// data cannot be converted, and integer data that is not
+    /// `normalized` can only be converted into floating-point
+    /// data with the same number of components.
+    pub fn set_semantic_as<T: Read>(
+        &mut self,
+        reader: T,
+        semantic: Semantic,
+        data_type: DataType,
+        normalized: bool,
+        stride: Option<usize>,
+        store_as: DataType,
+    ) -> io::Result<&mut Self> {
+        self.set_semantic_with(
+            reader,
+            semantic,
+            data_type,
+            normalized,
+            stride,
+            Some(store_as),
+        )
+    }
+
+    /// Sets semantic data, storing it as `store_as` elements or
+    /// in the builder's [`VertexFormat`] if `store_as` is `None`.
+    fn set_semantic_with<T: Read>(
         &mut self,
         mut reader: T,
         semantic: Semantic,
         data_type: DataType,
+        normalized: bool,
         stride: Option<usize>,
+        store_as: Option<DataType>,
     ) -> io::Result<&mut Self> {
         let layout = data_type.layout();
-        debug_assert!(VertAlloc::STRIDE >= layout.align());
         if self.vert_count == 0 {
             return Err(io::Error::from(io::ErrorKind::Other));
         }
         // This should not happen in practice, but we guard
         // against it anyway. We will not try anything
         // fancy like reusing the entry though.
-        if let Some(x) = self.semantics[semantic as usize].take() {
+        if let Some(x) = self.semantics.take(semantic) {
             eprintln!(
                 "[!] mesh::Builder: set_semantic called twice for {:?}",
                 semantic
             );
-            self.vert_buf.write().unwrap().dealloc(x.entry);
+            let vb = self.vert_buf.read().unwrap();
+            vb.dealloc(x.entry);
             if semantic == Semantic::Position {
                 self.mask &= !Self::POSITION;
+                // Levels of detail and meshlets are no
+                // longer valid.
+                self.lods
+                    .drain(..)
+                    .for_each(|x| vb.dealloc(x.indices.entry));
+                if let Some(x) = self.meshlets.take() {
+                    x.into_entries().into_iter().for_each(|x| vb.dealloc(x));
+                }
             }
         }
-        // In the vertex buffer, we store the data
-        // tightly packed.
-        let size = layout.size() * self.vert_count;
-        let entry = self.vert_buf.write().unwrap().alloc(size)?;
-        let mut buf = vec![0u8; size];
+        let mut buf = vec![0u8; layout.size() * self.vert_count];
         if stride.is_none() || stride.unwrap() == layout.size() {
-            match reader.read_exact(&mut buf) {
-                Ok(_) => (),
-                Err(e) => {
-                    self.vert_buf.write().unwrap().dealloc(entry);
-                    return Err(e);
-                }
-            }
+            reader.read_exact(&mut buf)?;
         } else {
             todo!();
         }
+        let store_as = store_as.unwrap_or_else(|| self.format.store_as(&buf, semantic, data_type));
+        debug_assert!(VertAlloc::STRIDE >= store_as.layout().align());
+        if store_as != data_type {
+            buf = match convert(&buf, semantic, data_type, normalized, store_as) {
+                Some(x) => x,
+                None => {
+                    eprintln!(
+                        "[!] mesh::Builder: cannot convert {:?} from {:?} into {:?}",
+                        semantic, data_type, store_as
+                    );
+                    return Err(io::Error::from(io::ErrorKind::InvalidInput));
+                }
+            };
+        }
+        // In the vertex buffer, we store the data
+        // tightly packed.
+        let entry = self.alloc(buf.len(), store_as.layout().align())?;
         // TODO: Provide a way to read the data directly
         // into `gpu` memory.
         self.vert_buf.write().unwrap().copy(&buf, &entry);
-        self.semantics[semantic as usize] = Some(DataEntry { data_type, entry });
+        self.semantics.insert(
+            semantic,
+            DataEntry {
+                data_type: store_as,
+                // Converted integers are always normalized.
+                normalized: (normalized || store_as != data_type)
+                    && matches!(
+                        store_as.scalar(),
+                        DataType::I16 | DataType::U16 | DataType::I8 | DataType::U8
+                    ),
+                entry,
+            },
+        );
         // Do not allow the vertex count to change
         // for this primitive anymore.
         self.mask |= Self::FROZEN_VERT_COUNT;
@@ -550,19 +1631,30 @@ impl Builder {
             _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
         };
         debug_assert!(stride <= VertAlloc::STRIDE);
+        let vb = self.vert_buf.read().unwrap();
         if let Some(x) = self.indices.take() {
             eprintln!("[!] mesh::Builder: set_indexed called twice");
-            self.vert_buf.write().unwrap().dealloc(x.entry);
+            vb.dealloc(x.entry);
+            // Levels of detail are no longer valid.
+            self.lods
+                .drain(..)
+                .for_each(|x| vb.dealloc(x.indices.entry));
             self.idx_count = 0;
         }
+        // Neither are meshlets, even if they were built
+        // from non-indexed vertices.
+        if let Some(x) = self.meshlets.take() {
+            x.into_entries().into_iter().for_each(|x| vb.dealloc(x));
+        }
+        drop(vb);
         let size = stride * count;
-        let entry = self.vert_buf.write().unwrap().alloc(size)?;
+        let entry = self.alloc(size, stride)?;
         let mut buf = vec![0u8; size];
         if data_size == stride {
             match reader.read_exact(&mut buf) {
                 Ok(_) => (),
                 Err(e) => {
-                    self.vert_buf.write().unwrap().dealloc(entry);
+                    self.vert_buf.read().unwrap().dealloc(entry);
                     return Err(e);
                 }
             }
@@ -572,22 +1664,395 @@ impl Builder {
         // TODO: Provide a way to read the data directly
         // into `gpu` memory.
         self.vert_buf.write().unwrap().copy(&buf, &entry);
-        self.indices = Some(DataEntry { data_type, entry });
+        self.indices = Some(DataEntry {
+            data_type,
+            normalized: false,
+            entry,
+        });
         self.idx_count = count;
         Ok(self)
     }
 
+    /// Sets morph target displacements.
+    ///
+    /// This method sets the displacements of `semantic` for
+    /// the morph target that the next `push_morph_target`
+    /// call will push. They are fetched from `reader` as
+    /// tightly packed `data_type` elements, which must have
+    /// three components. `normalized` states whether integer
+    /// types represent normalized values.
+    ///
+    /// Only [`Semantic::Position`], [`Semantic::Normal`] and
+    /// [`Semantic::Tangent`] can be displaced.
+    ///
+    /// The number of elements to read is defined by
+    /// `set_vertex_count`. Displacements are stored as
+    /// [`DataType::F32x3`].
+    pub fn set_morph_target<T: Read>(
+        &mut self,
+        mut reader: T,
+        semantic: Semantic,
+        data_type: DataType,
+        normalized: bool,
+    ) -> io::Result<&mut Self> {
+        if self.vert_count == 0 {
+            return Err(io::Error::from(io::ErrorKind::Other));
+        }
+        let err = io::Error::from(io::ErrorKind::InvalidInput);
+        let Some(i) = Morph::index(semantic) else {
+            eprintln!(
+                "[!] mesh::Builder: {:?} cannot be displaced by morph targets",
+                semantic
+            );
+            return Err(err);
+        };
+        let mut buf = vec![0u8; data_type.layout().size() * self.vert_count];
+        reader.read_exact(&mut buf)?;
+        let buf = match decode(&buf, data_type, normalized) {
+            Some(x) if data_type.components() == 3 => encode_norm(&x, DataType::F32x3).unwrap(),
+            _ => {
+                eprintln!(
+                    "[!] mesh::Builder: {:?} is not a valid morph target data type",
+                    data_type
+                );
+                return Err(err);
+            }
+        };
+        let entry = self.alloc(buf.len(), 4)?;
+        let mut vb = self.vert_buf.write().unwrap();
+        vb.copy(&buf, &entry);
+        if let Some(x) = self.morph.deltas[i].replace(entry) {
+            eprintln!(
+                "[!] mesh::Builder: set_morph_target called twice for {:?}",
+                semantic
+            );
+            vb.dealloc(x);
+        }
+        drop(vb);
+        // Do not allow the vertex count to change
+        // for this primitive anymore.
+        self.mask |= Self::FROZEN_VERT_COUNT;
+        Ok(self)
+    }
+
+    /// Pushes a morph target onto the current primitive.
+    ///
+    /// This method consumes the displacements set by
+    /// `set_morph_target`, which must have been called at
+    /// least once since the last push.
+    ///
+    /// NOTE: Every primitive of a mesh must have the same
+    /// number of morph targets.
+    pub fn push_morph_target(&mut self) -> io::Result<&mut Self> {
+        if self.morph.deltas.iter().all(Option::is_none) {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        let morph = mem::take(&mut self.morph);
+        self.morphs.push(morph);
+        Ok(self)
+    }
+
+    /// Sets the mesh's default morph target weights.
+    ///
+    /// Unless `weights` is empty, its length must match the
+    /// number of morph targets of each primitive. Weights
+    /// default to zero otherwise.
+    pub fn set_morph_weights(&mut self, weights: &[f32]) -> &mut Self {
+        self.weights = weights.to_vec();
+        self
+    }
+
     /// Sets the material.
     pub fn set_material(&mut self, material: Option<Arc<Material>>) -> &mut Self {
         self.material = material;
         self
     }
 
+    /// Reorders the current primitive's data for rendering
+    /// efficiency.
+    ///
+    /// Triangles are reordered for a post-transform vertex
+    /// cache of `cache_size` entries (using Tipsify) and then
+    /// for overdraw, and vertices are reordered in order of
+    /// first use. Every semantic is rewritten accordingly.
+    ///
+    /// It returns the (simulated) cache statistics before and
+    /// after optimization.
+    ///
+    /// Fails if the primitive is not an indexed triangle list.
+    ///
+    /// NOTE: One must ensure that the primitive is pushed with
+    /// [`Topology::Triangle`].
+    pub fn optimize_primitive(&mut self, cache_size: usize) -> io::Result<CacheReport> {
+        let err = io::Error::from(io::ErrorKind::InvalidInput);
+        let Some(indices) = self.index_data() else {
+            eprintln!("[!] mesh::Builder: cannot optimize non-indexed primitive");
+            return Err(err);
+        };
+        let n = self.vert_count;
+        if !indices.len().is_multiple_of(3) || cache_size == 0 {
+            return Err(err);
+        }
+        if indices.iter().any(|&x| x as usize >= n) {
+            eprintln!("[!] mesh::Builder: index out of bounds");
+            return Err(err);
+        }
+        let Some(pos) = self.semantic_f32(Semantic::Position) else {
+            eprintln!("[!] mesh::Builder: primitives must have position semantic");
+            return Err(err);
+        };
+        let pos: Vec<_> = (pos.chunks_exact(3))
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect();
+
+        let before = optimize::cache_stats(&indices, n, cache_size);
+        let (indices, clusters) = optimize::tipsify(&indices, n, cache_size);
+        let indices = optimize::sort_clusters(&indices, &pos, &clusters);
+        let remap = optimize::fetch_remap(&indices, n);
+        let indices: Vec<_> = indices.iter().map(|&x| remap[x as usize]).collect();
+        let after = optimize::cache_stats(&indices, n, cache_size);
+
+        let mut vb = self.vert_buf.write().unwrap();
+        for x in self.semantics.iter() {
+            let size = x.data_type.layout().size();
+            let buf = optimize::remap_data(&vb.read(&x.entry)[..size * n], size, &remap);
+            vb.copy(&buf, &x.entry);
+        }
+        let x = self.indices.as_ref().unwrap();
+        vb.copy(&encode_indices(&indices, x.data_type), &x.entry);
+        for x in &self.lods {
+            let idx = decode_indices(vb.read(&x.indices.entry), x.indices.data_type, x.count);
+            let (idx, clusters) = optimize::tipsify(&idx, n, cache_size);
+            let idx: Vec<_> = optimize::sort_clusters(&idx, &pos, &clusters)
+                .iter()
+                .map(|&x| remap[x as usize])
+                .collect();
+            vb.copy(&encode_indices(&idx, x.indices.data_type), &x.indices.entry);
+        }
+        if let Some(x) = &self.meshlets {
+            let verts = decode_indices(vb.read(&x.vertices), DataType::U32, x.vert_count);
+            let verts: Vec<_> = verts.iter().map(|&x| remap[x as usize]).collect();
+            vb.copy(&encode_indices(&verts, DataType::U32), &x.vertices);
+        }
+        for x in self.morphs.iter().flat_map(|x| &x.deltas).flatten() {
+            let buf = optimize::remap_data(&vb.read(x)[..12 * n], 12, &remap);
+            vb.copy(&buf, x);
+        }
+        Ok(CacheReport { before, after })
+    }
+
+    /// Creates levels of detail for the current primitive.
+    ///
+    /// Each target in `targets` produces a level of detail,
+    /// simplified from the primitive itself using quadric
+    /// error metrics. Levels share the primitive's vertex
+    /// data, and differences in [`Semantic::Normal`],
+    /// texture coordinates, [`Semantic::Color0`] and skin
+    /// weights are taken into account. Vertices are never
+    /// split from the attributes they share with their
+    /// neighbors, so UV seams are preserved.
+    ///
+    /// Levels are expected to be given from finest to
+    /// coarsest; levels that do not reduce the triangle
+    /// count of the previous one are discarded.
+    ///
+    /// Fails if the primitive is not an indexed triangle list.
+    ///
+    /// NOTE: One must ensure that the primitive is pushed with
+    /// [`Topology::Triangle`].
+    pub fn simplify_primitive(&mut self, targets: &[LodTarget]) -> io::Result<&mut Self> {
+        let err = io::Error::from(io::ErrorKind::InvalidInput);
+        let Some(indices) = self.index_data() else {
+            eprintln!("[!] mesh::Builder: cannot simplify non-indexed primitive");
+            return Err(err);
+        };
+        let n = self.vert_count;
+        if !indices.len().is_multiple_of(3) || indices.iter().any(|&x| x as usize >= n) {
+            return Err(err);
+        }
+        let Some(pos) = self.semantic_f32(Semantic::Position) else {
+            eprintln!("[!] mesh::Builder: primitives must have position semantic");
+            return Err(err);
+        };
+        let pos: Vec<_> = (pos.chunks_exact(3))
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect();
+
+        // Interleave the attributes.
+        let sems = [Semantic::Normal, Semantic::Color0, Semantic::Color1]
+            .into_iter()
+            .chain((0..Semantic::TEX_COORD_N).filter_map(Semantic::tex_coord));
+        let attrs: Vec<_> = sems.filter_map(|x| self.semantic_f32(x)).collect();
+        let attr_n = attrs.iter().map(|x| x.len() / n).sum();
+        let mut attr = Vec::with_capacity(attr_n * n);
+        for i in 0..n {
+            for x in &attrs {
+                let m = x.len() / n;
+                attr.extend_from_slice(&x[i * m..i * m + m]);
+            }
+        }
+        let skin = match (
+            self.influences(Semantic::Joints0, Semantic::Weights0),
+            self.influences(Semantic::Joints1, Semantic::Weights1),
+        ) {
+            (Some(a), b) => (a.iter().enumerate())
+                .map(|(i, a)| {
+                    let b = b.as_ref().map_or([(0, 0.0); 4], |b| b[i]);
+                    [a[0], a[1], a[2], a[3], b[0], b[1], b[2], b[3]]
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let verts = simplify::Vertices {
+            pos: &pos,
+            attr: &attr,
+            attr_n,
+            skin: &skin,
+        };
+
+        let data_type = self.indices.as_ref().unwrap().data_type;
+        let stride = if data_type == DataType::U32 { 4 } else { 2 };
+        let mut count = indices.len();
+        let mut lods = vec![];
+        for x in targets {
+            let (idx, error) = simplify::simplify(&verts, &indices, x.triangles, x.max_error);
+            if idx.len() >= count {
+                continue;
+            }
+            count = idx.len();
+            let entry = match self.alloc(idx.len().max(1) * stride, stride) {
+                Ok(x) => x,
+                Err(e) => {
+                    let vb = self.vert_buf.read().unwrap();
+                    lods.into_iter()
+                        .for_each(|x: Lod| vb.dealloc(x.indices.entry));
+                    return Err(e);
+                }
+            };
+            (self.vert_buf.write().unwrap()).copy(&encode_indices(&idx, data_type), &entry);
+            lods.push(Lod {
+                indices: DataEntry {
+                    data_type,
+                    normalized: false,
+                    entry,
+                },
+                count,
+                error,
+            });
+        }
+        let vb = self.vert_buf.read().unwrap();
+        self.lods
+            .drain(..)
+            .for_each(|x| vb.dealloc(x.indices.entry));
+        drop(vb);
+        self.lods = lods;
+        Ok(self)
+    }
+
+    /// Splits the current primitive into meshlets.
+    ///
+    /// Each [`Meshlet`] has at most `MESHLET_MAX_VERTICES`
+    /// vertices and `MESHLET_MAX_TRIANGLES` triangles, which
+    /// are taken in index order. Consider calling
+    /// `optimize_primitive` first to improve locality.
+    ///
+    /// Fails if the primitive is not a triangle list.
+    ///
+    /// NOTE: One must ensure that the primitive is pushed with
+    /// [`Topology::Triangle`].
+    pub fn build_meshlets(&mut self) -> io::Result<&mut Self> {
+        let err = io::Error::from(io::ErrorKind::InvalidInput);
+        let n = self.vert_count;
+        let indices = self.index_data().unwrap_or_else(|| (0..n as u32).collect());
+        if indices.is_empty()
+            || !indices.len().is_multiple_of(3)
+            || indices.iter().any(|&x| x as usize >= n)
+        {
+            return Err(err);
+        }
+        let Some(pos) = self.semantic_f32(Semantic::Position) else {
+            eprintln!("[!] mesh::Builder: primitives must have position semantic");
+            return Err(err);
+        };
+        let pos: Vec<_> = (pos.chunks_exact(3))
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect();
+
+        let clusters = meshlet::build(&indices, n, MESHLET_MAX_VERTICES, MESHLET_MAX_TRIANGLES);
+        let mut meshlets = Vec::with_capacity(clusters.len());
+        let (mut verts, mut tris) = (vec![], vec![]);
+        for x in &clusters {
+            let (bounds, cone_axis, cone_cutoff) = meshlet::bounds(x, &pos);
+            meshlets.push(Meshlet {
+                vertex_offset: verts.len() as u32,
+                vertex_count: x.verts.len() as u32,
+                triangle_offset: tris.len() as u32,
+                triangle_count: x.tris.len() as u32,
+                bounds,
+                cone_axis,
+                cone_cutoff,
+            });
+            verts.extend_from_slice(&x.verts);
+            tris.extend(x.tris.iter().map(|&x| meshlet::pack(x)));
+        }
+
+        let bufs = [
+            meshlet::encode(&meshlets),
+            encode_indices(&verts, DataType::U32),
+            encode_indices(&tris, DataType::U32),
+        ];
+        let mut entries = vec![];
+        for buf in &bufs {
+            match self.alloc(buf.len(), 4) {
+                Ok(x) => entries.push(x),
+                Err(e) => {
+                    let vb = self.vert_buf.read().unwrap();
+                    entries.into_iter().for_each(|x| vb.dealloc(x));
+                    return Err(e);
+                }
+            }
+        }
+        let mut vb = self.vert_buf.write().unwrap();
+        for (buf, entry) in bufs.iter().zip(&entries) {
+            vb.copy(buf, entry);
+        }
+        if let Some(x) = self.meshlets.take() {
+            x.into_entries().into_iter().for_each(|x| vb.dealloc(x));
+        }
+        drop(vb);
+        let mut entries = entries.into_iter();
+        self.meshlets = Some(Meshlets {
+            count: meshlets.len(),
+            vert_count: verts.len(),
+            tri_count: tris.len(),
+            meshlets: entries.next().unwrap(),
+            vertices: entries.next().unwrap(),
+            triangles: entries.next().unwrap(),
+        });
+        Ok(self)
+    }
+
     /// Consumes the current state to create a [`Primitive`].
     ///
     /// If this method fails, the state is left untouched.
     /// One may call `clear_primitive` to start over.
     pub fn push_primitive(&mut self, topology: Topology) -> io::Result<&mut Self> {
+        self.push_primitive_with(topology, &Generate::default())
+    }
+
+    /// Consumes the current state to create a [`Primitive`],
+    /// generating missing data as `generate` dictates.
+    ///
+    /// Data is only generated for triangle topologies, and
+    /// it is stored as `set_vertex_format` dictates.
+    ///
+    /// If this method fails, the state is left untouched,
+    /// except for data that has been generated.
+    pub fn push_primitive_with(
+        &mut self,
+        topology: Topology,
+        generate: &Generate,
+    ) -> io::Result<&mut Self> {
         // Check correctness before consuming any state.
         let err = io::Error::from(io::ErrorKind::InvalidInput);
         if self.mask & Self::POSITION == 0 {
@@ -632,19 +2097,40 @@ impl Builder {
                 }
             }
         }
+        if self.morph.deltas.iter().any(Option::is_some) {
+            eprintln!("[!] mesh::Builder: morph target set but not pushed");
+            return Err(err);
+        }
+        if let Some(x) = self.primitives.first() {
+            if x.morphs.len() != self.morphs.len() {
+                eprintln!(
+                    "[!] mesh::Builder: primitives must have the same number of morph targets"
+                );
+                return Err(err);
+            }
+        }
         // TODO: More checks.
 
+        self.generate(topology, count, generate)?;
+
         // Now we can consume the state.
-        let mut semantics = none_semantics();
-        mem::swap(&mut semantics, &mut self.semantics);
+        let semantics = mem::replace(&mut self.semantics, Semantics::new());
         let indices = mem::take(&mut self.indices);
+        let vert_count = self.vert_count;
         self.vert_count = 0;
         self.idx_count = 0;
+        let lods = mem::take(&mut self.lods);
+        let meshlets = self.meshlets.take();
+        let morphs = mem::take(&mut self.morphs);
         let material = self.material.take();
         self.primitives.push(Primitive {
             vert_buf: Arc::clone(&self.vert_buf),
             semantics,
             indices,
+            lods,
+            meshlets,
+            morphs,
+            vert_count,
             count,
             material,
             topology,
@@ -653,19 +2139,162 @@ impl Builder {
         Ok(self)
     }
 
+    /// Generates missing normals and tangents.
+    fn generate(
+        &mut self,
+        topology: Topology,
+        count: usize,
+        generate: &Generate,
+    ) -> io::Result<()> {
+        let missing = |x: Semantic| self.semantics.get(x).is_none();
+        let normals = generate.normals.filter(|_| missing(Semantic::Normal));
+        let tangents = generate.tangents && missing(Semantic::Tangent);
+        if normals.is_none() && !tangents {
+            return Ok(());
+        }
+        let indices = self.index_data();
+        let tris = generate::triangles(topology, count, indices.as_deref());
+        if tris.is_empty() {
+            return Ok(());
+        }
+        let n = self.vert_count;
+        if tris.iter().flatten().any(|&x| x as usize >= n) {
+            eprintln!("[!] mesh::Builder: index out of bounds");
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        let err = io::Error::from(io::ErrorKind::InvalidData);
+        let pos = self.semantic_f32(Semantic::Position).ok_or(err)?;
+        let pos: Vec<Vec3<f32>> = pos
+            .chunks_exact(3)
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect();
+        if let Some(weight) = normals {
+            let nrm = generate::normals(&pos, &tris, weight, generate.crease_angle);
+            let buf: Vec<u8> = nrm
+                .iter()
+                .flat_map(|x| <[f32; 3]>::from(x).map(f32::to_ne_bytes))
+                .flatten()
+                .collect();
+            self.set_semantic_with(
+                &buf[..],
+                Semantic::Normal,
+                DataType::F32x3,
+                false,
+                None,
+                None,
+            )?;
+        }
+        if !tangents {
+            return Ok(());
+        }
+        let (Some(nrm), Some(uv)) = (
+            self.semantic_f32(Semantic::Normal),
+            self.semantic_f32(Semantic::TexCoord0),
+        ) else {
+            eprintln!("[!] mesh::Builder: tangents require normal and tex. coord. semantics");
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        };
+        let nrm: Vec<_> = nrm
+            .chunks_exact(3)
+            .map(|x| Vec3::new(x[0], x[1], x[2]))
+            .collect();
+        let uv: Vec<_> = uv.chunks_exact(2).map(|x| Vec2::new(x[0], x[1])).collect();
+        let tan = generate::tangents(&pos, &nrm, &uv, &tris);
+        let buf: Vec<u8> = tan
+            .iter()
+            .flat_map(|x| <[f32; 4]>::from(x).map(f32::to_ne_bytes))
+            .flatten()
+            .collect();
+        self.set_semantic_with(
+            &buf[..],
+            Semantic::Tangent,
+            DataType::F32x4,
+            false,
+            None,
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Reads the current semantic data as `f32`s, in the
+    /// semantic's canonical layout.
+    fn semantic_f32(&self, semantic: Semantic) -> Option<Vec<f32>> {
+        let data = self.semantics.get(semantic)?;
+        let size = data.data_type.layout().size() * self.vert_count;
+        let vb = self.vert_buf.read().unwrap();
+        canonical_f32(
+            &vb.read(&data.entry)[..size],
+            semantic,
+            data.data_type,
+            data.normalized,
+        )
+    }
+
+    /// Reads the current joint indices and weights of a set
+    /// of four influences.
+    fn influences(&self, joints: Semantic, weights: Semantic) -> Option<Vec<[(u32, f32); 4]>> {
+        let n = self.vert_count;
+        let x = self.semantics.get(joints)?;
+        let w = self.semantic_f32(weights)?;
+        if x.data_type.components() != 4 || w.len() != 4 * n {
+            return None;
+        }
+        let vb = self.vert_buf.read().unwrap();
+        let buf = vb.read(&x.entry);
+        let size = x.data_type.scalar().layout().size();
+        let joint = |i: usize| {
+            let x = &buf[i * size..i * size + size];
+            match size {
+                1 => x[0] as u32,
+                2 => u16::from_ne_bytes([x[0], x[1]]) as u32,
+                _ => u32::from_ne_bytes([x[0], x[1], x[2], x[3]]),
+            }
+        };
+        Some(
+            (0..n)
+                .map(|i| [0, 1, 2, 3].map(|j| (joint(4 * i + j), w[4 * i + j])))
+                .collect(),
+        )
+    }
+
+    /// Reads the current index data.
+    fn index_data(&self) -> Option<Vec<u32>> {
+        let data = self.indices.as_ref()?;
+        let vb = self.vert_buf.read().unwrap();
+        Some(decode_indices(
+            vb.read(&data.entry),
+            data.data_type,
+            self.idx_count,
+        ))
+    }
+
+    /// Allocates a vertex buffer entry, only taking the
+    /// write lock if the buffer needs to grow.
+    fn alloc(&self, size: usize, align: usize) -> io::Result<VarEntry> {
+        let entry = self.vert_buf.read().unwrap().try_alloc(size, align);
+        match entry {
+            Some(x) => Ok(x),
+            None => self.vert_buf.write().unwrap().alloc(size, align),
+        }
+    }
+
     /// Clears the current primitive state.
     pub fn clear_primitive(&mut self) -> &mut Self {
-        // TODO: It may be better locking at `dealloc`
-        // call sites.
-        let mut vb = self.vert_buf.write().unwrap();
-        for i in &mut self.semantics {
-            if let Some(x) = i.take() {
-                vb.dealloc(x.entry);
-            }
+        let vb = self.vert_buf.read().unwrap();
+        for x in self.semantics.drain() {
+            vb.dealloc(x.entry);
         }
         if let Some(x) = self.indices.take() {
             vb.dealloc(x.entry);
         }
+        self.lods
+            .drain(..)
+            .for_each(|x| vb.dealloc(x.indices.entry));
+        if let Some(x) = self.meshlets.take() {
+            x.into_entries().into_iter().for_each(|x| vb.dealloc(x));
+        }
+        mem::take(&mut self.morph).dealloc(&vb);
+        self.morphs.drain(..).for_each(|x| x.dealloc(&vb));
         self.vert_count = 0;
         self.idx_count = 0;
         self.material = None;
@@ -680,32 +2309,549 @@ impl Builder {
     /// pushed up to this point.
     /// The current primitive state is unaffected.
     ///
-    /// Fails if no primitive has been pushed yet.
+    /// The default morph target weights are consumed as well.
+    ///
+    /// Fails if no primitive has been pushed yet, or if the
+    /// number of weights does not match the number of morph
+    /// targets.
     pub fn create(&mut self) -> io::Result<Mesh> {
-        if !self.primitives.is_empty() {
-            Ok(Mesh(mem::take(&mut self.primitives)))
-        } else {
-            Err(io::Error::from(io::ErrorKind::InvalidInput))
+        let err = io::Error::from(io::ErrorKind::InvalidInput);
+        let Some(x) = self.primitives.first() else {
+            return Err(err);
+        };
+        let n = x.morphs.len();
+        if !self.weights.is_empty() && self.weights.len() != n {
+            eprintln!("[!] mesh::Builder: expected {} morph target weights", n);
+            return Err(err);
         }
+        let weights = mem::take(&mut self.weights);
+        Ok(Mesh {
+            primitives: mem::take(&mut self.primitives),
+            weights: if weights.is_empty() {
+                vec![0.0; n]
+            } else {
+                weights
+            },
+        })
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Builder {
+    fn drop(&mut self) {
+        self.clear_primitive();
+    }
+}
+
+// TODO: Test further (vertex buffer in particular).
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    fn to_bytes(data: &[f32]) -> Vec<u8> {
+        data.iter().flat_map(|x| x.to_ne_bytes()).collect()
+    }
+
+    // Reads the data of `sem` as `f32`s, in the semantic's
+    // canonical layout.
+    fn read_f32(prim: &Primitive, sem: Semantic) -> Vec<f32> {
+        let x = prim.semantic_data(sem).unwrap();
+        let size = x.data_type().layout().size() * prim.vert_count;
+        let vb = prim.vertex_buffer().read().unwrap();
+        canonical_f32(
+            &vb.read(x.entry())[..size],
+            sem,
+            x.data_type(),
+            x.normalized(),
+        )
+        .unwrap()
+    }
+
+    // Reads the index data.
+    fn read_indices(prim: &Primitive) -> Vec<u32> {
+        let x = prim.index_data().unwrap();
+        let vb = prim.vertex_buffer().read().unwrap();
+        decode_indices(vb.read(x.entry()), x.data_type(), prim.vertex_count())
+    }
+
+    // Sets the data of a `n` by `n` grid of quads in the XY
+    // plane, with triangles emitted in an order that is
+    // unfriendly to the cache. Texture coordinates and colors
+    // are unique per vertex.
+    fn set_grid(bld: &mut Builder, n: u32) -> &mut Builder {
+        let mut idx = vec![];
+        for x in 0..n {
+            for y in 0..n {
+                let v = y * (n + 1) + x;
+                idx.extend([v, v + 1, v + n + 2, v, v + n + 2, v + n + 1]);
+            }
+        }
+        let m = (n + 1) * (n + 1);
+        let (mut pos, mut uv, mut col) = (vec![], vec![], vec![]);
+        for i in 0..m {
+            let (x, y) = ((i % (n + 1)) as f32, (i / (n + 1)) as f32);
+            pos.extend([x, y, 0.0]);
+            uv.extend([x / n as f32, y / n as f32]);
+            col.extend([i as f32 / m as f32, 0.5, 1.0, 1.0]);
+        }
+        bld.set_vertex_count(m as usize)
+            .set_semantic(
+                &to_bytes(&pos)[..],
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_semantic(
+                &to_bytes(&uv)[..],
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_semantic(
+                &to_bytes(&col)[..],
+                Semantic::Color0,
+                DataType::F32x4,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_indexed(
+                &encode_indices(&idx, DataType::U32)[..],
+                idx.len(),
+                DataType::U32,
+            )
+            .unwrap()
+    }
+
+    // Gathers the attributes of every triangle's corners,
+    // rotated to keep the winding order, sorted.
+    fn triangle_attrs(prim: &Primitive, idx: &[u32]) -> Vec<[Vec<u32>; 3]> {
+        let data: Vec<_> = prim.semantics().map(|x| read_f32(prim, x)).collect();
+        let attr = |v: u32| -> Vec<u32> {
+            let v = v as usize;
+            data.iter()
+                .flat_map(|d| {
+                    let n = d.len() / prim.vert_count;
+                    d[v * n..v * n + n].iter().map(|x| x.to_bits())
+                })
+                .collect()
+        };
+        let mut tris: Vec<_> = idx
+            .chunks_exact(3)
+            .map(|x| {
+                let t = [0, 1, 2].map(|i| attr(x[i]));
+                let i = (0..3).min_by(|&i, &j| t[i].cmp(&t[j])).unwrap();
+                [0, 1, 2].map(|j| t[(i + j) % 3].clone())
+            })
+            .collect();
+        tris.sort();
+        tris
+    }
+
+    // Curves the positions of a grid set by `set_grid`.
+    fn curve_grid(bld: &mut Builder, n: u32) -> &mut Builder {
+        let c = n as f32 / 2.0;
+        let pos: Vec<_> = (0..(n + 1) * (n + 1))
+            .flat_map(|i| {
+                let (x, y) = ((i % (n + 1)) as f32, (i / (n + 1)) as f32);
+                [x, y, ((x - c) * (x - c) + (y - c) * (y - c)) * 0.1]
+            })
+            .collect();
+        bld.set_semantic(
+            &to_bytes(&pos)[..],
+            Semantic::Position,
+            DataType::F32x3,
+            false,
+            None,
+        )
+        .unwrap()
+    }
+
+    // Sets the data of a 1x1 quad in the XY plane, whose
+    // texture coordinates match its positions.
+    fn set_quad(bld: &mut Builder) -> &mut Builder {
+        let pos = to_bytes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0]);
+        let idx: Vec<u8> = [0u16, 1, 2, 0, 2, 3]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        bld.set_vertex_count(4)
+            .set_semantic(&pos[..], Semantic::Position, DataType::F32x3, false, None)
+            .unwrap()
+            .set_indexed(&idx[..], 6, DataType::U16)
+            .unwrap()
+    }
+
+    #[test]
+    fn convert_data() {
+        let uv = [0.0, 1.0, 0.25, 0.5, 1.0, 0.75];
+        let buf = to_bytes(&uv);
+        for dt in [
+            DataType::U16x2,
+            DataType::U8x2,
+            DataType::I16x2,
+            DataType::I8x2,
+        ] {
+            let q = convert(&buf, Semantic::TexCoord0, DataType::F32x2, false, dt).unwrap();
+            assert_eq!(q.len(), 6 * dt.scalar().layout().size());
+            let r = convert(&q, Semantic::TexCoord0, dt, true, DataType::F32x2).unwrap();
+            let r = decode_norm(&r, DataType::F32x2).unwrap();
+            let eps = if dt.scalar().layout().size() == 1 {
+                1.0 / 254.0
+            } else {
+                1.0 / 65534.0
+            };
+            for (x, y) in uv.iter().zip(&r) {
+                assert!((x - y).abs() <= eps);
+            }
+        }
+
+        let wgt = [255u8, 0, 0, 0, 128, 127, 0, 0];
+        let r = convert(
+            &wgt,
+            Semantic::Weights0,
+            DataType::U8x4,
+            true,
+            DataType::F32x4,
+        )
+        .unwrap();
+        let r = decode_norm(&r, DataType::F32x4).unwrap();
+        assert_eq!(r[..4], [1.0, 0.0, 0.0, 0.0]);
+        assert!((r[4] + r[5] - 1.0).abs() <= f32::EPSILON);
+
+        let n = [0.0, 0.0, 1.0, 0.6, -0.8, 0.0, -0.48, 0.6, -0.64];
+        let buf = to_bytes(&n);
+        for (dt, eps) in [
+            (DataType::I16x2, 1e-4),
+            (DataType::U16x2, 1e-4),
+            (DataType::I8x2, 2e-2),
+            (DataType::U8x2, 2e-2),
+        ] {
+            let q = convert(&buf, Semantic::Normal, DataType::F32x3, false, dt).unwrap();
+            assert_eq!(q.len(), 3 * dt.layout().size());
+            let r = convert(&q, Semantic::Normal, dt, true, DataType::F32x3).unwrap();
+            let r = decode_norm(&r, DataType::F32x3).unwrap();
+            for (x, y) in n.iter().zip(&r) {
+                assert!((x - y).abs() <= eps);
+            }
+        }
+
+        assert!(convert(
+            &buf,
+            Semantic::TexCoord0,
+            DataType::F32x3,
+            false,
+            DataType::I16x2
+        )
+        .is_none());
+        assert!(convert(
+            &buf,
+            Semantic::Normal,
+            DataType::F32x3,
+            false,
+            DataType::I32x3
+        )
+        .is_none());
+        assert!(convert(
+            &wgt,
+            Semantic::Joints0,
+            DataType::U8x4,
+            false,
+            DataType::U16x4
+        )
+        .is_none());
+
+        // Integers that are not normalized keep their values.
+        let pos: Vec<u8> = [-3i16, 0, 200]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        let (sem, dt) = (Semantic::Position, DataType::I16x3);
+        let r = convert(&pos, sem, dt, false, DataType::F32x3).unwrap();
+        assert_eq!(
+            decode_norm(&r, DataType::F32x3).unwrap(),
+            [-3.0, 0.0, 200.0]
+        );
+        assert!(convert(&pos, sem, dt, false, DataType::I8x3).is_none());
+        let (sem, dt) = (Semantic::Normal, DataType::I16x2);
+        assert!(convert(&pos[..4], sem, dt, false, DataType::F32x3).is_none());
+    }
+
+    #[test]
+    fn semantic_slots() {
+        for i in 0..SEMANTIC_N {
+            assert_eq!(Semantic::from_index(i).index(), Some(i));
+        }
+        assert_eq!(Semantic::Custom(3).index(), None);
+        for i in 0..Semantic::TEX_COORD_N {
+            assert!(Semantic::tex_coord(i).unwrap().is_tex_coord());
+        }
+        assert_eq!(Semantic::tex_coord(2), Some(Semantic::TexCoord2));
+        assert_eq!(Semantic::tex_coord(Semantic::TEX_COORD_N), None);
+        assert!(!Semantic::Color0.is_tex_coord());
+        assert!(!Semantic::Tangent.is_tex_coord());
+
+        let uv = to_bytes(&[0.5, 0.25]);
+        let q = VertexFormat::Quantized;
+        let c = VertexFormat::Canonical;
+        let dt = DataType::F32x2;
+        assert_eq!(q.store_as(&uv, Semantic::TexCoord7, dt), DataType::U16x2);
+        assert_eq!(q.store_as(&uv, Semantic::Custom(0), dt), dt);
+        let dt = DataType::U16x2;
+        assert_eq!(c.store_as(&uv, Semantic::TexCoord5, dt), DataType::F32x2);
+        assert_eq!(c.store_as(&uv, Semantic::Custom(0), dt), dt);
+        let wgt = [255, 0, 0, 0];
+        assert_eq!(
+            c.store_as(&wgt, Semantic::Weights1, DataType::U8x4),
+            DataType::F32x4
+        );
+        assert_eq!(
+            c.store_as(&wgt, Semantic::Joints1, DataType::U8x4),
+            DataType::U8x4
+        );
+        for sem in [Semantic::Joints1, Semantic::Custom(1)] {
+            assert!(convert(&wgt, sem, DataType::U8x4, true, DataType::F32x4).is_none());
+        }
+    }
+
+    #[test]
+    fn semantics() {
+        let data = |offset| DataEntry {
+            data_type: DataType::F32x3,
+            normalized: false,
+            entry: VarEntry::dummy(offset, 12),
+        };
+        let offset = |x: Option<&DataEntry>| x.map(|x| x.entry.offset());
+
+        let mut sems = Semantics::new();
+        assert_eq!(sems.keys().count(), 0);
+        assert!(sems.get(Semantic::Position).is_none());
+        assert!(sems.get(Semantic::Custom(0)).is_none());
+        assert!(sems.take(Semantic::Normal).is_none());
+        assert!(sems.take(Semantic::Custom(0)).is_none());
+
+        assert!(sems.insert(Semantic::Custom(7), data(0)).is_none());
+        assert!(sems.insert(Semantic::Normal, data(12)).is_none());
+        assert!(sems.insert(Semantic::Custom(2), data(24)).is_none());
+        assert!(sems.insert(Semantic::Position, data(36)).is_none());
+        assert!(sems.insert(Semantic::Custom(u16::MAX), data(48)).is_none());
+        assert_eq!(
+            sems.keys().collect::<Vec<_>>(),
+            [
+                Semantic::Position,
+                Semantic::Normal,
+                Semantic::Custom(2),
+                Semantic::Custom(7),
+                Semantic::Custom(u16::MAX),
+            ]
+        );
+        assert_eq!(offset(sems.get(Semantic::Position)), Some(36));
+        assert_eq!(offset(sems.get(Semantic::Custom(7))), Some(0));
+        assert!(sems.get(Semantic::Custom(3)).is_none());
+        assert!(sems.get(Semantic::Tangent).is_none());
+        assert_eq!(
+            sems.iter().map(|x| x.entry.offset()).collect::<Vec<_>>(),
+            [36, 12, 24, 0, 48]
+        );
+
+        let prev = sems.insert(Semantic::Normal, data(60));
+        assert_eq!(offset(prev.as_ref()), Some(12));
+        let prev = sems.insert(Semantic::Custom(2), data(72));
+        assert_eq!(offset(prev.as_ref()), Some(24));
+        assert_eq!(offset(sems.get(Semantic::Normal)), Some(60));
+        assert_eq!(offset(sems.get(Semantic::Custom(2))), Some(72));
+        assert_eq!(sems.keys().count(), 5);
+
+        assert_eq!(offset(sems.take(Semantic::Custom(7)).as_ref()), Some(0));
+        assert!(sems.take(Semantic::Custom(7)).is_none());
+        assert_eq!(offset(sems.take(Semantic::Normal).as_ref()), Some(60));
+        assert!(sems.get(Semantic::Normal).is_none());
+        assert_eq!(
+            sems.keys().collect::<Vec<_>>(),
+            [
+                Semantic::Position,
+                Semantic::Custom(2),
+                Semantic::Custom(u16::MAX),
+            ]
+        );
+
+        let drained: Vec<_> = sems.drain().map(|x| x.entry.offset()).collect();
+        assert_eq!(drained, [36, 72, 48]);
+        assert_eq!(sems.keys().count(), 0);
+        assert_eq!(sems.iter().count(), 0);
+        assert!(sems.insert(Semantic::Custom(2), data(0)).is_none());
+        assert_eq!(sems.keys().collect::<Vec<_>>(), [Semantic::Custom(2)]);
     }
-}
 
-impl Default for Builder {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn morph_blend() {
+        let mut pos = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        blend(&mut pos, 3, &[1.0, 0.0, -1.0, 2.0, 2.0, 2.0], 0.5);
+        assert_eq!(pos, [0.5, 1.0, 1.5, 4.0, 5.0, 6.0]);
+        blend(&mut pos, 3, &[1.0, 0.0, -1.0, 2.0, 2.0, 2.0], -0.5);
+        assert_eq!(pos, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        // Handedness is not displaced.
+        let mut tan = vec![1.0, 0.0, 0.0, -1.0, 0.0, 1.0, 0.0, 1.0];
+        blend(&mut tan, 4, &[0.0, 2.0, 0.0, 0.0, 0.0, 4.0], 0.25);
+        assert_eq!(tan, [1.0, 0.5, 0.0, -1.0, 0.0, 1.0, 1.0, 1.0]);
+
+        let nrm = canonical_f32(
+            &[127, 0, 0, 0, 127, 0],
+            Semantic::Normal,
+            DataType::I8x3,
+            true,
+        );
+        assert_eq!(nrm.unwrap(), [1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
     }
-}
 
-impl Drop for Builder {
-    fn drop(&mut self) {
-        self.clear_primitive();
+    #[test]
+    fn vertex_format() {
+        let uv = to_bytes(&[0.0, 1.0, 0.25, 0.5]);
+        let wrap = to_bytes(&[0.0, 1.5, 0.25, 0.5]);
+        let nrm = to_bytes(&[0.0, 0.0, 1.0, 0.6, -0.8, 0.0]);
+        let pos = to_bytes(&[1.0, -2.0, 3.0]);
+        let wgt = [255u8, 0, 0, 0];
+        let (a, c, q) = (
+            VertexFormat::AsIs,
+            VertexFormat::Canonical,
+            VertexFormat::Quantized,
+        );
+
+        for (fmt, buf, sem, dt, exp) in [
+            (
+                a,
+                &uv,
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                DataType::F32x2,
+            ),
+            (
+                a,
+                &wgt.to_vec(),
+                Semantic::Weights0,
+                DataType::U8x4,
+                DataType::U8x4,
+            ),
+            (
+                c,
+                &uv,
+                Semantic::TexCoord1,
+                DataType::U16x2,
+                DataType::F32x2,
+            ),
+            (
+                c,
+                &wgt.to_vec(),
+                Semantic::Weights0,
+                DataType::U8x4,
+                DataType::F32x4,
+            ),
+            (
+                c,
+                &wgt.to_vec(),
+                Semantic::Joints0,
+                DataType::U8x4,
+                DataType::U8x4,
+            ),
+            (
+                c,
+                &wgt.to_vec(),
+                Semantic::Color0,
+                DataType::U8x4,
+                DataType::F32x4,
+            ),
+            (c, &nrm, Semantic::Normal, DataType::I16x2, DataType::F32x3),
+            (
+                c,
+                &pos,
+                Semantic::Position,
+                DataType::I16x3,
+                DataType::F32x3,
+            ),
+            (
+                q,
+                &uv,
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                DataType::U16x2,
+            ),
+            (
+                q,
+                &wrap,
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                DataType::F32x2,
+            ),
+            (q, &nrm, Semantic::Normal, DataType::F32x3, DataType::I16x3),
+            (
+                q,
+                &pos,
+                Semantic::Position,
+                DataType::F32x3,
+                DataType::F32x3,
+            ),
+            (q, &pos, Semantic::Color0, DataType::F32x3, DataType::F32x3),
+            (q, &uv, Semantic::Weights0, DataType::F32x4, DataType::U8x4),
+            (
+                q,
+                &wgt.to_vec(),
+                Semantic::Weights0,
+                DataType::U8x4,
+                DataType::U8x4,
+            ),
+        ] {
+            assert_eq!(fmt.store_as(buf, sem, dt), exp, "{fmt:?} {sem:?} {dt:?}");
+        }
+
+        // Whatever is chosen must be convertible.
+        for (buf, sem, dt) in [
+            (&nrm, Semantic::Normal, DataType::F32x3),
+            (&uv, Semantic::TexCoord0, DataType::F32x2),
+            (&uv, Semantic::Weights0, DataType::F32x4),
+        ] {
+            let to = q.store_as(buf, sem, dt);
+            assert_ne!(to, dt);
+            let r = convert(buf, sem, dt, false, to).unwrap();
+            assert_eq!(c.store_as(&r, sem, to), dt);
+            assert!(convert(&r, sem, to, true, dt).is_some());
+        }
     }
-}
 
-// TODO: Test further (vertex buffer in particular).
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn positions_data() {
+        let pos = [1.0, -2.0, 3.0, 0.5, 0.25, -0.125];
+        let r = decode_positions(&to_bytes(&pos), DataType::F32x3, false).unwrap();
+        assert_eq!(r, [Vec3::new(1.0, -2.0, 3.0), Vec3::new(0.5, 0.25, -0.125)]);
+
+        let buf: Vec<u8> = [i16::MAX, 0, i16::MIN, -16384, 32767, 0]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        let r = decode_positions(&buf, DataType::I16x3, true).unwrap();
+        assert_eq!(r[0], Vec3::new(1.0, 0.0, -1.0));
+        assert!((r[1][0] + 0.5).abs() <= 1e-4);
+        let r = decode_positions(&buf, DataType::I16x3, false).unwrap();
+        assert_eq!(r[0], Vec3::new(32767.0, 0.0, -32768.0));
+        assert_eq!(r[1], Vec3::new(-16384.0, 32767.0, 0.0));
+
+        let r = decode_positions(&[255, 0, 51], DataType::U8x3, true).unwrap();
+        assert_eq!(r, [Vec3::new(1.0, 0.0, 0.2)]);
+
+        assert!(decode_positions(&to_bytes(&pos), DataType::F32x2, false).is_none());
+        assert!(decode_positions(&[0; 12], DataType::U32x3, false).is_none());
+    }
 
     #[test]
     fn create_mesh() {
@@ -714,7 +2860,13 @@ mod tests {
 
         let mesh = bld
             .set_vertex_count(3)
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .push_primitive(Topology::Triangle)
             .unwrap()
@@ -741,7 +2893,13 @@ mod tests {
 
         let mesh = bld
             .set_vertex_count(4)
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .set_indexed(io::repeat(2), 6, DataType::U16)
             .unwrap()
@@ -770,9 +2928,21 @@ mod tests {
 
         let mesh = bld
             .set_vertex_count(500)
-            .set_semantic(io::repeat(1), Semantic::Color0, DataType::F32x4, Some(16))
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Color0,
+                DataType::F32x4,
+                false,
+                Some(16),
+            )
             .unwrap()
-            .set_semantic(io::repeat(2), Semantic::Position, DataType::F32x3, Some(12))
+            .set_semantic(
+                io::repeat(2),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                Some(12),
+            )
             .unwrap()
             .push_primitive(Topology::Point)
             .unwrap()
@@ -802,16 +2972,29 @@ mod tests {
 
         let mesh = bld
             .set_vertex_count(20)
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x4, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x4,
+                false,
+                None,
+            )
             .unwrap()
             .set_semantic(
                 io::repeat(2),
                 Semantic::Normal,
                 DataType::F32x3,
+                false,
                 None, /*Some(16),*/ // TODO: 'not yet implemented'
             )
             .unwrap()
-            .set_semantic(io::repeat(3), Semantic::TexCoord0, DataType::F32x2, None)
+            .set_semantic(
+                io::repeat(3),
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                false,
+                None,
+            )
             .unwrap()
             .set_indexed(io::repeat(4), 30, DataType::U16)
             .unwrap()
@@ -847,16 +3030,34 @@ mod tests {
 
         let mesh = bld
             .set_vertex_count(10)
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .set_indexed(io::repeat(2), 21, DataType::U16)
             .unwrap()
             .push_primitive(Topology::Triangle)
             .unwrap()
             .set_vertex_count(1000)
-            .set_semantic(io::repeat(3), Semantic::TexCoord1, DataType::F32x2, None)
+            .set_semantic(
+                io::repeat(3),
+                Semantic::TexCoord1,
+                DataType::F32x2,
+                false,
+                None,
+            )
             .unwrap()
-            .set_semantic(io::repeat(4), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(4),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .push_primitive(Topology::Point)
             .unwrap()
@@ -902,6 +3103,654 @@ mod tests {
         assert_eq!(p1.topology(), Topology::Point);
     }
 
+    #[test]
+    fn builder_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Builder>();
+        assert_send::<Mesh>();
+    }
+
+    #[test]
+    fn create_mesh_threads() {
+        crate::init();
+        let join: Vec<_> = (0..4)
+            .map(|i| {
+                thread::spawn(move || {
+                    let mut bld = Builder::new();
+                    bld.set_vertex_count(3 * (i + 1))
+                        .set_semantic(
+                            io::repeat(1),
+                            Semantic::Position,
+                            DataType::F32x3,
+                            false,
+                            None,
+                        )
+                        .unwrap()
+                        .push_primitive(Topology::Triangle)
+                        .unwrap();
+                    (bld, 3 * (i + 1))
+                })
+            })
+            .collect();
+        for x in join {
+            // Builders can be moved across threads.
+            let (mut bld, n) = x.join().unwrap();
+            let mesh = bld.create().unwrap();
+            assert_eq!(mesh.primitives()[0].vertex_count(), n);
+        }
+    }
+
+    #[test]
+    fn normalized_data() {
+        crate::init();
+        let nrm = to_bytes(&[0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0]);
+        let mesh = Builder::new()
+            .set_vertex_format(VertexFormat::Quantized)
+            .set_vertex_count(3)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_semantic(&nrm[..], Semantic::Normal, DataType::F32x3, false, None)
+            .unwrap()
+            .set_semantic(
+                io::repeat(0),
+                Semantic::Joints0,
+                DataType::U8x4,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_semantic(
+                io::repeat(0),
+                Semantic::Custom(0),
+                DataType::I16x2,
+                false,
+                None,
+            )
+            .unwrap()
+            .set_indexed(io::repeat(0), 3, DataType::U16)
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+
+        let p0 = &mesh.primitives()[0];
+        let x = p0.semantic_data(Semantic::Position).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::F32x3, false));
+        let x = p0.semantic_data(Semantic::Normal).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::I16x3, true));
+        let x = p0.semantic_data(Semantic::Joints0).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::U8x4, false));
+        let x = p0.semantic_data(Semantic::Custom(0)).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::I16x2, false));
+        assert!(!p0.index_data().unwrap().normalized());
+
+        let pos: Vec<u8> = [-3i16, 0, 200, 1, 2, 3, 0, 0, 0]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        let uv: Vec<u8> = [0u16, 65535, 32768, 0, 65535, 65535]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        let mut bld = Builder::new();
+        bld.set_vertex_format(VertexFormat::Canonical)
+            .set_vertex_count(3)
+            .set_semantic(&pos[..], Semantic::Position, DataType::I16x3, false, None)
+            .unwrap()
+            .set_semantic(&uv[..], Semantic::TexCoord0, DataType::U16x2, true, None)
+            .unwrap()
+            .set_semantic_as(
+                &uv[..],
+                Semantic::TexCoord1,
+                DataType::U16x2,
+                true,
+                None,
+                DataType::U8x2,
+            )
+            .unwrap()
+            .set_semantic_as(
+                &uv[..],
+                Semantic::TexCoord2,
+                DataType::U16x2,
+                false,
+                None,
+                DataType::F32x2,
+            )
+            .unwrap();
+        // Integers that are not normalized cannot be
+        // quantized.
+        assert!(bld
+            .set_semantic_as(
+                &pos[..],
+                Semantic::Normal,
+                DataType::I16x3,
+                false,
+                None,
+                DataType::I8x3,
+            )
+            .is_err());
+        let mesh = bld
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+
+        let p0 = &mesh.primitives()[0];
+        // Positions are never normalized implicitly.
+        let x = p0.semantic_data(Semantic::Position).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::F32x3, false));
+        assert_eq!(
+            read_f32(p0, Semantic::Position),
+            [-3.0, 0.0, 200.0, 1.0, 2.0, 3.0, 0.0, 0.0, 0.0]
+        );
+        let x = p0.semantic_data(Semantic::TexCoord0).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::F32x2, false));
+        let r = read_f32(p0, Semantic::TexCoord0);
+        assert_eq!([r[0], r[1], r[4], r[5]], [0.0, 1.0, 1.0, 1.0]);
+        assert!((r[2] - 0.5).abs() <= 1e-4);
+        let x = p0.semantic_data(Semantic::TexCoord1).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::U8x2, true));
+        let r = read_f32(p0, Semantic::TexCoord1);
+        assert_eq!([r[0], r[1], r[4], r[5]], [0.0, 1.0, 1.0, 1.0]);
+        let x = p0.semantic_data(Semantic::TexCoord2).unwrap();
+        assert_eq!((x.data_type(), x.normalized()), (DataType::F32x2, false));
+        assert_eq!(
+            read_f32(p0, Semantic::TexCoord2),
+            [0.0, 65535.0, 32768.0, 0.0, 65535.0, 65535.0]
+        );
+        assert!(p0.semantic_data(Semantic::Normal).is_none());
+    }
+
+    #[test]
+    fn generate_data() {
+        crate::init();
+        let mut bld = Builder::new();
+        let generate = Generate {
+            normals: Some(NormalWeight::Area),
+            tangents: true,
+            ..Default::default()
+        };
+
+        // Tangents require `TexCoord0`.
+        assert!(set_quad(&mut bld)
+            .push_primitive_with(Topology::Triangle, &generate)
+            .is_err());
+        // Generated normals are kept, though.
+        let uv = to_bytes(&[0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+        let mesh = bld
+            .set_semantic(&uv[..], Semantic::TexCoord0, DataType::F32x2, false, None)
+            .unwrap()
+            .push_primitive_with(Topology::Triangle, &generate)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        assert_eq!(
+            p0.semantic_data_type(Semantic::Normal),
+            Some(DataType::F32x3)
+        );
+        assert_eq!(
+            p0.semantic_data_type(Semantic::Tangent),
+            Some(DataType::F32x4)
+        );
+        let nrm = read_f32(p0, Semantic::Normal);
+        assert!(nrm.chunks(3).all(|x| x == [0.0, 0.0, 1.0]));
+        let tan = read_f32(p0, Semantic::Tangent);
+        assert!(tan.chunks(4).all(|x| x == [1.0, 0.0, 0.0, 1.0]));
+
+        // Existing data is not replaced.
+        let nrm = to_bytes(&[0.0, 1.0, 0.0].repeat(4));
+        let mesh = set_quad(&mut bld)
+            .set_semantic(&nrm[..], Semantic::Normal, DataType::F32x3, false, None)
+            .unwrap()
+            .set_semantic(&uv[..], Semantic::TexCoord0, DataType::F32x2, false, None)
+            .unwrap()
+            .push_primitive_with(Topology::Triangle, &generate)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        assert!(read_f32(p0, Semantic::Normal)
+            .chunks(3)
+            .all(|x| x == [0.0, 1.0, 0.0]));
+        assert!(p0.semantic_data(Semantic::Tangent).is_some());
+
+        // Generated data is stored as `set_vertex_format`
+        // dictates.
+        let mesh = set_quad(bld.set_vertex_format(VertexFormat::Quantized))
+            .set_semantic(&uv[..], Semantic::TexCoord0, DataType::F32x2, false, None)
+            .unwrap()
+            .push_primitive_with(Topology::Triangle, &generate)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        assert_eq!(
+            p0.semantic_data_type(Semantic::Normal),
+            Some(DataType::I16x3)
+        );
+        assert_eq!(
+            p0.semantic_data_type(Semantic::Tangent),
+            Some(DataType::I16x4)
+        );
+        assert_eq!(
+            p0.semantic_data_type(Semantic::TexCoord0),
+            Some(DataType::U16x2)
+        );
+        assert!(read_f32(p0, Semantic::Normal)
+            .chunks(3)
+            .all(|x| x == [0.0, 0.0, 1.0]));
+        let tan = read_f32(p0, Semantic::Tangent);
+        assert!(tan.chunks(4).all(|x| x == [1.0, 0.0, 0.0, 1.0]));
+
+        // Non-triangle topologies generate nothing.
+        let mesh = set_quad(&mut bld)
+            .push_primitive_with(Topology::Point, &generate)
+            .unwrap()
+            .create()
+            .unwrap();
+        assert!(mesh.primitives()[0]
+            .semantic_data(Semantic::Normal)
+            .is_none());
+    }
+
+    #[test]
+    fn optimize_data() {
+        crate::init();
+        let mut bld = Builder::new();
+
+        // Non-indexed primitives cannot be optimized.
+        bld.set_vertex_count(3)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
+            .unwrap();
+        assert!(bld.optimize_primitive(16).is_err());
+        bld.clear_primitive();
+
+        let mesh = set_grid(&mut bld, 8)
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        let before = read_indices(p0);
+        let expected = triangle_attrs(p0, &before);
+
+        set_grid(&mut bld, 8);
+        assert!(bld.optimize_primitive(0).is_err());
+        let report = bld.optimize_primitive(16).unwrap();
+        assert!(report.after.acmr < report.before.acmr, "{report:?}");
+        assert!(report.after.atvr <= report.before.atvr, "{report:?}");
+        let mesh = bld
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        let idx = read_indices(p0);
+        assert_ne!(idx, before);
+        assert_eq!(triangle_attrs(p0, &idx), expected);
+        // Vertices are in order of first use.
+        let mut next = 0;
+        for &i in &idx {
+            assert!(i <= next);
+            next = next.max(i + 1);
+        }
+    }
+
+    #[test]
+    fn lod_data() {
+        crate::init();
+        let mut bld = Builder::new();
+        // Use a vertex buffer of our own, so it can be
+        // compacted without affecting other tests.
+        let vb = Arc::new(RwLock::new(VertBuf::new(VertAlloc::new(0))));
+        bld.vert_buf = Arc::clone(&vb);
+        let used = || vb.read().unwrap().stats().used();
+        let targets = [
+            LodTarget {
+                triangles: 32,
+                max_error: f32::MAX,
+            },
+            LodTarget {
+                triangles: 8,
+                max_error: f32::MAX,
+            },
+        ];
+
+        // Levels of detail are dropped along with the data
+        // they were created from.
+        let other = set_grid(&mut bld, 16)
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let base = used();
+        set_grid(&mut bld, 8);
+        let grid = used();
+        bld.simplify_primitive(&targets).unwrap();
+        assert_eq!(bld.lods.len(), 2);
+        assert!(used() > grid);
+        bld.clear_primitive();
+        assert!(bld.lods.is_empty());
+        assert_eq!(used(), base);
+        set_grid(&mut bld, 8).simplify_primitive(&targets).unwrap();
+        curve_grid(&mut bld, 8);
+        assert!(bld.lods.is_empty());
+        assert_eq!(used(), grid);
+        bld.simplify_primitive(&targets).unwrap();
+        assert_eq!(bld.lods.len(), 2);
+        let idx = encode_indices(&[0, 1, 2], DataType::U16);
+        bld.set_indexed(&idx[..], 3, DataType::U16).unwrap();
+        assert!(bld.lods.is_empty());
+        bld.clear_primitive();
+        assert_eq!(used(), base);
+
+        let mut mesh = curve_grid(set_grid(&mut bld, 8), 8)
+            .simplify_primitive(&targets)
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        assert_eq!(p0.lod_count(), 3);
+        assert_eq!(p0.lod_vertex_count(0), 8 * 8 * 6);
+        assert!(p0.lod_vertex_count(1) <= 32 * 3);
+        assert!(p0.lod_vertex_count(2) <= 8 * 3);
+        assert!(p0.lod_vertex_count(2) < p0.lod_vertex_count(1));
+        assert_eq!(p0.lod_error(0), 0.0);
+        assert!(p0.lod_error(1) > 0.0);
+        assert!(p0.lod_error(2) >= p0.lod_error(1));
+        assert_eq!(p0.select_lod(1.0, 0.0), 0);
+        assert_eq!(p0.select_lod(0.0, 0.0), 2);
+        assert_eq!(p0.select_lod(1.0, p0.lod_error(1)), 1);
+        assert_eq!(p0.select_lod(1.0, p0.lod_error(2)), 2);
+        assert_eq!(p0.select_lod(2.0, p0.lod_error(2)), 1);
+
+        // Levels of detail are relocated by compaction.
+        let read = |p: &Primitive, level: usize| {
+            let x = p.lod_index_data(level).unwrap();
+            let buf = vb.read().unwrap().read(x.entry()).to_vec();
+            (x.entry().offset(), buf)
+        };
+        let before: Vec<_> = (0..3).map(|i| read(p0, i)).collect();
+        drop(other);
+        // SAFETY: `mesh` is the only mesh in `vb`.
+        unsafe { compact(&vb, &mut [&mut mesh]).unwrap() };
+        let p0 = &mesh.primitives()[0];
+        let mut moved = false;
+        for (i, (off, buf)) in before.into_iter().enumerate() {
+            let x = read(p0, i);
+            assert!(x.0 <= off);
+            assert_eq!(x.1, buf);
+            moved |= i > 0 && x.0 < off;
+        }
+        assert!(moved);
+        assert_eq!(p0.lod_count(), 3);
+    }
+
+    #[test]
+    fn compact_data() {
+        crate::init();
+        let mut bld = Builder::new();
+        // See `lod_data`.
+        let vb = Arc::new(RwLock::new(VertBuf::new(VertAlloc::new(0))));
+        bld.vert_buf = Arc::clone(&vb);
+        let mut grid = |n| {
+            set_grid(&mut bld, n)
+                .push_primitive(Topology::Triangle)
+                .unwrap()
+                .create()
+                .unwrap()
+        };
+        let mut m1 = grid(8);
+        let m2 = grid(32);
+        let mut m3 = grid(4);
+        let entries = |m: &Mesh| {
+            let p = &m.primitives()[0];
+            (p.semantics().map(|x| p.semantic_data(x).unwrap()))
+                .chain(p.index_data())
+                .map(|x| {
+                    (
+                        x.entry().offset(),
+                        vb.read().unwrap().read(x.entry()).to_vec(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let e1 = entries(&m1);
+        let e3 = entries(&m3);
+
+        // Nothing to move, but trailing memory is released.
+        let size = vb.read().unwrap().stats().total();
+        // SAFETY: `vb` holds no other meshes.
+        let freed = unsafe { compact(&vb, &mut [&mut m1, &mut m3]).unwrap() };
+        let size = size - freed;
+        assert_eq!(vb.read().unwrap().stats().total(), size);
+        assert_eq!(entries(&m1), e1);
+        assert_eq!(entries(&m3), e3);
+
+        drop(m2);
+        let used = vb.read().unwrap().stats().used();
+        // SAFETY: `vb` holds no other meshes.
+        let freed = unsafe { compact(&vb, &mut [&mut m1, &mut m3]).unwrap() };
+        let stats = vb.read().unwrap().stats();
+        assert!(freed > 0);
+        assert_eq!(stats.total(), size - freed);
+        assert_eq!(stats.used(), used);
+        assert_eq!(entries(&m1), e1);
+        let moved = entries(&m3);
+        assert_eq!(moved.len(), e3.len());
+        for (x, y) in moved.iter().zip(&e3) {
+            assert!(x.0 < y.0);
+            assert_eq!(x.1, y.1);
+        }
+        drop((m1, m3));
+        assert_eq!(vb.read().unwrap().stats().used(), 0);
+    }
+
+    #[test]
+    #[ignore]
+    // NOTE: This cannot run in parallel with other tests.
+    fn compact_vertex_buffer_data() {
+        crate::init();
+        let mut bld = Builder::new();
+        let mut m1 = set_grid(&mut bld, 8)
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let m2 = set_grid(&mut bld, 32)
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let mut m3 = set_quad(&mut bld)
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let pos = |m: &Mesh| read_f32(&m.primitives()[0], Semantic::Position);
+        let (p1, p3) = (pos(&m1), pos(&m3));
+        drop(m2);
+        // SAFETY: No other meshes are alive.
+        let freed = unsafe { compact_vertex_buffer(&mut [&mut m1, &mut m3]).unwrap() };
+        assert!(freed > 0);
+        assert_eq!(pos(&m1), p1);
+        assert_eq!(pos(&m3), p3);
+    }
+
+    #[test]
+    fn meshlet_data() {
+        crate::init();
+        let mut bld = Builder::new();
+
+        // Meshlets are dropped along with the data they were
+        // built from.
+        set_grid(&mut bld, 16).build_meshlets().unwrap();
+        assert!(bld.meshlets.is_some());
+        curve_grid(&mut bld, 16);
+        assert!(bld.meshlets.is_none());
+        bld.build_meshlets().unwrap();
+        let idx = encode_indices(&[0, 1, 2], DataType::U16);
+        bld.set_indexed(&idx[..], 3, DataType::U16).unwrap();
+        assert!(bld.meshlets.is_none());
+        bld.clear_primitive();
+        // Even if the primitive was not indexed.
+        let pos = to_bytes(&[0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        bld.set_vertex_count(3)
+            .set_semantic(&pos[..], Semantic::Position, DataType::F32x3, false, None)
+            .unwrap()
+            .build_meshlets()
+            .unwrap();
+        assert!(bld.meshlets.is_some());
+        bld.set_indexed(&idx[..], 3, DataType::U16).unwrap();
+        assert!(bld.meshlets.is_none());
+        bld.clear_primitive();
+
+        set_grid(&mut bld, 16).build_meshlets().unwrap();
+        bld.optimize_primitive(16).unwrap();
+        let mesh = bld
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap();
+        let p0 = &mesh.primitives()[0];
+        let meshlets = p0.meshlets();
+        let verts = p0.meshlet_vertices();
+        let tris = p0.meshlet_triangles();
+        assert_eq!(meshlets.len(), p0.meshlet_count());
+        assert!(meshlets.len() >= 512 / MESHLET_MAX_TRIANGLES);
+        let (mut v, mut t) = (0, 0);
+        let mut out = vec![];
+        for x in &meshlets {
+            assert_eq!((x.vertex_offset, x.triangle_offset), (v, t));
+            assert!(x.vertex_count as usize <= MESHLET_MAX_VERTICES);
+            assert!(x.triangle_count as usize <= MESHLET_MAX_TRIANGLES);
+            v += x.vertex_count;
+            t += x.triangle_count;
+            let verts = &verts[x.vertex_offset as usize..v as usize];
+            for tri in &tris[x.triangle_offset as usize..t as usize] {
+                assert!(tri.iter().all(|&i| (i as u32) < x.vertex_count));
+                out.extend(tri.map(|i| verts[i as usize]));
+            }
+        }
+        assert_eq!((v as usize, t as usize), (verts.len(), tris.len()));
+
+        // The meshlets reproduce the (optimized) triangles.
+        let sorted = |idx: &[u32]| {
+            let mut t: Vec<_> = idx
+                .chunks_exact(3)
+                .map(|x| {
+                    let i = (0..3).min_by_key(|&i| x[i]).unwrap();
+                    [x[i], x[(i + 1) % 3], x[(i + 2) % 3]]
+                })
+                .collect();
+            t.sort();
+            t
+        };
+        assert_eq!(sorted(&out), sorted(&read_indices(p0)));
+    }
+
+    #[test]
+    fn morph_targets() {
+        crate::init();
+        let mut bld = Builder::new();
+        let nrm = to_bytes(&[0.0, 0.0, 1.0].repeat(4));
+        let dz = to_bytes(&[0.0, 0.0, 1.0].repeat(4));
+        let dx = to_bytes(&[1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        let dn = to_bytes(&[1.0, 0.0, -1.0].repeat(4));
+        let set = |bld: &mut Builder| {
+            set_quad(bld)
+                .set_semantic(&nrm[..], Semantic::Normal, DataType::F32x3, false, None)
+                .unwrap()
+                .set_morph_target(&dz[..], Semantic::Position, DataType::F32x3, false)
+                .unwrap()
+                .push_morph_target()
+                .unwrap();
+        };
+
+        // Targets must displace something, and do so with
+        // three components.
+        set_quad(&mut bld);
+        assert!(bld.push_morph_target().is_err());
+        assert!(bld
+            .set_morph_target(&dz[..], Semantic::Position, DataType::F32x2, false)
+            .is_err());
+        assert!(bld
+            .set_morph_target(&dz[..], Semantic::Color0, DataType::F32x3, false)
+            .is_err());
+        assert!(bld.push_morph_target().is_err());
+        // Targets must be pushed before the primitive.
+        bld.set_morph_target(&dz[..], Semantic::Position, DataType::F32x3, false)
+            .unwrap();
+        assert!(bld.push_primitive(Topology::Triangle).is_err());
+        bld.clear_primitive();
+
+        // Every primitive must have the same number of targets.
+        set(&mut bld);
+        bld.set_morph_target(&dx[..], Semantic::Position, DataType::F32x3, false)
+            .unwrap()
+            .set_morph_target(&dn[..], Semantic::Normal, DataType::F32x3, false)
+            .unwrap()
+            .push_morph_target()
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap();
+        set(&mut bld);
+        assert!(bld.push_primitive(Topology::Triangle).is_err());
+        // Setting a semantic twice replaces its displacements.
+        bld.set_morph_target(&dx[..], Semantic::Normal, DataType::F32x3, false)
+            .unwrap()
+            .set_morph_target(&dn[..], Semantic::Normal, DataType::F32x3, false)
+            .unwrap()
+            .push_morph_target()
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap();
+
+        // Weights default to zero.
+        let mesh = bld.create().unwrap();
+        assert_eq!(mesh.weights(), [0.0, 0.0]);
+        let p0 = &mesh.primitives()[0];
+        assert_eq!(p0.morph_target_count(), 2);
+        let m = p0.morph(mesh.weights()).unwrap();
+        assert_eq!(m.positions[2], Vec3::new(1.0, 1.0, 0.0));
+        assert!(m.tangents.is_none());
+        let m = p0.morph(&[0.5, 1.0]).unwrap();
+        assert_eq!(m.positions[0], Vec3::new(1.0, 0.0, 0.5));
+        assert_eq!(m.positions[2], Vec3::new(1.0, 1.0, 0.5));
+        assert_eq!(m.normals.unwrap()[1], Vec3::new(1.0, 0.0, 0.0));
+        // Targets beyond the length of `weights` are ignored.
+        let m = p0.morph(&[-1.0]).unwrap();
+        assert_eq!(m.positions[0], Vec3::new(0.0, 0.0, -1.0));
+        let p1 = &mesh.primitives()[1];
+        let m = p1.morph(&[0.0, 1.0]).unwrap();
+        assert_eq!(m.positions[0], Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(m.normals.unwrap()[0], Vec3::new(1.0, 0.0, 0.0));
+
+        // There must be one weight per target.
+        set(&mut bld);
+        bld.push_primitive(Topology::Triangle).unwrap();
+        assert!(bld.set_morph_weights(&[0.5, 0.5]).create().is_err());
+        let mesh = bld.set_morph_weights(&[0.25]).create().unwrap();
+        assert_eq!(mesh.weights(), [0.25]);
+        assert_eq!(mesh.primitives()[0].morph_target_count(), 1);
+    }
+
     #[test]
     fn create_mesh_no_primitive() {
         crate::init();
@@ -922,18 +3771,36 @@ mod tests {
         let mut bld = Builder::new();
 
         bld.set_vertex_count(66)
-            .set_semantic(io::repeat(1), Semantic::Normal, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Normal,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap();
 
         assert!(bld.push_primitive(Topology::Triangle).is_err());
 
-        bld.set_semantic(io::repeat(1), Semantic::TexCoord0, DataType::F32x2, None)
-            .unwrap();
+        bld.set_semantic(
+            io::repeat(1),
+            Semantic::TexCoord0,
+            DataType::F32x2,
+            false,
+            None,
+        )
+        .unwrap();
 
         assert!(bld.push_primitive(Topology::Triangle).is_err());
 
         let mesh = bld
-            .set_semantic(io::repeat(1), Semantic::Position, DataType::F32x3, None)
+            .set_semantic(
+                io::repeat(1),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
             .unwrap()
             .push_primitive(Topology::Triangle)
             .unwrap()
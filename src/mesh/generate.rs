@@ -0,0 +1,367 @@
+//! Generation of vertex normals and tangents.
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use crate::linear::{Vec2, Vec3, Vec4};
+use crate::mesh::{NormalWeight, Topology};
+
+/// Converts a primitive's vertices into a list of triangles.
+///
+/// `indices` are used if present, otherwise vertices are taken
+/// in order. Returns an empty list for non-triangle topologies.
+pub(super) fn triangles(
+    topology: Topology,
+    count: usize,
+    indices: Option<&[u32]>,
+) -> Vec<[u32; 3]> {
+    let idx = |i: usize| indices.map_or(i as u32, |x| x[i]);
+    match topology {
+        Topology::Triangle => (0..count / 3)
+            .map(|i| [idx(3 * i), idx(3 * i + 1), idx(3 * i + 2)])
+            .collect(),
+        Topology::TriangleStrip => (0..count.saturating_sub(2))
+            .map(|i| {
+                // Keep the winding order consistent.
+                if i & 1 == 0 {
+                    [idx(i), idx(i + 1), idx(i + 2)]
+                } else {
+                    [idx(i + 1), idx(i), idx(i + 2)]
+                }
+            })
+            .collect(),
+        Topology::TriangleFan => (1..count.saturating_sub(1))
+            .map(|i| [idx(0), idx(i), idx(i + 1)])
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Returns the angle between `a` and `b`.
+fn angle(a: &Vec3<f32>, b: &Vec3<f32>) -> f32 {
+    let (la, lb) = (a.length(), b.length());
+    if la == 0.0 || lb == 0.0 {
+        0.0
+    } else {
+        (a.dot(b) / (la * lb)).clamp(-1.0, 1.0).acos()
+    }
+}
+
+/// Returns the angle of each corner of a triangle.
+fn corner_angles(p: [Vec3<f32>; 3]) -> [f32; 3] {
+    let a0 = angle(&(p[1] - p[0]), &(p[2] - p[0]));
+    let a1 = angle(&(p[2] - p[1]), &(p[0] - p[1]));
+    [a0, a1, (PI - a0 - a1).max(0.0)]
+}
+
+/// Returns a hashable key for welding vertices.
+pub(super) fn key<const N: usize>(x: [f32; N]) -> [u32; N] {
+    // Treat `0.0` and `-0.0` as equal.
+    x.map(|x| (x + 0.0).to_bits())
+}
+
+/// Computes smooth vertex normals.
+///
+/// Vertices that share a position share the contributions of
+/// their faces. If `crease_angle` is provided, a face only
+/// contributes to a vertex if its normal is within that angle
+/// of the vertex's own faces' normal.
+pub(super) fn normals(
+    pos: &[Vec3<f32>],
+    tris: &[[u32; 3]],
+    weight: NormalWeight,
+    crease_angle: Option<f32>,
+) -> Vec<Vec3<f32>> {
+    // Group vertices by position.
+    let mut groups = HashMap::new();
+    let group: Vec<usize> = pos
+        .iter()
+        .map(|x| {
+            let n = groups.len();
+            *groups.entry(key((*x).into())).or_insert(n)
+        })
+        .collect();
+
+    // Weighted face normal of every corner.
+    let mut corners = vec![vec![]; groups.len()];
+    let mut own = vec![Vec3::default(); pos.len()];
+    let mut faces = Vec::with_capacity(tris.len());
+    for (f, t) in tris.iter().enumerate() {
+        let p = t.map(|i| pos[i as usize]);
+        let n = (p[1] - p[0]).cross(&(p[2] - p[0]));
+        let len = n.length();
+        faces.push(if len > 0.0 { n / len } else { n });
+        if len == 0.0 {
+            continue;
+        }
+        let w = match weight {
+            NormalWeight::Area => [len; 3],
+            NormalWeight::Angle => corner_angles(p),
+        };
+        for (i, &v) in t.iter().enumerate() {
+            let x = n * (w[i] / len);
+            corners[group[v as usize]].push((f, x));
+            own[v as usize] += x;
+        }
+    }
+
+    let cos = crease_angle.map(f32::cos);
+    (0..pos.len())
+        .map(|v| {
+            let own = own[v];
+            let mut n = Vec3::default();
+            for (f, x) in &corners[group[v]] {
+                match cos {
+                    Some(c) if own.length() > 0.0 => {
+                        if faces[*f].dot(&own.normalize()) >= c - 1e-6 {
+                            n += *x;
+                        }
+                    }
+                    _ => n += *x,
+                }
+            }
+            if n.length() > 0.0 {
+                n.normalize()
+            } else {
+                Vec3::new(0.0, 0.0, 1.0)
+            }
+        })
+        .collect()
+}
+
+/// Returns a unit vector perpendicular to `n`.
+fn perpendicular(n: &Vec3<f32>) -> Vec3<f32> {
+    let a = if n[0].abs() < 0.9 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else {
+        Vec3::new(0.0, 1.0, 0.0)
+    };
+    let t = a - n * a.dot(n);
+    t.normalize()
+}
+
+/// Computes tangents following MikkTSpace's conventions.
+///
+/// Per-corner tangents are projected onto the plane of the
+/// vertex normal and weighted by the corner's angle. Vertices
+/// with the same position, normal and texture coordinates are
+/// welded, and corners of mirrored faces are accumulated apart.
+/// The `w` component holds the handedness, such that the
+/// bitangent is `normal.cross(tangent) * w`.
+///
+/// NOTE: MikkTSpace would split a vertex that mirrored faces
+/// share. Since the vertex count must not change, such a
+/// vertex takes the tangent of the orientation with more
+/// weight instead.
+pub(super) fn tangents(
+    pos: &[Vec3<f32>],
+    nrm: &[Vec3<f32>],
+    uv: &[Vec2<f32>],
+    tris: &[[u32; 3]],
+) -> Vec<Vec4<f32>> {
+    // Group vertices by position, normal and texture
+    // coordinates, as well as by orientation.
+    let mut groups = HashMap::new();
+    let mut group = |v: usize, flip: bool| {
+        let k = (
+            key(pos[v].into()),
+            key(nrm[v].into()),
+            key(uv[v].into()),
+            flip,
+        );
+        let n = groups.len();
+        *groups.entry(k).or_insert(n)
+    };
+    let mut acc: Vec<(Vec3<f32>, Vec3<f32>, f32)> = vec![];
+    // Groups of each vertex, by orientation.
+    let mut vert = vec![[usize::MAX; 2]; pos.len()];
+
+    for t in tris {
+        let v = t.map(|x| x as usize);
+        let p = v.map(|x| pos[x]);
+        let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+        let (d1, d2) = (uv[v[1]] - uv[v[0]], uv[v[2]] - uv[v[0]]);
+        let area = d1[0] * d2[1] - d2[0] * d1[1];
+        let s = if area < 0.0 { -1.0 } else { 1.0 };
+        let os = (e1 * d2[1] - e2 * d1[1]) * s;
+        let ot = (e2 * d1[0] - e1 * d2[0]) * s;
+        let flip = area < 0.0;
+        let angles = corner_angles(p);
+        for i in 0..3 {
+            let n = nrm[v[i]];
+            let proj = |x: Vec3<f32>| {
+                let x = x - n * n.dot(&x);
+                if x.length() > 0.0 {
+                    x.normalize()
+                } else {
+                    x
+                }
+            };
+            let g = group(v[i], flip);
+            if g == acc.len() {
+                acc.push(Default::default());
+            }
+            acc[g].0 += proj(os) * angles[i];
+            acc[g].1 += proj(ot) * angles[i];
+            acc[g].2 += angles[i];
+            vert[v[i]][flip as usize] = g;
+        }
+    }
+
+    (0..pos.len())
+        .map(|v| {
+            let n = nrm[v];
+            // Prefer the orientation with larger weight if the
+            // vertex is shared by mirrored faces.
+            let g = vert[v]
+                .iter()
+                .filter(|&&g| g != usize::MAX)
+                .max_by(|&&a, &&b| acc[a].2.total_cmp(&acc[b].2));
+            let (t, b) = g.map_or((Vec3::default(), Vec3::default()), |&g| {
+                (acc[g].0, acc[g].1)
+            });
+            let t = t - n * n.dot(&t);
+            let t = if t.length() > 1e-12 {
+                t.normalize()
+            } else {
+                perpendicular(&n)
+            };
+            let w = if n.cross(&t).dot(&b) < 0.0 { -1.0 } else { 1.0 };
+            Vec4::new(t[0], t[1], t[2], w)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec3(a: Vec3<f32>, b: Vec3<f32>) {
+        assert!((a - b).length() < 1e-5, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn triangle_lists() {
+        let idx = [4, 5, 6, 7, 8];
+        assert_eq!(
+            triangles(Topology::Triangle, 6, None),
+            [[0, 1, 2], [3, 4, 5]]
+        );
+        assert_eq!(triangles(Topology::Triangle, 3, Some(&idx)), [[4, 5, 6]]);
+        assert_eq!(
+            triangles(Topology::TriangleStrip, 5, Some(&idx)),
+            [[4, 5, 6], [6, 5, 7], [6, 7, 8]]
+        );
+        assert_eq!(
+            triangles(Topology::TriangleFan, 5, None),
+            [[0, 1, 2], [0, 2, 3], [0, 3, 4]]
+        );
+        assert!(triangles(Topology::Line, 4, None).is_empty());
+    }
+
+    // Two faces of a cube meeting at a right angle, one of
+    // which is split in two triangles, with duplicated
+    // positions as in a non-indexed primitive.
+    fn hinge() -> (Vec<Vec3<f32>>, Vec<[u32; 3]>) {
+        let p = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+        ];
+        let pos = vec![p[0], p[1], p[2], p[0], p[3], p[4], p[0], p[4], p[1]];
+        (pos, vec![[0, 1, 2], [3, 4, 5], [6, 7, 8]])
+    }
+
+    #[test]
+    fn smooth_normals() {
+        let (pos, tris) = hinge();
+        let n = normals(&pos, &tris, NormalWeight::Area, None);
+        // All triangles have the same area.
+        let d = Vec3::new(0.0, -2.0, 1.0).normalize();
+        assert_vec3(n[0], d);
+        assert_vec3(n[3], d);
+        assert_vec3(n[1], Vec3::new(0.0, -1.0, 1.0).normalize());
+        assert_vec3(n[2], Vec3::new(0.0, 0.0, 1.0));
+        assert_vec3(n[4], Vec3::new(0.0, -1.0, 0.0));
+
+        let n = normals(&pos, &tris, NormalWeight::Area, Some(PI / 4.0));
+        assert_vec3(n[0], Vec3::new(0.0, 0.0, 1.0));
+        assert_vec3(n[3], Vec3::new(0.0, -1.0, 0.0));
+        assert_vec3(n[8], Vec3::new(0.0, -1.0, 0.0));
+        let n = normals(&pos, &tris, NormalWeight::Area, Some(PI / 2.0));
+        assert_vec3(n[0], d);
+
+        // A thin triangle with a large area, and a small
+        // triangle with a right angle at the shared vertex.
+        let pos = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(10.0, 0.0, 0.0),
+            Vec3::new(10.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ];
+        let tris = [[0, 1, 2], [0, 3, 4]];
+        let n = normals(&pos, &tris, NormalWeight::Area, None);
+        assert_vec3(n[0], Vec3::new(0.0, -0.5, 5.0).normalize());
+        let n = normals(&pos, &tris, NormalWeight::Angle, None);
+        let a = 0.1f32.atan();
+        assert_vec3(n[0], Vec3::new(0.0, -0.5 * PI, a).normalize());
+
+        // Degenerate faces do not contribute.
+        let pos = [Vec3::default(); 3];
+        let n = normals(&pos, &[[0, 1, 2]], NormalWeight::Angle, None);
+        assert_vec3(n[0], Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn mikk_tangents() {
+        let pos = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let nrm = [Vec3::new(0.0, 0.0, 1.0); 4];
+        let uv = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(0.0, 1.0),
+        ];
+        let tris = [[0, 1, 2], [0, 2, 3]];
+        let t = tangents(&pos, &nrm, &uv, &tris);
+        for x in &t {
+            assert_eq!(*x, Vec4::new(1.0, 0.0, 0.0, 1.0));
+        }
+
+        // Mirrored texture coordinates flip the handedness.
+        let uv: Vec<_> = uv.iter().map(|x| Vec2::new(1.0 - x[0], x[1])).collect();
+        let t = tangents(&pos, &nrm, &uv, &tris);
+        for x in &t {
+            assert_eq!(*x, Vec4::new(-1.0, 0.0, 0.0, -1.0));
+        }
+
+        // Mirroring twice restores the handedness. The tangent
+        // rotates and stays orthogonal to a tilted normal.
+        let uv: Vec<_> = uv.iter().map(|x| Vec2::new(x[1], x[0])).collect();
+        let nrm = [Vec3::new(0.0, 0.6, 0.8); 4];
+        let t = tangents(&pos, &nrm, &uv, &tris);
+        for x in &t {
+            let v = Vec3::from(x);
+            assert!(v.dot(&nrm[0]).abs() < 1e-6);
+            assert_vec3(v, Vec3::new(0.0, 0.8, -0.6));
+            assert_eq!(x[3], 1.0);
+        }
+
+        // Degenerate texture coordinates still produce a
+        // unit tangent.
+        let uv = [Vec2::default(); 4];
+        let t = tangents(&pos, &[Vec3::new(0.0, 0.0, 1.0); 4], &uv, &tris);
+        for x in &t {
+            assert!((Vec3::from(x).length() - 1.0).abs() < 1e-6);
+            assert!(Vec3::from(x).dot(&Vec3::new(0.0, 0.0, 1.0)).abs() < 1e-6);
+        }
+    }
+}
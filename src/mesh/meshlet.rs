@@ -0,0 +1,249 @@
+//! Clustering of triangles into meshlets.
+
+use crate::linear::Vec3;
+use crate::mesh::Meshlet;
+use crate::shape::Sphere;
+
+/// Size of an encoded [`Meshlet`], in bytes.
+pub(super) const MESHLET_SIZE: usize = 48;
+
+/// Triangles that share a small set of vertices.
+#[derive(Debug)]
+pub(super) struct Cluster {
+    /// Vertices referenced by the cluster.
+    pub verts: Vec<u32>,
+    /// Triangles, as indices into `verts`.
+    pub tris: Vec<[u8; 3]>,
+}
+
+/// Splits a triangle list into clusters of at most
+/// `max_verts` vertices and `max_tris` triangles.
+///
+/// Triangles are taken in order, so clusters benefit from
+/// indices that have been optimized for locality.
+///
+/// NOTE: One must ensure that `max_verts` does not exceed
+/// 256 and is at least 3.
+pub(super) fn build(
+    indices: &[u32],
+    vert_count: usize,
+    max_verts: usize,
+    max_tris: usize,
+) -> Vec<Cluster> {
+    debug_assert!((3..=256).contains(&max_verts));
+    let mut local = vec![u32::MAX; vert_count];
+    let mut out = vec![];
+    let mut cur = Cluster {
+        verts: vec![],
+        tris: vec![],
+    };
+    for t in indices.chunks_exact(3) {
+        let new = (0..3)
+            .filter(|&i| local[t[i] as usize] == u32::MAX && !t[..i].contains(&t[i]))
+            .count();
+        if cur.verts.len() + new > max_verts || cur.tris.len() == max_tris {
+            for &v in &cur.verts {
+                local[v as usize] = u32::MAX;
+            }
+            out.push(cur);
+            cur = Cluster {
+                verts: vec![],
+                tris: vec![],
+            };
+        }
+        let tri = [t[0], t[1], t[2]].map(|v| {
+            let x = &mut local[v as usize];
+            if *x == u32::MAX {
+                *x = cur.verts.len() as u32;
+                cur.verts.push(v);
+            }
+            *x as u8
+        });
+        cur.tris.push(tri);
+    }
+    if !cur.tris.is_empty() {
+        out.push(cur);
+    }
+    out
+}
+
+/// Computes the bounding sphere and normal cone of a cluster.
+///
+/// The cone is returned as its axis and the cutoff described
+/// in [`Meshlet`].
+pub(super) fn bounds(cluster: &Cluster, pos: &[Vec3<f32>]) -> (Sphere, Vec3<f32>, f32) {
+    let points: Vec<_> = cluster.verts.iter().map(|&v| pos[v as usize]).collect();
+    let sphere = Sphere::from_points(&points);
+    let normals: Vec<_> = (cluster.tris.iter())
+        .filter_map(|t| {
+            let p = t.map(|x| points[x as usize]);
+            let n = (p[1] - p[0]).cross(&(p[2] - p[0]));
+            (n.length() > 0.0).then(|| n.normalize())
+        })
+        .collect();
+    let axis = normals.iter().fold(Vec3::default(), |a, x| a + *x);
+    if axis.length() == 0.0 {
+        return (sphere, Vec3::default(), 1.0);
+    }
+    let axis = axis.normalize();
+    let min = (normals.iter())
+        .map(|x| x.dot(&axis))
+        .fold(1.0f32, f32::min);
+    // The cutoff is the sine of the cone's half angle,
+    // and culling is not possible past 90 degrees.
+    let cutoff = if min <= 0.0 {
+        1.0
+    } else {
+        (1.0 - min * min).max(0.0).sqrt()
+    };
+    (sphere, axis, cutoff)
+}
+
+/// Packs a triangle's micro-indices into a `u32`.
+pub(super) fn pack(tri: [u8; 3]) -> u32 {
+    tri[0] as u32 | (tri[1] as u32) << 8 | (tri[2] as u32) << 16
+}
+
+/// Unpacks a triangle's micro-indices from a `u32`.
+pub(super) fn unpack(x: u32) -> [u8; 3] {
+    [x as u8, (x >> 8) as u8, (x >> 16) as u8]
+}
+
+/// Encodes meshlets into `MESHLET_SIZE`-byte records.
+pub(super) fn encode(meshlets: &[Meshlet]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(meshlets.len() * MESHLET_SIZE);
+    for x in meshlets {
+        let c = x.bounds.center();
+        for i in [
+            x.vertex_offset,
+            x.vertex_count,
+            x.triangle_offset,
+            x.triangle_count,
+        ] {
+            buf.extend(i.to_ne_bytes());
+        }
+        for f in [
+            c[0],
+            c[1],
+            c[2],
+            x.bounds.radius(),
+            x.cone_axis[0],
+            x.cone_axis[1],
+            x.cone_axis[2],
+            x.cone_cutoff,
+        ] {
+            buf.extend(f.to_ne_bytes());
+        }
+    }
+    buf
+}
+
+/// Decodes meshlets encoded by `encode`.
+pub(super) fn decode(buf: &[u8]) -> Vec<Meshlet> {
+    (buf.chunks_exact(MESHLET_SIZE))
+        .map(|x| {
+            let w: Vec<_> = (x.chunks_exact(4))
+                .map(|x| [x[0], x[1], x[2], x[3]])
+                .collect();
+            let f = |i: usize| f32::from_ne_bytes(w[i]);
+            Meshlet {
+                vertex_offset: u32::from_ne_bytes(w[0]),
+                vertex_count: u32::from_ne_bytes(w[1]),
+                triangle_offset: u32::from_ne_bytes(w[2]),
+                triangle_count: u32::from_ne_bytes(w[3]),
+                bounds: Sphere::new(Vec3::new(f(4), f(5), f(6)), f(7)),
+                cone_axis: Vec3::new(f(8), f(9), f(10)),
+                cone_cutoff: f(11),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A strip of `n` quads along `x`.
+    fn strip(n: u32) -> (Vec<u32>, Vec<Vec3<f32>>) {
+        let idx = (0..n)
+            .flat_map(|i| {
+                let v = 2 * i;
+                [v, v + 2, v + 3, v, v + 3, v + 1]
+            })
+            .collect();
+        let pos = (0..2 * n + 2)
+            .map(|i| Vec3::new((i / 2) as f32, (i % 2) as f32, 0.0))
+            .collect();
+        (idx, pos)
+    }
+
+    #[test]
+    fn limits() {
+        let (idx, _) = strip(100);
+        let n = 202;
+        let c = build(&idx, n, 64, 124);
+        assert!(c.len() > 1);
+        for x in &c {
+            assert!(x.verts.len() <= 64);
+            assert!(x.tris.len() <= 124);
+        }
+        // Every triangle is kept, in order.
+        let out: Vec<_> = (c.iter())
+            .flat_map(|x| x.tris.iter().flat_map(|t| t.map(|i| x.verts[i as usize])))
+            .collect();
+        assert_eq!(out, idx);
+
+        let c = build(&idx, n, 256, 10);
+        assert_eq!(c.len(), 20);
+        assert!(c.iter().all(|x| x.tris.len() == 10 && x.verts.len() == 12));
+        let c = build(&[0, 1, 2, 2, 1, 3], 4, 3, 124);
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[1].verts, [2, 1, 3]);
+        assert_eq!(c[1].tris, [[0, 1, 2]]);
+    }
+
+    #[test]
+    fn cone() {
+        let (idx, pos) = strip(4);
+        let c = build(&idx, pos.len(), 64, 124);
+        let (sphere, axis, cutoff) = bounds(&c[0], &pos);
+        assert_eq!(axis, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(cutoff, 0.0);
+        for p in &pos {
+            assert!((*p - sphere.center()).length() <= sphere.radius() * 1.0001);
+        }
+
+        // Folded in half at a right angle.
+        let pos: Vec<_> = (pos.iter())
+            .map(|x| {
+                if x[0] <= 2.0 {
+                    *x
+                } else {
+                    Vec3::new(2.0, x[1], 2.0 - x[0])
+                }
+            })
+            .collect();
+        let (_, axis, cutoff) = bounds(&c[0], &pos);
+        let d = 0.5f32.sqrt();
+        assert!((axis - Vec3::new(d, 0.0, d)).length() < 1e-6);
+        assert!((cutoff - d).abs() < 1e-6);
+    }
+
+    #[test]
+    fn packing() {
+        assert_eq!(pack([1, 2, 255]), 0xff0201);
+        assert_eq!(unpack(0xff0201), [1, 2, 255]);
+        let m = Meshlet {
+            vertex_offset: 1,
+            vertex_count: 2,
+            triangle_offset: 3,
+            triangle_count: 4,
+            bounds: Sphere::new(Vec3::new(5.0, 6.0, 7.0), 8.0),
+            cone_axis: Vec3::new(9.0, 10.0, 11.0),
+            cone_cutoff: 12.0,
+        };
+        let buf = encode(&[m, m]);
+        assert_eq!(buf.len(), 2 * MESHLET_SIZE);
+        assert_eq!(decode(&buf), [m, m]);
+    }
+}
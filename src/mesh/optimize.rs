@@ -0,0 +1,296 @@
+//! Reordering of triangle indices and vertices.
+
+use std::collections::VecDeque;
+use std::ops::Range;
+
+use crate::linear::Vec3;
+use crate::mesh::CacheStats;
+
+/// Simulates a FIFO post-transform vertex cache of
+/// `cache_size` entries over a triangle list.
+pub(super) fn cache_stats(indices: &[u32], vert_count: usize, cache_size: usize) -> CacheStats {
+    let mut cache = VecDeque::with_capacity(cache_size + 1);
+    let mut used = vec![false; vert_count];
+    let mut misses = 0;
+    for &i in indices {
+        used[i as usize] = true;
+        if !cache.contains(&i) {
+            misses += 1;
+            cache.push_back(i);
+            if cache.len() > cache_size {
+                cache.pop_front();
+            }
+        }
+    }
+    let tri_count = indices.len() / 3;
+    let vert_count = used.iter().filter(|&&x| x).count();
+    let ratio = |n: usize| {
+        if n == 0 {
+            0.0
+        } else {
+            misses as f32 / n as f32
+        }
+    };
+    CacheStats {
+        acmr: ratio(tri_count),
+        atvr: ratio(vert_count),
+    }
+}
+
+/// Reorders triangles for vertex cache efficiency, following
+/// Sander et al.'s Tipsify.
+///
+/// It returns the new indices along with the ranges of
+/// triangles that form each cluster. Clusters start where
+/// the algorithm cannot continue from cached vertices.
+pub(super) fn tipsify(
+    indices: &[u32],
+    vert_count: usize,
+    cache_size: usize,
+) -> (Vec<u32>, Vec<Range<usize>>) {
+    let tri_count = indices.len() / 3;
+    let mut adj = vec![vec![]; vert_count];
+    for (t, x) in indices.chunks_exact(3).enumerate() {
+        for &v in x {
+            adj[v as usize].push(t);
+        }
+    }
+    let mut live: Vec<_> = adj.iter().map(Vec::len).collect();
+    let mut cache_time = vec![0; vert_count];
+    let mut time = cache_size + 1;
+    let mut emitted = vec![false; tri_count];
+    let mut dead_end = vec![];
+    let mut cursor = 0;
+
+    let mut out = Vec::with_capacity(indices.len());
+    let mut clusters = vec![];
+    let mut beg = 0;
+    let mut fan = (0..vert_count).find(|&v| live[v] > 0);
+    while let Some(f) = fan {
+        let mut cand = vec![];
+        for &t in &adj[f] {
+            if emitted[t] {
+                continue;
+            }
+            emitted[t] = true;
+            for &v in &indices[3 * t..3 * t + 3] {
+                let v = v as usize;
+                out.push(v as u32);
+                dead_end.push(v);
+                cand.push(v);
+                live[v] -= 1;
+                if time - cache_time[v] > cache_size {
+                    cache_time[v] = time;
+                    time += 1;
+                }
+            }
+        }
+
+        // Pick the candidate that will still be cached after
+        // emitting its remaining triangles, preferring the
+        // oldest one.
+        let best = cand
+            .iter()
+            .filter(|&&v| live[v] > 0 && time - cache_time[v] + 2 * live[v] <= cache_size)
+            .max_by_key(|&&v| time - cache_time[v]);
+        fan = match best {
+            Some(&v) => Some(v),
+            None => {
+                clusters.push(beg / 3..out.len() / 3);
+                beg = out.len();
+                while dead_end.last().is_some_and(|&v| live[v] == 0) {
+                    dead_end.pop();
+                }
+                match dead_end.pop() {
+                    Some(v) => Some(v),
+                    None => {
+                        while cursor < vert_count && live[cursor] == 0 {
+                            cursor += 1;
+                        }
+                        (cursor < vert_count).then_some(cursor)
+                    }
+                }
+            }
+        };
+    }
+    if beg < out.len() {
+        clusters.push(beg / 3..out.len() / 3);
+    }
+    clusters.retain(|x| !x.is_empty());
+    (out, clusters)
+}
+
+/// Reorders clusters of triangles to reduce overdraw.
+///
+/// Clusters that face away from the center of the mesh are
+/// drawn first, since they are more likely to occlude others.
+pub(super) fn sort_clusters(
+    indices: &[u32],
+    pos: &[Vec3<f32>],
+    clusters: &[Range<usize>],
+) -> Vec<u32> {
+    let tri = |t: usize| [0, 1, 2].map(|i| pos[indices[3 * t + i] as usize]);
+    let mut area = 0.0;
+    let mut center = Vec3::default();
+    let mut info: Vec<_> = clusters
+        .iter()
+        .map(|r| {
+            let (mut a, mut c, mut n) = (0.0, Vec3::default(), Vec3::default());
+            for t in r.clone() {
+                let p = tri(t);
+                let x = (p[1] - p[0]).cross(&(p[2] - p[0]));
+                let w = x.length();
+                a += w;
+                c += (p[0] + p[1] + p[2]) * (w / 3.0);
+                n += x;
+            }
+            area += a;
+            center += c;
+            let c = if a > 0.0 { c / a } else { c };
+            (r.clone(), c, n)
+        })
+        .collect();
+    if area > 0.0 {
+        center /= area;
+    }
+    let key = |(_, c, n): &(Range<usize>, Vec3<f32>, Vec3<f32>)| (*c - center).dot(n);
+    info.sort_by(|a, b| key(b).total_cmp(&key(a)));
+    info.into_iter()
+        .flat_map(|(r, ..)| indices[3 * r.start..3 * r.end].iter().copied())
+        .collect()
+}
+
+/// Returns the new index of every vertex, in order of first
+/// use by `indices`.
+///
+/// Vertices that are not referenced are moved to the end.
+pub(super) fn fetch_remap(indices: &[u32], vert_count: usize) -> Vec<u32> {
+    let mut remap = vec![u32::MAX; vert_count];
+    let mut n = 0;
+    for &i in indices {
+        if remap[i as usize] == u32::MAX {
+            remap[i as usize] = n;
+            n += 1;
+        }
+    }
+    for x in &mut remap {
+        if *x == u32::MAX {
+            *x = n;
+            n += 1;
+        }
+    }
+    remap
+}
+
+/// Reorders tightly packed elements of `size` bytes as
+/// `remap` dictates.
+pub(super) fn remap_data(buf: &[u8], size: usize, remap: &[u32]) -> Vec<u8> {
+    let mut out = vec![0; buf.len()];
+    for (x, &i) in buf.chunks_exact(size).zip(remap) {
+        let i = i as usize * size;
+        out[i..i + size].copy_from_slice(x);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A `n` by `n` grid of quads, with triangles emitted in
+    // an order that is unfriendly to the cache.
+    fn grid(n: u32) -> (Vec<u32>, Vec<Vec3<f32>>) {
+        let mut idx = vec![];
+        for x in 0..n {
+            for y in 0..n {
+                let v = y * (n + 1) + x;
+                idx.extend([v, v + 1, v + n + 2, v, v + n + 2, v + n + 1]);
+            }
+        }
+        let pos = (0..(n + 1) * (n + 1))
+            .map(|i| Vec3::new((i % (n + 1)) as f32, (i / (n + 1)) as f32, 0.0))
+            .collect();
+        (idx, pos)
+    }
+
+    fn sorted_tris(idx: &[u32]) -> Vec<[u32; 3]> {
+        let mut t: Vec<_> = idx
+            .chunks_exact(3)
+            .map(|x| {
+                // Rotate, keeping the winding order.
+                let i = (0..3).min_by_key(|&i| x[i]).unwrap();
+                [x[i], x[(i + 1) % 3], x[(i + 2) % 3]]
+            })
+            .collect();
+        t.sort();
+        t
+    }
+
+    #[test]
+    fn stats() {
+        let s = cache_stats(&[0, 1, 2, 2, 1, 3], 5, 16);
+        assert_eq!(
+            s,
+            CacheStats {
+                acmr: 2.0,
+                atvr: 1.0
+            }
+        );
+        let s = cache_stats(&[0, 1, 2, 0, 1, 2], 4, 2);
+        assert_eq!(
+            s,
+            CacheStats {
+                acmr: 3.0,
+                atvr: 2.0
+            }
+        );
+        assert_eq!(cache_stats(&[], 0, 16), CacheStats::default());
+    }
+
+    #[test]
+    fn reorder() {
+        let (idx, pos) = grid(16);
+        let n = pos.len();
+        let before = cache_stats(&idx, n, 16);
+        let (out, clusters) = tipsify(&idx, n, 16);
+        assert_eq!(sorted_tris(&out), sorted_tris(&idx));
+        assert_eq!(clusters.first().unwrap().start, 0);
+        assert_eq!(clusters.last().unwrap().end, idx.len() / 3);
+        assert!(clusters.windows(2).all(|x| x[0].end == x[1].start));
+        let after = cache_stats(&out, n, 16);
+        assert!(after.acmr < before.acmr, "{after:?} {before:?}");
+        assert!(after.acmr < 0.9);
+
+        let out2 = sort_clusters(&out, &pos, &clusters);
+        assert_eq!(sorted_tris(&out2), sorted_tris(&idx));
+
+        // Facing away from the center, sideways and towards
+        // the center, respectively.
+        let pos = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(0.0, 1.0, 2.0),
+            Vec3::new(1.0 / 3.0, 0.0, 0.0),
+            Vec3::new(1.0 / 3.0, 1.0, 0.0),
+            Vec3::new(1.0 / 3.0, 0.0, 1.0),
+        ];
+        let idx = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let out = sort_clusters(&idx, &pos, &[0..1, 1..2, 2..3]);
+        assert_eq!(out, [3, 4, 5, 6, 7, 8, 0, 1, 2]);
+        let out = sort_clusters(&idx, &pos, &[0..1, 1..3]);
+        assert_eq!(out, [3, 4, 5, 6, 7, 8, 0, 1, 2]);
+        let out = sort_clusters(&idx, &pos, &[0..2, 2..3]);
+        assert_eq!(out, idx);
+    }
+
+    #[test]
+    fn remap() {
+        let remap = fetch_remap(&[3, 1, 3, 4], 5);
+        assert_eq!(remap, [3, 1, 4, 0, 2]);
+        let data = remap_data(&[0, 0, 1, 1, 2, 2, 3, 3, 4, 4], 2, &remap);
+        assert_eq!(data, [3, 3, 1, 1, 4, 4, 0, 0, 2, 2]);
+    }
+}
@@ -0,0 +1,638 @@
+//! Procedural meshes.
+//!
+//! Every function in this module creates a [`Mesh`] with
+//! a single indexed [`Topology::Triangle`] primitive that
+//! has [`Semantic::Position`], [`Semantic::Normal`],
+//! [`Semantic::Tangent`] and [`Semantic::TexCoord0`] data,
+//! along with a [`Shape`] that encloses it.
+//!
+//! Meshes are centered at the origin, and surfaces of
+//! revolution are built around the `y` axis. Triangles
+//! are wound counter-clockwise when seen from outside.
+//!
+//! Fails if the tessellation parameters are too small
+//! (e.g., less than three sectors).
+
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::io;
+
+use crate::drawable::Shape;
+use crate::linear::{Vec2, Vec3};
+use crate::mesh::{Builder, DataType, Generate, Mesh, Semantic, Topology};
+use crate::shape::{Bbox, Capsule, Sphere};
+
+/// Vertex and index data.
+#[derive(Default, Debug)]
+struct Geometry {
+    pos: Vec<Vec3<f32>>,
+    nrm: Vec<Vec3<f32>>,
+    uv: Vec<Vec2<f32>>,
+    indices: Vec<u32>,
+}
+
+impl Geometry {
+    fn push(&mut self, pos: Vec3<f32>, nrm: Vec3<f32>, uv: Vec2<f32>) -> u32 {
+        self.pos.push(pos);
+        self.nrm.push(nrm);
+        self.uv.push(uv);
+        self.pos.len() as u32 - 1
+    }
+
+    /// Adds a `segments` by `segments` grid on the
+    /// parallelogram spanned by `du` and `dv` from `origin`.
+    fn face(
+        &mut self,
+        origin: Vec3<f32>,
+        du: Vec3<f32>,
+        dv: Vec3<f32>,
+        nrm: Vec3<f32>,
+        segments: [u32; 2],
+    ) {
+        let base = self.pos.len() as u32;
+        let [m, n] = segments;
+        for j in 0..=n {
+            for i in 0..=m {
+                let (u, v) = (i as f32 / m as f32, j as f32 / n as f32);
+                self.push(origin + du * u + dv * v, nrm, Vec2::new(u, v));
+            }
+        }
+        let ccw = du.cross(&dv).dot(&nrm) > 0.0;
+        for j in 0..n {
+            for i in 0..m {
+                let a = base + j * (m + 1) + i;
+                let (b, c, d) = (a + 1, a + m + 2, a + m + 1);
+                if ccw {
+                    self.indices.extend([a, b, c, a, c, d]);
+                } else {
+                    self.indices.extend([a, c, b, a, d, c]);
+                }
+            }
+        }
+    }
+
+    /// Adds a surface of revolution around the `y` axis.
+    ///
+    /// Each element of `profile` holds the distance to the
+    /// axis, the height and the normal (in the same terms)
+    /// of a ring. The profile is expected to go from top to
+    /// bottom on the outer side of the surface.
+    fn lathe(&mut self, profile: &[[f32; 4]], sectors: u32) {
+        let base = self.pos.len() as u32;
+        let rows = profile.len() as u32;
+        for i in 0..=sectors {
+            let u = i as f32 / sectors as f32;
+            let (s, c) = (2.0 * PI * u).sin_cos();
+            for (j, &[r, y, nr, ny]) in profile.iter().enumerate() {
+                let v = j as f32 / (rows - 1) as f32;
+                self.push(
+                    Vec3::new(r * s, y, r * c),
+                    Vec3::new(nr * s, ny, nr * c),
+                    Vec2::new(u, v),
+                );
+            }
+        }
+        for i in 0..sectors {
+            for j in 0..rows - 1 {
+                let a = base + i * rows + j;
+                let (b, c, d) = (a + 1, a + rows + 1, a + rows);
+                // Skip triangles that collapse on the axis.
+                if profile[j as usize + 1][0] != 0.0 {
+                    self.indices.extend([a, b, c]);
+                }
+                if profile[j as usize][0] != 0.0 {
+                    self.indices.extend([a, c, d]);
+                }
+            }
+        }
+    }
+
+    /// Adds a disk of `radius` at height `y`, facing up or down.
+    fn disk(&mut self, radius: f32, y: f32, up: bool, sectors: u32) {
+        let ny = if up { 1.0 } else { -1.0 };
+        let (center, rim) = ([0.0, y, 0.0, ny], [radius, y, 0.0, ny]);
+        if up {
+            self.lathe(&[center, rim], sectors);
+        } else {
+            self.lathe(&[rim, center], sectors);
+        }
+    }
+
+    /// Creates the mesh.
+    fn build(&self) -> io::Result<Mesh> {
+        let f32s = |x: &[f32]| -> Vec<u8> { x.iter().flat_map(|x| x.to_ne_bytes()).collect() };
+        let pos: Vec<_> = self.pos.iter().flat_map(<[f32; 3]>::from).collect();
+        let nrm: Vec<_> = self.nrm.iter().flat_map(<[f32; 3]>::from).collect();
+        let uv: Vec<_> = self.uv.iter().flat_map(<[f32; 2]>::from).collect();
+        let (idx, data_type) = if self.pos.len() <= u16::MAX as usize + 1 {
+            let idx = self.indices.iter().flat_map(|&x| (x as u16).to_ne_bytes());
+            (idx.collect::<Vec<_>>(), DataType::U16)
+        } else {
+            let idx = self.indices.iter().flat_map(|x| x.to_ne_bytes());
+            (idx.collect(), DataType::U32)
+        };
+        Builder::new()
+            .set_vertex_count(self.pos.len())
+            .set_semantic(
+                &f32s(&pos)[..],
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )?
+            .set_semantic(
+                &f32s(&nrm)[..],
+                Semantic::Normal,
+                DataType::F32x3,
+                false,
+                None,
+            )?
+            .set_semantic(
+                &f32s(&uv)[..],
+                Semantic::TexCoord0,
+                DataType::F32x2,
+                false,
+                None,
+            )?
+            .set_indexed(&idx[..], self.indices.len(), data_type)?
+            .push_primitive_with(
+                Topology::Triangle,
+                &Generate {
+                    tangents: true,
+                    ..Default::default()
+                },
+            )?
+            .create()
+    }
+}
+
+fn check(x: bool) -> io::Result<()> {
+    if x {
+        Ok(())
+    } else {
+        Err(io::Error::from(io::ErrorKind::InvalidInput))
+    }
+}
+
+fn cube_geometry(half_extent: Vec3<f32>, segments: u32) -> Geometry {
+    let [x, y, z] = <[f32; 3]>::from(half_extent);
+    let mut geom = Geometry::default();
+    // Normal, `u` and `v` directions of each face.
+    let faces = [
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -z], [0.0, -y, 0.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, z], [0.0, -y, 0.0]),
+        ([0.0, 1.0, 0.0], [x, 0.0, 0.0], [0.0, 0.0, z]),
+        ([0.0, -1.0, 0.0], [x, 0.0, 0.0], [0.0, 0.0, -z]),
+        ([0.0, 0.0, 1.0], [x, 0.0, 0.0], [0.0, -y, 0.0]),
+        ([0.0, 0.0, -1.0], [-x, 0.0, 0.0], [0.0, -y, 0.0]),
+    ];
+    for (n, du, dv) in faces {
+        let (n, du, dv) = (Vec3::from(n), Vec3::from(du), Vec3::from(dv));
+        let c = Vec3::new(n[0] * x, n[1] * y, n[2] * z);
+        geom.face(c - du - dv, du * 2.0, dv * 2.0, n, [segments; 2]);
+    }
+    geom
+}
+
+/// Creates a box with `segments` subdivisions along each
+/// edge.
+pub fn cube(half_extent: Vec3<f32>, segments: u32) -> io::Result<(Mesh, Shape)> {
+    check(segments > 0)?;
+    let mesh = cube_geometry(half_extent, segments).build()?;
+    Ok((mesh, Shape::Bbox(Bbox::new_origin(half_extent))))
+}
+
+fn grid_geometry(size: Vec2<f32>, segments: [u32; 2]) -> Geometry {
+    let mut geom = Geometry::default();
+    geom.face(
+        Vec3::new(-0.5 * size[0], 0.0, -0.5 * size[1]),
+        Vec3::new(size[0], 0.0, 0.0),
+        Vec3::new(0.0, 0.0, size[1]),
+        Vec3::new(0.0, 1.0, 0.0),
+        segments,
+    );
+    geom
+}
+
+/// Creates a grid on the `xz` plane, facing up, with
+/// `x_segments` by `z_segments` cells.
+pub fn grid(size: Vec2<f32>, x_segments: u32, z_segments: u32) -> io::Result<(Mesh, Shape)> {
+    check(x_segments > 0 && z_segments > 0)?;
+    let mesh = grid_geometry(size, [x_segments, z_segments]).build()?;
+    let e = Vec3::new(0.5 * size[0], 0.0, 0.5 * size[1]);
+    Ok((mesh, Shape::Bbox(Bbox::new_origin(e))))
+}
+
+/// Creates a plane on the `xz` plane, facing up.
+pub fn plane(size: Vec2<f32>) -> io::Result<(Mesh, Shape)> {
+    grid(size, 1, 1)
+}
+
+/// Returns the profile of an arc of a sphere, from `from`
+/// to `to` radians away from the top, offset by `y`.
+fn arc(radius: f32, y: f32, from: f32, to: f32, stacks: u32) -> Vec<[f32; 4]> {
+    (0..=stacks)
+        .map(|j| {
+            let a = from + (to - from) * j as f32 / stacks as f32;
+            let (s, c) = a.sin_cos();
+            // Keep the poles exactly on the axis.
+            let s = if j == 0 && from == 0.0 || j == stacks && to == PI {
+                0.0
+            } else {
+                s
+            };
+            [radius * s, y + radius * c, s, c]
+        })
+        .collect()
+}
+
+fn uv_sphere_geometry(radius: f32, sectors: u32, stacks: u32) -> Geometry {
+    let mut geom = Geometry::default();
+    geom.lathe(&arc(radius, 0.0, 0.0, PI, stacks), sectors);
+    geom
+}
+
+/// Creates a sphere with `sectors` subdivisions around the
+/// `y` axis and `stacks` subdivisions from pole to pole.
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> io::Result<(Mesh, Shape)> {
+    check(sectors >= 3 && stacks >= 2)?;
+    let mesh = uv_sphere_geometry(radius, sectors, stacks).build()?;
+    Ok((mesh, Shape::Sphere(Sphere::new_origin(radius))))
+}
+
+fn icosphere_geometry(radius: f32, subdivisions: u32) -> Geometry {
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut dirs: Vec<_> = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .into_iter()
+    .map(|x| Vec3::from(x).normalize())
+    .collect();
+    let mut tris = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+    for _ in 0..subdivisions {
+        let mut mid = HashMap::new();
+        let mut split = |a: u32, b: u32| {
+            *mid.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                let x = (dirs[a as usize] + dirs[b as usize]).normalize();
+                dirs.push(x);
+                dirs.len() as u32 - 1
+            })
+        };
+        tris = (tris.iter())
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (split(a, b), split(b, c), split(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut geom = Geometry::default();
+    for &d in &dirs {
+        let u = (d[0].atan2(d[2]) / (2.0 * PI)).rem_euclid(1.0);
+        let v = d[1].clamp(-1.0, 1.0).acos() / PI;
+        geom.push(d * radius, d, Vec2::new(u, v));
+    }
+    // Duplicate vertices of triangles that cross the seam,
+    // so texture coordinates do not wrap around, and give
+    // poles the `u` of each triangle they belong to.
+    let pole = |d: &Vec3<f32>| d[0] * d[0] + d[2] * d[2] < 1e-12;
+    let mut wrapped = HashMap::new();
+    for t in &mut tris {
+        let u: Vec<_> = (t.iter())
+            .filter(|&&x| !pole(&dirs[x as usize]))
+            .map(|&x| geom.uv[x as usize][0])
+            .collect();
+        let (min, max) = (
+            u.iter().copied().fold(1.0, f32::min),
+            u.iter().copied().fold(0.0, f32::max),
+        );
+        let wrap = max - min > 0.5;
+        let u: Vec<_> = u
+            .iter()
+            .map(|&x| if wrap && x < 0.5 { x + 1.0 } else { x })
+            .collect();
+        for x in t.iter_mut() {
+            let i = *x as usize;
+            let d = dirs[i];
+            if pole(&d) {
+                let uv = Vec2::new(u.iter().sum::<f32>() / u.len() as f32, geom.uv[i][1]);
+                *x = geom.push(geom.pos[i], geom.nrm[i], uv);
+            } else if wrap && geom.uv[i][0] < 0.5 {
+                *x = *wrapped.entry(*x).or_insert_with(|| {
+                    let uv = geom.uv[i] + Vec2::new(1.0, 0.0);
+                    geom.push(geom.pos[i], geom.nrm[i], uv)
+                });
+            }
+        }
+    }
+    geom.indices = tris.into_iter().flatten().collect();
+    geom
+}
+
+/// Creates a sphere by subdividing an icosahedron
+/// `subdivisions` times.
+pub fn icosphere(radius: f32, subdivisions: u32) -> io::Result<(Mesh, Shape)> {
+    check(subdivisions <= 7)?;
+    let mesh = icosphere_geometry(radius, subdivisions).build()?;
+    Ok((mesh, Shape::Sphere(Sphere::new_origin(radius))))
+}
+
+fn cylinder_geometry(radius: f32, height: f32, sectors: u32, stacks: u32) -> Geometry {
+    let h = 0.5 * height;
+    let profile: Vec<_> = (0..=stacks)
+        .map(|j| [radius, h - height * j as f32 / stacks as f32, 1.0, 0.0])
+        .collect();
+    let mut geom = Geometry::default();
+    geom.lathe(&profile, sectors);
+    geom.disk(radius, h, true, sectors);
+    geom.disk(radius, -h, false, sectors);
+    geom
+}
+
+/// Creates a capped cylinder with `sectors` subdivisions
+/// around the `y` axis and `stacks` subdivisions along it.
+pub fn cylinder(radius: f32, height: f32, sectors: u32, stacks: u32) -> io::Result<(Mesh, Shape)> {
+    check(sectors >= 3 && stacks >= 1)?;
+    let mesh = cylinder_geometry(radius, height, sectors, stacks).build()?;
+    let e = Vec3::new(radius, 0.5 * height, radius);
+    Ok((mesh, Shape::Bbox(Bbox::new_origin(e))))
+}
+
+fn cone_geometry(radius: f32, height: f32, sectors: u32, stacks: u32) -> Geometry {
+    let h = 0.5 * height;
+    let n = Vec2::new(height, radius).normalize();
+    let profile: Vec<_> = (0..=stacks)
+        .map(|j| {
+            let a = j as f32 / stacks as f32;
+            [radius * a, h - height * a, n[0], n[1]]
+        })
+        .collect();
+    let mut geom = Geometry::default();
+    geom.lathe(&profile, sectors);
+    geom.disk(radius, -h, false, sectors);
+    geom
+}
+
+/// Creates a cone pointing up, with `sectors` subdivisions
+/// around the `y` axis and `stacks` subdivisions along it.
+pub fn cone(radius: f32, height: f32, sectors: u32, stacks: u32) -> io::Result<(Mesh, Shape)> {
+    check(sectors >= 3 && stacks >= 1)?;
+    let mesh = cone_geometry(radius, height, sectors, stacks).build()?;
+    let e = Vec3::new(radius, 0.5 * height, radius);
+    Ok((mesh, Shape::Bbox(Bbox::new_origin(e))))
+}
+
+fn torus_geometry(major: f32, minor: f32, sectors: u32, sides: u32) -> Geometry {
+    let profile: Vec<_> = (0..=sides)
+        .map(|j| {
+            let (s, c) = (2.0 * PI * j as f32 / sides as f32).sin_cos();
+            [major + minor * c, -minor * s, c, -s]
+        })
+        .collect();
+    let mut geom = Geometry::default();
+    geom.lathe(&profile, sectors);
+    geom
+}
+
+/// Creates a torus around the `y` axis, with `sectors`
+/// subdivisions around the axis and `sides` subdivisions
+/// around the tube.
+pub fn torus(major: f32, minor: f32, sectors: u32, sides: u32) -> io::Result<(Mesh, Shape)> {
+    check(sectors >= 3 && sides >= 3)?;
+    let mesh = torus_geometry(major, minor, sectors, sides).build()?;
+    let e = Vec3::new(major + minor, minor, major + minor);
+    Ok((mesh, Shape::Bbox(Bbox::new_origin(e))))
+}
+
+fn capsule_geometry(radius: f32, height: f32, sectors: u32, stacks: u32) -> Geometry {
+    let h = 0.5 * height;
+    let mut profile = arc(radius, h, 0.0, 0.5 * PI, stacks);
+    profile.extend(arc(radius, -h, 0.5 * PI, PI, stacks));
+    let mut geom = Geometry::default();
+    geom.lathe(&profile, sectors);
+    geom
+}
+
+/// Creates a capsule along the `y` axis, where `height` is
+/// the distance between the centers of the hemispheres.
+///
+/// It has `sectors` subdivisions around the axis and
+/// `stacks` subdivisions along each hemisphere.
+pub fn capsule(radius: f32, height: f32, sectors: u32, stacks: u32) -> io::Result<(Mesh, Shape)> {
+    check(sectors >= 3 && stacks >= 1)?;
+    let mesh = capsule_geometry(radius, height, sectors, stacks).build()?;
+    let h = Vec3::new(0.0, 0.5 * height, 0.0);
+    Ok((mesh, Shape::Capsule(Capsule::new(-h, h, radius))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that the geometry is consistent and enclosed
+    // by `shape`, and that triangles face outward.
+    fn check_geometry(geom: &Geometry, shape: Shape, tri_count: usize) {
+        let n = geom.pos.len();
+        assert_eq!(geom.nrm.len(), n);
+        assert_eq!(geom.uv.len(), n);
+        assert_eq!(geom.indices.len(), 3 * tri_count);
+        assert!(geom.indices.iter().all(|&x| (x as usize) < n));
+        for x in &geom.nrm {
+            assert!((x.length() - 1.0).abs() < 1e-5, "{x:?}");
+        }
+        for t in geom.indices.chunks_exact(3) {
+            let p = [0, 1, 2].map(|i| geom.pos[t[i] as usize]);
+            let f = (p[1] - p[0]).cross(&(p[2] - p[0]));
+            assert!(f.length() > 1e-7, "{p:?}");
+            let v = t
+                .iter()
+                .fold(Vec3::default(), |a, &x| a + geom.nrm[x as usize]);
+            assert!(f.dot(&v) > 0.0, "{p:?}");
+            // Texture coordinates do not wrap around.
+            let uv = [0, 1, 2].map(|i| geom.uv[t[i] as usize]);
+            for i in 0..3 {
+                assert!((uv[i][0] - uv[(i + 1) % 3][0]).abs() <= 0.5, "{uv:?}");
+            }
+        }
+        let eps = 1e-5;
+        for p in &geom.pos {
+            match shape {
+                Shape::Bbox(x) => {
+                    let e = x.half_extent();
+                    assert!((0..3).all(|i| p[i].abs() <= e[i] + eps), "{p:?}");
+                }
+                Shape::Sphere(x) => assert!(p.length() <= x.radius() + eps),
+                Shape::Capsule(x) => {
+                    let (a, b) = x.segment();
+                    let y = p[1].clamp(a[1], b[1]);
+                    assert!((*p - Vec3::new(0.0, y, 0.0)).length() <= x.radius() + eps);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn boxes() {
+        let e = Vec3::new(1.0, 2.0, 3.0);
+        let geom = cube_geometry(e, 2);
+        check_geometry(&geom, Shape::Bbox(Bbox::new_origin(e)), 6 * 8);
+        let size = Vec2::new(2.0, 4.0);
+        let geom = grid_geometry(size, [3, 5]);
+        let e = Vec3::new(1.0, 0.0, 2.0);
+        check_geometry(&geom, Shape::Bbox(Bbox::new_origin(e)), 30);
+    }
+
+    #[test]
+    fn spheres() {
+        let shape = Shape::Sphere(Sphere::new_origin(2.0));
+        let geom = uv_sphere_geometry(2.0, 8, 6);
+        // The rings next to the poles are fans.
+        check_geometry(&geom, shape, 8 * (2 * 6 - 2));
+        for (p, n) in geom.pos.iter().zip(&geom.nrm) {
+            assert!((*p - *n * 2.0).length() < 1e-5);
+        }
+        let geom = icosphere_geometry(2.0, 2);
+        check_geometry(&geom, shape, 20 * 16);
+        for p in &geom.pos {
+            assert!((p.length() - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn revolutions() {
+        let geom = cylinder_geometry(1.0, 2.0, 8, 2);
+        let e = Vec3::new(1.0, 1.0, 1.0);
+        check_geometry(&geom, Shape::Bbox(Bbox::new_origin(e)), 8 * 4 + 2 * 8);
+        let geom = cone_geometry(1.0, 2.0, 8, 2);
+        check_geometry(&geom, Shape::Bbox(Bbox::new_origin(e)), 8 * 3 + 8);
+        let geom = torus_geometry(2.0, 0.5, 8, 6);
+        let e = Vec3::new(2.5, 0.5, 2.5);
+        check_geometry(&geom, Shape::Bbox(Bbox::new_origin(e)), 8 * 6 * 2);
+        for (p, n) in geom.pos.iter().zip(&geom.nrm) {
+            // The center of the tube is at the major radius.
+            let c = *p - *n * 0.5;
+            assert!((Vec2::new(c[0], c[2]).length() - 2.0).abs() < 1e-5);
+            assert!(c[1].abs() < 1e-5);
+        }
+
+        let geom = capsule_geometry(0.5, 2.0, 8, 3);
+        let h = Vec3::new(0.0, 1.0, 0.0);
+        let shape = Shape::Capsule(Capsule::new(-h, h, 0.5));
+        // Two hemispheres of 4 rings each.
+        check_geometry(&geom, shape, 8 * (2 * 7 - 2));
+    }
+    #[test]
+    fn constructors() {
+        crate::init();
+        let e = Vec3::new(1.0, 2.0, 3.0);
+        let size = Vec2::new(2.0, 4.0);
+        let h = Vec3::new(0.0, 1.0, 0.0);
+        let bbox = |x, y, z| Shape::Bbox(Bbox::new_origin(Vec3::new(x, y, z)));
+        for (x, geom, shape) in [
+            (
+                cube(e, 2),
+                cube_geometry(e, 2),
+                Shape::Bbox(Bbox::new_origin(e)),
+            ),
+            (
+                grid(size, 3, 5),
+                grid_geometry(size, [3, 5]),
+                bbox(1.0, 0.0, 2.0),
+            ),
+            (
+                plane(size),
+                grid_geometry(size, [1, 1]),
+                bbox(1.0, 0.0, 2.0),
+            ),
+            (
+                uv_sphere(2.0, 8, 6),
+                uv_sphere_geometry(2.0, 8, 6),
+                Shape::Sphere(Sphere::new_origin(2.0)),
+            ),
+            (
+                icosphere(2.0, 2),
+                icosphere_geometry(2.0, 2),
+                Shape::Sphere(Sphere::new_origin(2.0)),
+            ),
+            (
+                cylinder(1.0, 2.0, 8, 2),
+                cylinder_geometry(1.0, 2.0, 8, 2),
+                bbox(1.0, 1.0, 1.0),
+            ),
+            (
+                cone(1.0, 2.0, 8, 2),
+                cone_geometry(1.0, 2.0, 8, 2),
+                bbox(1.0, 1.0, 1.0),
+            ),
+            (
+                torus(2.0, 0.5, 8, 6),
+                torus_geometry(2.0, 0.5, 8, 6),
+                bbox(2.5, 0.5, 2.5),
+            ),
+            (
+                capsule(0.5, 2.0, 8, 3),
+                capsule_geometry(0.5, 2.0, 8, 3),
+                Shape::Capsule(Capsule::new(-h, h, 0.5)),
+            ),
+        ] {
+            let (mesh, s) = x.unwrap();
+            assert_eq!(s, shape);
+            assert_eq!(mesh.primitives().len(), 1);
+            let p0 = &mesh.primitives()[0];
+            assert_eq!(p0.topology(), Topology::Triangle);
+            assert_eq!(p0.vertex_count(), geom.indices.len());
+            assert_eq!(p0.index_data_type(), Some(DataType::U16));
+            for (sem, dt) in [
+                (Semantic::Position, DataType::F32x3),
+                (Semantic::Normal, DataType::F32x3),
+                (Semantic::Tangent, DataType::F32x4),
+                (Semantic::TexCoord0, DataType::F32x2),
+            ] {
+                assert_eq!(p0.semantic_data_type(sem), Some(dt), "{sem:?}");
+            }
+        }
+
+        assert!(cube(e, 0).is_err());
+        assert!(grid(size, 0, 1).is_err());
+        assert!(uv_sphere(1.0, 2, 2).is_err());
+        assert!(uv_sphere(1.0, 3, 1).is_err());
+        assert!(icosphere(1.0, 8).is_err());
+        assert!(cylinder(1.0, 1.0, 2, 1).is_err());
+        assert!(cone(1.0, 1.0, 3, 0).is_err());
+        assert!(torus(1.0, 0.5, 3, 2).is_err());
+        assert!(capsule(1.0, 1.0, 3, 0).is_err());
+    }
+}
@@ -0,0 +1,415 @@
+//! Simplification of triangle meshes.
+
+use std::collections::HashMap;
+
+use crate::linear::Vec3;
+use crate::mesh::generate::key;
+
+/// Weight of the planes that preserve open borders,
+/// relative to the squared length of border edges.
+const BORDER_WEIGHT: f64 = 10.0;
+
+/// Vertex data considered by [`simplify`].
+pub(super) struct Vertices<'a> {
+    pub pos: &'a [Vec3<f32>],
+    /// `attr_n` attributes (e.g., normals and texture
+    /// coordinates) per vertex.
+    pub attr: &'a [f32],
+    pub attr_n: usize,
+    /// Joint indices and weights of up to eight influences
+    /// per vertex, which may be empty.
+    pub skin: &'a [[(u32, f32); 8]],
+}
+
+impl Vertices<'_> {
+    /// Returns how much the attributes of `u` and `v` differ.
+    fn distance(&self, u: usize, v: usize) -> f32 {
+        let n = self.attr_n;
+        let a = &self.attr[u * n..u * n + n];
+        let b = &self.attr[v * n..v * n + n];
+        let mut d: f32 = a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum();
+        if !self.skin.is_empty() {
+            let (a, b) = (&self.skin[u], &self.skin[v]);
+            let weight = |x: &[(u32, f32); 8], j| -> f32 {
+                x.iter().filter(|x| x.0 == j).map(|x| x.1).sum()
+            };
+            let mut joints: Vec<_> = a.iter().chain(b).map(|x| x.0).collect();
+            joints.sort_unstable();
+            joints.dedup();
+            for j in joints {
+                let w: f32 = weight(a, j) - weight(b, j);
+                d += w * w;
+            }
+        }
+        d
+    }
+}
+
+/// Symmetric 4x4 matrix measuring the squared distance of
+/// a point to a set of planes.
+#[derive(Copy, Clone, Default, Debug)]
+struct Quadric {
+    a: [f64; 10],
+    // Sum of the planes' weights.
+    w: f64,
+}
+
+impl Quadric {
+    /// Creates the quadric of the plane through `p` with
+    /// (unit) normal `n`.
+    fn plane(n: &Vec3<f32>, p: &Vec3<f32>, w: f64) -> Self {
+        let [x, y, z] = <[f32; 3]>::from(n).map(f64::from);
+        let d = -f64::from(n.dot(p));
+        Self {
+            a: [
+                x * x,
+                x * y,
+                x * z,
+                x * d,
+                y * y,
+                y * z,
+                y * d,
+                z * z,
+                z * d,
+                d * d,
+            ]
+            .map(|a| a * w),
+            w,
+        }
+    }
+
+    fn add(&mut self, other: &Self) {
+        for (a, b) in self.a.iter_mut().zip(other.a) {
+            *a += b;
+        }
+        self.w += other.w;
+    }
+
+    /// Returns the mean squared distance of `p` to the planes.
+    fn error(&self, p: &Vec3<f32>) -> f64 {
+        if self.w == 0.0 {
+            return 0.0;
+        }
+        let [x, y, z] = <[f32; 3]>::from(p).map(f64::from);
+        let a = &self.a;
+        let e = a[0] * x * x
+            + 2.0 * (a[1] * x * y + a[2] * x * z + a[3] * x)
+            + a[4] * y * y
+            + 2.0 * (a[5] * y * z + a[6] * y)
+            + a[7] * z * z
+            + 2.0 * a[8] * z
+            + a[9];
+        (e / self.w).max(0.0)
+    }
+}
+
+/// Simplifies a triangle list using quadric error metrics.
+///
+/// Edges are collapsed onto one of their vertices, so the
+/// result references a subset of the original vertices and
+/// their attributes are never interpolated. Collapses that
+/// would split a vertex from the attributes it shares with
+/// its neighbors (as on UV seams) or flip a triangle are
+/// rejected, and differences in attributes and skin weights
+/// add to the cost of a collapse.
+///
+/// It stops once `target` triangles remain or no edge can be
+/// collapsed within `max_error`, and returns the new indices
+/// along with the largest error of an applied collapse (the
+/// RMS distance to the original surface around the vertex).
+pub(super) fn simplify(
+    verts: &Vertices,
+    indices: &[u32],
+    target: usize,
+    max_error: f32,
+) -> (Vec<u32>, f32) {
+    // Group vertices by position.
+    let mut groups = HashMap::new();
+    let group: Vec<usize> = (verts.pos.iter())
+        .map(|x| {
+            let n = groups.len();
+            *groups.entry(key((*x).into())).or_insert(n)
+        })
+        .collect();
+    let mut gpos = vec![Vec3::default(); groups.len()];
+    for (v, &g) in group.iter().enumerate() {
+        gpos[g] = verts.pos[v];
+    }
+    let g = |v: u32| group[v as usize];
+
+    let mut tris: Vec<[u32; 3]> = (indices.chunks_exact(3))
+        .map(|x| [x[0], x[1], x[2]])
+        .filter(|x| g(x[0]) != g(x[1]) && g(x[1]) != g(x[2]) && g(x[2]) != g(x[0]))
+        .collect();
+    let mut alive = vec![true; tris.len()];
+    let mut live = tris.len();
+
+    let mut quadrics = vec![Quadric::default(); groups.len()];
+    let mut edges = HashMap::new();
+    for (i, t) in tris.iter().enumerate() {
+        let p = t.map(|v| gpos[g(v)]);
+        let n = (p[1] - p[0]).cross(&(p[2] - p[0]));
+        let len = n.length();
+        if len > 0.0 {
+            let q = Quadric::plane(&(n / len), &p[0], f64::from(len) * 0.5);
+            t.iter().for_each(|&v| quadrics[g(v)].add(&q));
+        }
+        for k in 0..3 {
+            let (a, b) = (g(t[k]), g(t[(k + 1) % 3]));
+            let e = edges.entry((a.min(b), a.max(b))).or_insert((0, i));
+            e.0 += 1;
+        }
+    }
+    // Keep open borders in place.
+    for (&(a, b), &(n, i)) in &edges {
+        let p = tris[i].map(|v| gpos[g(v)]);
+        let nrm = (p[1] - p[0]).cross(&(p[2] - p[0]));
+        let e = gpos[b] - gpos[a];
+        let x = e.cross(&nrm);
+        if n != 1 || x.length() == 0.0 {
+            continue;
+        }
+        let w = f64::from(e.dot(&e)) * BORDER_WEIGHT;
+        let q = Quadric::plane(&x.normalize(), &gpos[a], w);
+        quadrics[a].add(&q);
+        quadrics[b].add(&q);
+    }
+
+    let mut error = 0.0f32;
+    while live > target {
+        let mut adj = vec![vec![]; groups.len()];
+        for (i, t) in tris.iter().enumerate().filter(|x| alive[x.0]) {
+            t.iter().for_each(|&v| adj[g(v)].push(i));
+        }
+        let mut cand = vec![];
+        for t in tris.iter().enumerate().filter(|x| alive[x.0]).map(|x| x.1) {
+            for k in 0..3 {
+                let (a, b) = (t[k], t[(k + 1) % 3]);
+                for (u, v) in [(a, b), (b, a)] {
+                    let (gu, gv) = (g(u), g(v));
+                    let geo = quadrics[gu].error(&gpos[gv]) as f32;
+                    let e = gpos[gv] - gpos[gu];
+                    let cost = geo + verts.distance(u as usize, v as usize) * e.dot(&e);
+                    cand.push((cost, geo.sqrt(), gu, gv));
+                }
+            }
+        }
+        cand.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Only collapse edges whose neighborhood is unchanged
+        // during a pass.
+        let mut touched = vec![false; groups.len()];
+        let mut any = false;
+        for (_, e, gu, gv) in cand {
+            if live <= target {
+                break;
+            }
+            if touched[gu] || touched[gv] || e > max_error {
+                continue;
+            }
+            let Some(remap) = collapse(&tris, &adj[gu], &group, &gpos, gu, gv) else {
+                continue;
+            };
+            for &i in &adj[gu] {
+                let t = &mut tris[i];
+                if t.iter().any(|&v| g(v) == gv) {
+                    alive[i] = false;
+                    live -= 1;
+                } else {
+                    for v in t.iter_mut().filter(|v| group[**v as usize] == gu) {
+                        *v = remap.iter().find(|x| x.0 == *v).unwrap().1;
+                    }
+                }
+                for &v in &tris[i] {
+                    touched[g(v)] = true;
+                }
+            }
+            let q = quadrics[gu];
+            quadrics[gv].add(&q);
+            error = error.max(e);
+            any = true;
+        }
+        if !any {
+            break;
+        }
+    }
+    let out = (tris.iter().zip(&alive))
+        .filter(|x| *x.1)
+        .flat_map(|x| *x.0)
+        .collect();
+    (out, error)
+}
+
+/// Checks whether the vertices in group `gu` can collapse
+/// onto group `gv`, returning the vertex each one is
+/// replaced with.
+fn collapse(
+    tris: &[[u32; 3]],
+    adj: &[usize],
+    group: &[usize],
+    gpos: &[Vec3<f32>],
+    gu: usize,
+    gv: usize,
+) -> Option<Vec<(u32, u32)>> {
+    let g = |v: u32| group[v as usize];
+    let mut remap: Vec<(u32, u32)> = vec![];
+    // Vertices of `gu` must map to a single vertex of `gv`
+    // across the collapsed triangles.
+    for t in adj.iter().map(|&i| &tris[i]) {
+        let (Some(u), Some(v)) = (
+            t.iter().find(|&&x| g(x) == gu),
+            t.iter().find(|&&x| g(x) == gv),
+        ) else {
+            continue;
+        };
+        match remap.iter().find(|x| x.0 == *u) {
+            Some(x) if x.1 != *v => return None,
+            Some(_) => (),
+            None => remap.push((*u, *v)),
+        }
+    }
+    for t in adj.iter().map(|&i| &tris[i]) {
+        if t.iter().any(|&x| g(x) == gv) {
+            continue;
+        }
+        let u = t.iter().find(|&&x| g(x) == gu).unwrap();
+        remap.iter().find(|x| x.0 == *u)?;
+        let p = t.map(|x| gpos[g(x)]);
+        let q = t.map(|x| if g(x) == gu { gpos[gv] } else { gpos[g(x)] });
+        let n0 = (p[1] - p[0]).cross(&(p[2] - p[0]));
+        let n1 = (q[1] - q[0]).cross(&(q[2] - q[0]));
+        if n0.dot(&n1) <= 0.0 {
+            return None;
+        }
+    }
+    Some(remap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A `n` by `n` grid on the `xy` plane, with heights given
+    // by `z`. If `seam` is set, vertices on the middle column
+    // are duplicated, with the copies only used by triangles
+    // to the right.
+    fn grid(n: u32, seam: bool, z: impl Fn(f32, f32) -> f32) -> (Vec<Vec3<f32>>, Vec<u32>) {
+        let m = n + 1;
+        let mut pos: Vec<_> = (0..m * m)
+            .map(|i| {
+                let (x, y) = ((i % m) as f32, (i / m) as f32);
+                Vec3::new(x, y, z(x, y))
+            })
+            .collect();
+        let base = pos.len() as u32;
+        if seam {
+            let copies: Vec<_> = (0..m).map(|y| pos[(y * m + n / 2) as usize]).collect();
+            pos.extend(copies);
+        }
+        let v = |x: u32, y: u32, right: bool| {
+            if seam && right && x == n / 2 {
+                base + y
+            } else {
+                y * m + x
+            }
+        };
+        let mut idx = vec![];
+        for y in 0..n {
+            for x in 0..n {
+                let r = x >= n / 2;
+                let (a, b) = (v(x, y, r), v(x + 1, y, r));
+                let (c, d) = (v(x + 1, y + 1, r), v(x, y + 1, r));
+                idx.extend([a, b, c, a, c, d]);
+            }
+        }
+        (pos, idx)
+    }
+
+    fn area(pos: &[Vec3<f32>], idx: &[u32]) -> f32 {
+        (idx.chunks_exact(3))
+            .map(|t| {
+                let p = [0, 1, 2].map(|i| pos[t[i] as usize]);
+                (p[1] - p[0]).cross(&(p[2] - p[0])).length() * 0.5
+            })
+            .sum()
+    }
+
+    fn verts(pos: &[Vec3<f32>]) -> Vertices<'_> {
+        Vertices {
+            pos,
+            attr: &[],
+            attr_n: 0,
+            skin: &[],
+        }
+    }
+
+    #[test]
+    fn plane() {
+        let (pos, idx) = grid(8, false, |_, _| 0.0);
+        let (out, e) = simplify(&verts(&pos), &idx, 0, 1e-3);
+        assert!(out.len() / 3 <= 4, "{}", out.len() / 3);
+        assert!(e < 1e-3);
+        assert!((area(&pos, &out) - 64.0).abs() < 1e-3);
+
+        let (out, e) = simplify(&verts(&pos), &idx, 128, f32::INFINITY);
+        assert_eq!(out, idx);
+        assert_eq!(e, 0.0);
+    }
+
+    #[test]
+    fn error_bound() {
+        let (pos, idx) = grid(16, false, |x, y| (x * 0.5).sin() * (y * 0.3).cos());
+        let (fine, e1) = simplify(&verts(&pos), &idx, 0, 0.02);
+        assert!(e1 <= 0.02);
+        assert!(fine.len() < idx.len());
+        let (coarse, e2) = simplify(&verts(&pos), &idx, 0, 0.2);
+        assert!(e2 <= 0.2);
+        assert!(coarse.len() < fine.len());
+        let (out, _) = simplify(&verts(&pos), &idx, 64, f32::INFINITY);
+        assert!(out.len() / 3 <= 64);
+    }
+
+    #[test]
+    fn seam() {
+        let (pos, idx) = grid(8, true, |_, _| 0.0);
+        let attr: Vec<_> = (0..pos.len())
+            .flat_map(|i| [if i >= 81 || pos[i][0] > 4.0 { 1.0 } else { 0.0 }])
+            .collect();
+        let verts = Vertices {
+            pos: &pos,
+            attr: &attr,
+            attr_n: 1,
+            skin: &[],
+        };
+        let (out, _) = simplify(&verts, &idx, 0, 1e-3);
+        assert!(out.len() / 3 <= 8, "{}", out.len() / 3);
+        assert!((area(&pos, &out) - 64.0).abs() < 1e-3);
+        // No triangle crosses the seam.
+        for t in out.chunks_exact(3) {
+            let a: Vec<_> = t.iter().map(|&x| attr[x as usize]).collect();
+            assert!(a[0] == a[1] && a[1] == a[2], "{t:?}");
+        }
+    }
+
+    #[test]
+    fn skin_distance() {
+        let pos = [Vec3::default(); 3];
+        let pad = [(0, 0.0); 4];
+        let mut skin = [
+            [(0, 0.5), (1, 0.0), (2, 0.0), (3, 0.0)],
+            [(1, 0.0), (0, 1.0), (2, 0.0), (3, 0.0)],
+            [(4, 1.0), (1, 0.0), (2, 0.0), (3, 0.0)],
+        ]
+        .map(|x| [x[0], x[1], x[2], x[3], pad[0], pad[1], pad[2], pad[3]]);
+        // The second set of influences counts as well.
+        skin[0][4] = (0, 0.5);
+        let verts = Vertices {
+            pos: &pos,
+            attr: &[],
+            attr_n: 0,
+            skin: &skin,
+        };
+        assert_eq!(verts.distance(0, 1), 0.0);
+        assert_eq!(verts.distance(0, 2), 2.0);
+    }
+}
@@ -5,16 +5,77 @@ use std::mem;
 use crate::bit_vec::BitVec;
 use crate::drawable::Drawable;
 use crate::light::Light;
-use crate::linear::Mat4;
+use crate::linear::{Affine3, Mat4, Vec3};
+
+mod bvh;
+pub use crate::node::bvh::Bvh;
 
 #[cfg(test)]
 mod tests;
 
+/// Transform stored in a [`Graph`].
+///
+/// [`Affine3`] is cheaper to compose than [`Mat4`], and should
+/// be preferred unless projective transforms are required.
+///
+/// Double-precision transforms (e.g., `Graph<Affine3<f64>>`) can
+/// be used for large worlds. In this case, world transforms are
+/// accumulated in `f64` and only converted to `f32` relative to
+/// an origin (usually the active camera's position). See
+/// [`Graph::world_rel`].
+pub trait Transform: Copy + Default {
+    /// Computes the world transform of a node given the world
+    /// transform of its parent (`self`) and its own `local`
+    /// transform.
+    fn compose(&self, local: &Self) -> Self;
+
+    /// Converts `self` into a single-precision `Mat4` whose
+    /// translation is relative to `origin`.
+    ///
+    /// The subtraction is computed in double precision, so the
+    /// result is accurate whenever `self` is close to `origin`.
+    fn rebase(&self, origin: &Vec3<f64>) -> Mat4<f32>;
+}
+
+// NOTE: `f32` transforms are widened before the translation is
+// rebased so every implementation rounds only once.
+macro_rules! transform_impl {
+    ($($t:ty => $wide:expr),*) => {$(
+        impl Transform for Mat4<$t> {
+            fn compose(&self, local: &Self) -> Self {
+                self * local
+            }
+
+            fn rebase(&self, origin: &Vec3<f64>) -> Mat4<f32> {
+                let mut m = $wide(self);
+                for i in 0..3 {
+                    m[3][i] -= origin[i] * m[3][3];
+                }
+                m.to_f32()
+            }
+        }
+
+        impl Transform for Affine3<$t> {
+            fn compose(&self, local: &Self) -> Self {
+                self * local
+            }
+
+            fn rebase(&self, origin: &Vec3<f64>) -> Mat4<f32> {
+                let mut m = $wide(self);
+                m[3] -= origin;
+                Mat4::from(m.to_f32())
+            }
+        }
+    )*};
+}
+
+transform_impl!(f32 => |x: &Self| x.to_f64(), f64 => |x: &Self| *x);
+
 /// Node in a [`Graph`].
-pub enum Node {
-    Drawable(Drawable, Mat4<f32>),
-    Light(Light, Mat4<f32>),
-    Xform(Mat4<f32>),
+pub enum Node<X = Mat4<f32>> {
+    Drawable(Drawable, X),
+    Light(Light, X),
+    Xform(X),
 }
 
 #[derive(Copy, Clone)]
@@ -35,9 +96,9 @@ struct NodeLink {
     data: usize,
 }
 
-struct NodeData {
-    data: Node,
-    world: Mat4<f32>,
+struct NodeData<X> {
+    data: Node<X>,
+    world: X,
     changed: bool,
     ignored: bool,
     node: usize,
@@ -48,21 +109,28 @@ struct NodeData {
 pub struct NodeId(usize);
 
 /// Node graph.
-pub struct Graph {
+///
+/// `X` is the type of the nodes' transforms.
+///
+/// The graph keeps a [`Bvh`] of its drawables' world-space
+/// shapes, which is updated along with their world transforms.
+pub struct Graph<X = Mat4<f32>> {
     nodes: Vec<NodeLink>,
     nbits: BitVec<u32>,
-    data: Vec<NodeData>,
+    data: Vec<NodeData<X>>,
+    bvh: Bvh,
 }
 
 const NBITS_GRAN: usize = u32::BITS as _;
 
-impl Graph {
+impl<X: Transform> Graph<X> {
     /// Creates an empty graph.
     pub fn new() -> Self {
         Self {
             nodes: vec![],
             nbits: BitVec::new(),
             data: vec![],
+            bvh: Bvh::new(),
         }
     }
 
@@ -71,7 +139,7 @@ impl Graph {
     /// as an unconnected node.
     /// It returns a [`NodeId`] that identifies `node` in this
     /// specific graph.
-    pub fn insert(&mut self, node: Node, prev: Option<NodeId>) -> NodeId {
+    pub fn insert(&mut self, node: Node<X>, prev: Option<NodeId>) -> NodeId {
         let idx = self
             .nbits
             .find()
@@ -120,10 +188,11 @@ impl Graph {
     /// Removes `node` and its descendants.
     /// The [`NodeId`] of every removed node becomes invalid and
     /// thus must no longer be used.
-    pub fn remove(&mut self, node: NodeId) -> Vec<Node> {
+    pub fn remove(&mut self, node: NodeId) -> Vec<Node<X>> {
         let idx = node.0;
         let node = remove_link(self, idx);
         self.nbits.unset(idx);
+        self.bvh.remove(NodeId(idx));
         let mut nodes = vec![remove_node(self, &node)];
 
         if node.next != NONE {
@@ -141,6 +210,7 @@ impl Graph {
             while let Some(idx) = desc.pop() {
                 let node = remove_link(self, idx);
                 self.nbits.unset(idx);
+                self.bvh.remove(NodeId(idx));
                 nodes.push(remove_node(self, &node));
                 if node.next != NONE {
                     desc.push(node.next);
@@ -153,7 +223,7 @@ impl Graph {
 
         return nodes;
 
-        fn remove_link(g: &mut Graph, n: usize) -> NodeLink {
+        fn remove_link<X>(g: &mut Graph<X>, n: usize) -> NodeLink {
             mem::replace(
                 &mut g.nodes[n],
                 NodeLink {
@@ -165,7 +235,7 @@ impl Graph {
             )
         }
 
-        fn remove_node(g: &mut Graph, n: &NodeLink) -> Node {
+        fn remove_node<X>(g: &mut Graph<X>, n: &NodeLink) -> Node<X> {
             let swap = g.data.last().unwrap().node;
             g.nodes[swap].data = n.data;
             g.data.swap_remove(n.data).data
@@ -194,6 +264,7 @@ impl Graph {
                 Node::Light(_, x) => x,
                 Node::Xform(x) => x,
             };
+            self.update_bvh(data);
         }
         let sub = self.nodes[node.0].sub;
         if sub == NONE {
@@ -239,8 +310,9 @@ impl Graph {
                         Node::Light(_, x) => x,
                         Node::Xform(x) => x,
                     };
-                    self.data[data].world = prev_world * local;
+                    self.data[data].world = prev_world.compose(local);
                     self.data[data].changed = false;
+                    self.update_bvh(data);
                     // This will only affect descendants of `node`
                     // since we already pushed the next sibling.
                     prev_chg = true;
@@ -256,9 +328,37 @@ impl Graph {
         }
     }
 
+    /// Updates the [`Bvh`] entry of a drawable node
+    /// from its world transform.
+    fn update_bvh(&mut self, data: usize) {
+        if let Node::Drawable(d, _) = &self.data[data].data {
+            // NOTE: Double-precision worlds are rebased onto the
+            // origin, so their shapes lose precision far from it.
+            let world = self.data[data].world.rebase(&Vec3::default());
+            let shape = d.shape().transform(&world);
+            self.bvh.update(NodeId(self.data[data].node), shape);
+        }
+    }
+
+    /// Returns a reference to the graph's [`Bvh`].
+    ///
+    /// Only drawables that have been updated (see `update`)
+    /// are present in the hierarchy.
+    pub fn bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    /// Rebuilds the graph's [`Bvh`].
+    ///
+    /// Incremental updates degrade the hierarchy over time,
+    /// so this should be called after large changes.
+    pub fn rebuild_bvh(&mut self) {
+        self.bvh.rebuild();
+    }
+
     /// Returns a reference to the [`Node`] that a given
     /// [`NodeId`] identifies.
-    pub fn node(&self, node: NodeId) -> &Node {
+    pub fn node(&self, node: NodeId) -> &Node<X> {
         let data = self.nodes[node.0].data;
         &self.data[data].data
     }
@@ -287,7 +387,7 @@ impl Graph {
 
     /// Returns a reference to the local transform that a
     /// given [`NodeId`] identifies.
-    pub fn local(&self, node: NodeId) -> &Mat4<f32> {
+    pub fn local(&self, node: NodeId) -> &X {
         let data = self.nodes[node.0].data;
         match &self.data[data].data {
             Node::Drawable(_, x) => x,
@@ -300,7 +400,7 @@ impl Graph {
     /// that a given [`NodeId`] identifies.
     ///
     /// The sub-graph rooted at `node` becomes out of date.
-    pub fn local_mut(&mut self, node: NodeId) -> &mut Mat4<f32> {
+    pub fn local_mut(&mut self, node: NodeId) -> &mut X {
         let data = self.nodes[node.0].data;
         self.data[data].changed = true;
         match &mut self.data[data].data {
@@ -314,11 +414,22 @@ impl Graph {
     /// given [`NodeId`] identifies.
     ///
     /// This transform is not necessarily up to date.
-    pub fn world(&self, node: NodeId) -> &Mat4<f32> {
+    pub fn world(&self, node: NodeId) -> &X {
         let data = self.nodes[node.0].data;
         &self.data[data].world
     }
 
+    /// Returns the world transform that a given [`NodeId`]
+    /// identifies, in single precision and relative to `origin`.
+    ///
+    /// This is meant for camera-relative rendering, with `origin`
+    /// set to the camera's world position.
+    ///
+    /// This transform is not necessarily up to date.
+    pub fn world_rel(&self, node: NodeId, origin: &Vec3<f64>) -> Mat4<f32> {
+        self.world(node).rebase(origin)
+    }
+
     /// Returns the length of the graph.
     pub fn len(&self) -> usize {
         self.data.len()
@@ -0,0 +1,614 @@
+//! Bounding volume hierarchy.
+
+use crate::drawable::Shape;
+use crate::linear::Vec3;
+use crate::node::{NodeId, NONE};
+use crate::shape::{Bbox, Frustum, Hit, Intersection, Ray, Sphere};
+
+// Sentinel value that `Bvh::leaves` uses to identify
+// nodes whose shape is `Shape::None`.
+const UNBOUNDED: usize = NONE - 1;
+
+// Number of bins used when evaluating the SAH.
+const BIN_N: usize = 16;
+
+/// Axis-aligned box stored as min/max corners.
+#[derive(Copy, Clone, Debug)]
+struct Aabb {
+    min: Vec3<f32>,
+    max: Vec3<f32>,
+}
+
+impl Aabb {
+    fn empty() -> Self {
+        Self {
+            min: Vec3::from(f32::INFINITY),
+            max: Vec3::from(f32::NEG_INFINITY),
+        }
+    }
+
+    fn new(shape: &Shape) -> Self {
+        let bbox = match *shape {
+            Shape::Bbox(x) => x,
+            Shape::Sphere(x) => Bbox::from(x),
+            Shape::Obb(x) => Bbox::from(x),
+            Shape::Capsule(x) => Bbox::from(x),
+            Shape::None => unreachable!(),
+        };
+        Self {
+            min: bbox.center() - bbox.half_extent(),
+            max: bbox.center() + bbox.half_extent(),
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let mut u = *self;
+        for i in 0..3 {
+            u.min[i] = u.min[i].min(other.min[i]);
+            u.max[i] = u.max[i].max(other.max[i]);
+        }
+        u
+    }
+
+    /// Computes half of the surface area.
+    fn area(&self) -> f32 {
+        let e = self.max - self.min;
+        if e[0] < 0.0 {
+            0.0
+        } else {
+            e[0] * e[1] + e[1] * e[2] + e[2] * e[0]
+        }
+    }
+
+    fn overlaps(&self, other: &Self) -> bool {
+        (0..3).all(|i| self.min[i] <= other.max[i] && self.max[i] >= other.min[i])
+    }
+
+    fn centroid(&self) -> Vec3<f32> {
+        (self.min + self.max) / 2.0
+    }
+
+    fn bbox(&self) -> Bbox {
+        Bbox::new(self.centroid(), (self.max - self.min) / 2.0)
+    }
+
+    /// Computes the distance at which `ray` enters the box,
+    /// or zero if its origin is inside.
+    fn entry(&self, ray: &Ray) -> Option<f32> {
+        let (o, d) = (ray.origin(), ray.dir());
+        let (mut near, mut far) = (0f32, f32::INFINITY);
+        for i in 0..3 {
+            if d[i] == 0.0 {
+                if o[i] < self.min[i] || o[i] > self.max[i] {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1.0 / d[i];
+            let t0 = (self.min[i] - o[i]) * inv;
+            let t1 = (self.max[i] - o[i]) * inv;
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        (near <= far).then_some(near)
+    }
+}
+
+#[derive(Debug)]
+struct BvhNode {
+    aabb: Aabb,
+    parent: usize,
+    // `NONE` for leaves and unused nodes.
+    child: [usize; 2],
+    // `NONE` for internal and unused nodes.
+    key: usize,
+    // Meaningful for leaves only.
+    shape: Shape,
+}
+
+impl BvhNode {
+    fn is_leaf(&self) -> bool {
+        self.child[0] == NONE
+    }
+}
+
+/// Dynamic bounding volume hierarchy of world-space shapes.
+///
+/// Each leaf is keyed by the [`NodeId`] of a
+/// [`Node::Drawable`](crate::node::Node::Drawable).
+/// Shapes are inserted incrementally, using the surface area
+/// heuristic (SAH) to choose where, and refitted in place as
+/// they change. `rebuild` performs a full (binned) SAH build,
+/// which restores the tree's quality.
+///
+/// Drawables whose shape is [`Shape::None`] are kept apart.
+/// They are reported by every frustum query and never by
+/// any other query.
+#[derive(Debug)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    free: Vec<usize>,
+    root: usize,
+    // Maps `NodeId`s into leaf indices.
+    leaves: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl Bvh {
+    /// Creates an empty hierarchy.
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![],
+            free: vec![],
+            root: NONE,
+            leaves: vec![],
+            unbounded: vec![],
+        }
+    }
+
+    /// Inserts or updates the world-space `shape` of `node`.
+    ///
+    /// An existing leaf is refitted in place unless its new
+    /// bounds are disjoint from the previous ones, in which
+    /// case it is reinserted.
+    pub fn update(&mut self, node: NodeId, shape: Shape) {
+        let id = node.0;
+        if id >= self.leaves.len() {
+            self.leaves.resize(id + 1, NONE);
+        }
+        match (self.leaves[id], shape) {
+            (UNBOUNDED, Shape::None) => (),
+            (NONE, Shape::None) => {
+                self.leaves[id] = UNBOUNDED;
+                self.unbounded.push(id);
+            }
+            (NONE, _) => {
+                self.leaves[id] = self.insert_leaf(id, shape);
+            }
+            (UNBOUNDED, _) => {
+                self.remove_unbounded(id);
+                self.leaves[id] = self.insert_leaf(id, shape);
+            }
+            (leaf, Shape::None) => {
+                self.remove_leaf(leaf);
+                self.leaves[id] = UNBOUNDED;
+                self.unbounded.push(id);
+            }
+            (leaf, _) => {
+                let aabb = Aabb::new(&shape);
+                self.nodes[leaf].shape = shape;
+                if aabb.overlaps(&self.nodes[leaf].aabb) {
+                    self.nodes[leaf].aabb = aabb;
+                    self.refit(self.nodes[leaf].parent);
+                } else {
+                    self.remove_leaf(leaf);
+                    self.leaves[id] = self.insert_leaf(id, shape);
+                }
+            }
+        }
+    }
+
+    /// Removes `node`.
+    ///
+    /// It has no effect if `node` is not in the hierarchy.
+    pub fn remove(&mut self, node: NodeId) {
+        let id = node.0;
+        match self.leaves.get(id).copied() {
+            None | Some(NONE) => return,
+            Some(UNBOUNDED) => self.remove_unbounded(id),
+            Some(leaf) => self.remove_leaf(leaf),
+        }
+        self.leaves[id] = NONE;
+    }
+
+    /// Returns the world-space shape of `node`, or `None`
+    /// if it is not in the hierarchy.
+    pub fn shape(&self, node: NodeId) -> Option<Shape> {
+        match self.leaves.get(node.0).copied() {
+            None | Some(NONE) => None,
+            Some(UNBOUNDED) => Some(Shape::None),
+            Some(leaf) => Some(self.nodes[leaf].shape),
+        }
+    }
+
+    /// Rebuilds the whole hierarchy using the SAH.
+    pub fn rebuild(&mut self) {
+        let mut leaves = Vec::with_capacity(self.nodes.len());
+        self.free.clear();
+        for (i, x) in self.nodes.iter_mut().enumerate() {
+            if x.key != NONE {
+                leaves.push(i);
+            } else {
+                x.child = [NONE; 2];
+                self.free.push(i);
+            }
+        }
+        self.root = if leaves.is_empty() {
+            NONE
+        } else {
+            self.build(&mut leaves, NONE)
+        };
+    }
+
+    /// Returns the number of shapes in the hierarchy.
+    ///
+    /// This counts both bounded shapes (i.e., leaves) and
+    /// [`Shape::None`]s. It does not count internal nodes.
+    pub fn len(&self) -> usize {
+        // A tree with `n` leaves has `n - 1` internal nodes.
+        (self.nodes.len() - self.free.len()).div_ceil(2) + self.unbounded.len()
+    }
+
+    /// Returns whether the hierarchy is empty.
+    ///
+    /// This is equivalent to `self.len() == 0`.
+    pub fn is_empty(&self) -> bool {
+        self.root == NONE && self.unbounded.is_empty()
+    }
+
+    /// Calls `f` for every node that intersects `frustum`.
+    pub fn query_frustum(&self, frustum: &Frustum, mut f: impl FnMut(NodeId, Intersection)) {
+        for &i in &self.unbounded {
+            f(NodeId(i), Intersection::Intersecting);
+        }
+        if self.root == NONE {
+            return;
+        }
+        let mut stack = vec![(self.root, false)];
+        while let Some((i, inside)) = stack.pop() {
+            let node = &self.nodes[i];
+            let res = if inside {
+                Intersection::Inside
+            } else {
+                frustum.intersects_bbox(node.aabb.bbox())
+            };
+            match res {
+                Intersection::Outside => (),
+                Intersection::Inside if node.is_leaf() => f(NodeId(node.key), res),
+                _ if node.is_leaf() => {
+                    let res = match node.shape {
+                        Shape::Bbox(x) => frustum.intersects_bbox(x),
+                        Shape::Sphere(x) => frustum.intersects_sphere(x),
+                        Shape::Obb(x) => frustum.intersects_obb(x),
+                        Shape::Capsule(x) => frustum.intersects_capsule(x),
+                        Shape::None => unreachable!(),
+                    };
+                    if res != Intersection::Outside {
+                        f(NodeId(node.key), res);
+                    }
+                }
+                _ => {
+                    let inside = res == Intersection::Inside;
+                    stack.push((node.child[0], inside));
+                    stack.push((node.child[1], inside));
+                }
+            }
+        }
+    }
+
+    /// Calls `f` for every node that `ray` hits.
+    ///
+    /// Nodes are not visited in any particular order.
+    pub fn query_ray(&self, ray: &Ray, mut f: impl FnMut(NodeId, Hit)) {
+        self.traverse(
+            |x| x.entry(ray).is_some(),
+            |x| {
+                if let Some(hit) = intersect_ray(ray, x) {
+                    f(NodeId(x.key), hit);
+                }
+            },
+        );
+    }
+
+    /// Finds the closest node that `ray` hits.
+    pub fn closest_hit(&self, ray: &Ray) -> Option<(NodeId, Hit)> {
+        if self.root == NONE {
+            return None;
+        }
+        let mut best: Option<(NodeId, Hit)> = None;
+        let mut stack = vec![(self.root, 0.0)];
+        while let Some((i, entry)) = stack.pop() {
+            if best.is_some_and(|x| x.1.distance < entry) {
+                continue;
+            }
+            let node = &self.nodes[i];
+            if node.is_leaf() {
+                if let Some(hit) = intersect_ray(ray, node) {
+                    if best.is_none_or(|x| hit.distance < x.1.distance) {
+                        best = Some((NodeId(node.key), hit));
+                    }
+                }
+                continue;
+            }
+            let e0 = self.nodes[node.child[0]].aabb.entry(ray);
+            let e1 = self.nodes[node.child[1]].aabb.entry(ray);
+            // Visit the nearest child first.
+            let mut ch = [(node.child[0], e0), (node.child[1], e1)];
+            if e0.unwrap_or(f32::INFINITY) < e1.unwrap_or(f32::INFINITY) {
+                ch.swap(0, 1);
+            }
+            for (c, e) in ch {
+                if let Some(e) = e {
+                    stack.push((c, e));
+                }
+            }
+        }
+        best
+    }
+
+    /// Calls `f` for every node that intersects `sphere`.
+    pub fn query_sphere(&self, sphere: Sphere, mut f: impl FnMut(NodeId)) {
+        self.traverse(
+            |x| sphere.intersects_bbox(x.bbox()),
+            |x| {
+                let hit = match x.shape {
+                    Shape::Bbox(y) => sphere.intersects_bbox(y),
+                    Shape::Sphere(y) => sphere.intersects(y),
+                    Shape::Obb(y) => sphere.intersects_obb(y),
+                    Shape::Capsule(y) => sphere.intersects_capsule(y),
+                    Shape::None => unreachable!(),
+                };
+                if hit {
+                    f(NodeId(x.key));
+                }
+            },
+        );
+    }
+
+    /// Calls `f` for every node that intersects `bbox`.
+    pub fn query_bbox(&self, bbox: Bbox, mut f: impl FnMut(NodeId)) {
+        self.traverse(
+            |x| bbox.intersects(x.bbox()),
+            |x| {
+                let hit = match x.shape {
+                    Shape::Bbox(y) => bbox.intersects(y),
+                    Shape::Sphere(y) => bbox.intersects_sphere(y),
+                    Shape::Obb(y) => bbox.intersects_obb(y),
+                    Shape::Capsule(y) => bbox.intersects_capsule(y),
+                    Shape::None => unreachable!(),
+                };
+                if hit {
+                    f(NodeId(x.key));
+                }
+            },
+        );
+    }
+
+    /// Visits every leaf whose ancestors (and itself)
+    /// pass `test`.
+    fn traverse(&self, test: impl Fn(&Aabb) -> bool, mut leaf: impl FnMut(&BvhNode)) {
+        if self.root == NONE {
+            return;
+        }
+        let mut stack = vec![self.root];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !test(&node.aabb) {
+                continue;
+            }
+            if node.is_leaf() {
+                leaf(node);
+            } else {
+                stack.extend(node.child);
+            }
+        }
+    }
+
+    fn alloc(&mut self, node: BvhNode) -> usize {
+        match self.free.pop() {
+            Some(i) => {
+                self.nodes[i] = node;
+                i
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn dealloc(&mut self, i: usize) {
+        self.nodes[i].child = [NONE; 2];
+        self.nodes[i].key = NONE;
+        self.free.push(i);
+    }
+
+    fn remove_unbounded(&mut self, id: usize) {
+        let i = self.unbounded.iter().position(|&x| x == id).unwrap();
+        self.unbounded.swap_remove(i);
+    }
+
+    /// Recomputes the bounds of `i` and its ancestors.
+    fn refit(&mut self, mut i: usize) {
+        while i != NONE {
+            let [c0, c1] = self.nodes[i].child;
+            self.nodes[i].aabb = self.nodes[c0].aabb.union(&self.nodes[c1].aabb);
+            i = self.nodes[i].parent;
+        }
+    }
+
+    fn insert_leaf(&mut self, key: usize, shape: Shape) -> usize {
+        let aabb = Aabb::new(&shape);
+        let leaf = self.alloc(BvhNode {
+            aabb,
+            parent: NONE,
+            child: [NONE; 2],
+            key,
+            shape,
+        });
+        if self.root == NONE {
+            self.root = leaf;
+            return leaf;
+        }
+
+        // Descend towards the sibling that minimizes
+        // the increase in surface area.
+        let mut i = self.root;
+        while !self.nodes[i].is_leaf() {
+            let area = self.nodes[i].aabb.area();
+            let comb = self.nodes[i].aabb.union(&aabb).area();
+            let cost = 2.0 * comb;
+            let inherit = 2.0 * (comb - area);
+            let child_cost = |c: usize| {
+                let node = &self.nodes[c];
+                let u = node.aabb.union(&aabb).area();
+                if node.is_leaf() {
+                    u + inherit
+                } else {
+                    u - node.aabb.area() + inherit
+                }
+            };
+            let [c0, c1] = self.nodes[i].child;
+            let (cost0, cost1) = (child_cost(c0), child_cost(c1));
+            if cost < cost0 && cost < cost1 {
+                break;
+            }
+            i = if cost0 < cost1 { c0 } else { c1 };
+        }
+
+        let sibling = i;
+        let parent = self.nodes[sibling].parent;
+        let new = self.alloc(BvhNode {
+            aabb: self.nodes[sibling].aabb.union(&aabb),
+            parent,
+            child: [sibling, leaf],
+            key: NONE,
+            shape: Shape::None,
+        });
+        self.nodes[sibling].parent = new;
+        self.nodes[leaf].parent = new;
+        if parent == NONE {
+            self.root = new;
+        } else {
+            let ch = &mut self.nodes[parent].child;
+            ch[(ch[1] == sibling) as usize] = new;
+            self.refit(parent);
+        }
+        leaf
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        let parent = self.nodes[leaf].parent;
+        self.dealloc(leaf);
+        if parent == NONE {
+            self.root = NONE;
+            return;
+        }
+        let [c0, c1] = self.nodes[parent].child;
+        let sibling = if c0 == leaf { c1 } else { c0 };
+        let grand = self.nodes[parent].parent;
+        self.nodes[sibling].parent = grand;
+        self.dealloc(parent);
+        if grand == NONE {
+            self.root = sibling;
+        } else {
+            let ch = &mut self.nodes[grand].child;
+            ch[(ch[1] == parent) as usize] = sibling;
+            self.refit(grand);
+        }
+    }
+
+    /// Builds a sub-tree from `leaves` using the binned SAH.
+    fn build(&mut self, leaves: &mut [usize], parent: usize) -> usize {
+        if leaves.len() == 1 {
+            self.nodes[leaves[0]].parent = parent;
+            return leaves[0];
+        }
+
+        let mut cent = Aabb::empty();
+        for &i in leaves.iter() {
+            let c = self.nodes[i].aabb.centroid();
+            cent = cent.union(&Aabb { min: c, max: c });
+        }
+        let ext = cent.max - cent.min;
+        let axis = if ext[0] >= ext[1] && ext[0] >= ext[2] {
+            0
+        } else if ext[1] >= ext[2] {
+            1
+        } else {
+            2
+        };
+
+        let mid = if ext[axis] > 0.0 {
+            let bin = |x: &Aabb| {
+                let t = (x.centroid()[axis] - cent.min[axis]) / ext[axis];
+                ((t * BIN_N as f32) as usize).min(BIN_N - 1)
+            };
+            let mut bins = [(Aabb::empty(), 0usize); BIN_N];
+            for &i in leaves.iter() {
+                let b = &mut bins[bin(&self.nodes[i].aabb)];
+                b.0 = b.0.union(&self.nodes[i].aabb);
+                b.1 += 1;
+            }
+            // `right[k]` accumulates bins `k + 1..`.
+            let mut right = [(Aabb::empty(), 0usize); BIN_N];
+            for k in (0..BIN_N - 1).rev() {
+                let (a, n) = right[k + 1];
+                right[k] = (a.union(&bins[k + 1].0), n + bins[k + 1].1);
+            }
+            let mut left = (Aabb::empty(), 0usize);
+            let mut best = (f32::INFINITY, 0);
+            for k in 0..BIN_N - 1 {
+                left = (left.0.union(&bins[k].0), left.1 + bins[k].1);
+                let cost = left.0.area() * left.1 as f32 + right[k].0.area() * right[k].1 as f32;
+                if left.1 > 0 && right[k].1 > 0 && cost < best.0 {
+                    best = (cost, k);
+                }
+            }
+            let mut mid = 0;
+            for j in 0..leaves.len() {
+                if bin(&self.nodes[leaves[j]].aabb) <= best.1 {
+                    leaves.swap(j, mid);
+                    mid += 1;
+                }
+            }
+            mid
+        } else {
+            0
+        };
+        // Fall back to a median split if the SAH
+        // could not separate the leaves.
+        let mid = if mid == 0 || mid == leaves.len() {
+            let mid = leaves.len() / 2;
+            let nodes = &self.nodes;
+            leaves.select_nth_unstable_by(mid, |&a, &b| {
+                let (a, b) = (nodes[a].aabb.centroid(), nodes[b].aabb.centroid());
+                a[axis].total_cmp(&b[axis])
+            });
+            mid
+        } else {
+            mid
+        };
+
+        let node = self.alloc(BvhNode {
+            aabb: Aabb::empty(),
+            parent,
+            child: [NONE; 2],
+            key: NONE,
+            shape: Shape::None,
+        });
+        let (l, r) = leaves.split_at_mut(mid);
+        let c0 = self.build(l, node);
+        let c1 = self.build(r, node);
+        self.nodes[node].child = [c0, c1];
+        self.nodes[node].aabb = self.nodes[c0].aabb.union(&self.nodes[c1].aabb);
+        node
+    }
+}
+
+impl Default for Bvh {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Computes the intersection of `ray` and a leaf's shape.
+fn intersect_ray(ray: &Ray, leaf: &BvhNode) -> Option<Hit> {
+    match leaf.shape {
+        Shape::Bbox(x) => ray.intersect_bbox(x),
+        Shape::Sphere(x) => ray.intersect_sphere(x),
+        Shape::Obb(x) => ray.intersect_obb(x),
+        Shape::Capsule(x) => ray.intersect_capsule(x),
+        Shape::None => unreachable!(),
+    }
+}
@@ -1,4 +1,11 @@
+use std::io;
+use std::sync::Arc;
+
+use crate::drawable::Shape;
 use crate::light::LightType;
+use crate::linear::{Mat3, Quat, Vec3};
+use crate::mesh::{self, DataType, Semantic, Topology};
+use crate::shape::{Bbox, Capsule, DepthRange, Frustum, Intersection, Obb, Ray, Sphere};
 
 use super::*;
 
@@ -1296,3 +1303,418 @@ fn get_set() {
     let m = Mat4::scale(0.5, 0.5, 0.5) * m;
     assert_eq!(g.world(n), &m);
 }
+
+#[test]
+fn update_affine() {
+    let mut g = Graph::<Affine3<f32>>::new();
+    let mut h = Graph::<Mat4<f32>>::new();
+
+    let m1 = Mat4::from_trs(
+        &Vec3::new(1.0, 2.0, 3.0),
+        &Quat::rotation_y(0.5),
+        &Vec3::new(2.0, 2.0, 2.0),
+    );
+    let m2 = Mat4::translation(-10.0, 2.0, -6.0);
+    let m3 = Mat4::rotation_x(-1.5) * Mat4::scale(1.0, 0.5, 0.25);
+
+    let n1 = g.insert(Node::Xform(m1.into()), None);
+    let n2 = g.insert(Node::Xform(m2.into()), Some(n1));
+    let n3 = g.insert(
+        Node::Light(Light::new_white(LightType::Directional, 1000.0), m3.into()),
+        Some(n2),
+    );
+    let k1 = h.insert(Node::Xform(m1), None);
+    let k2 = h.insert(Node::Xform(m2), Some(k1));
+    let k3 = h.insert(
+        Node::Light(Light::new_white(LightType::Directional, 1000.0), m3),
+        Some(k2),
+    );
+
+    g.update(n1);
+    h.update(k1);
+    for (n, k) in [(n1, k1), (n2, k2), (n3, k3)] {
+        assert_eq!(g.world(n), &Affine3::from(h.world(k)));
+    }
+
+    *g.local_mut(n2) *= Affine3::from(m3);
+    *h.local_mut(k2) *= m3;
+    g.update(n1);
+    h.update(k1);
+    for (n, k) in [(n1, k1), (n2, k2), (n3, k3)] {
+        assert_eq!(g.world(n), &Affine3::from(h.world(k)));
+    }
+}
+
+#[test]
+fn world_rel() {
+    let mut g = Graph::<Affine3<f64>>::new();
+    let mut h = Graph::<Affine3<f32>>::new();
+
+    let m1 = Mat4::<f64>::translation(1e7, -4e6, 3e6);
+    let m2 = Mat4::translation(0.125, 0.0625, -0.25) * Mat4::rotation_z(0.5);
+    let m3 = Mat4::translation(0.5, 0.25, 0.0);
+
+    let n1 = g.insert(Node::Xform(m1.into()), None);
+    let n2 = g.insert(Node::Xform(m2.into()), Some(n1));
+    let n3 = g.insert(Node::Xform(m3.into()), Some(n2));
+    let k1 = h.insert(Node::Xform(m1.to_f32().into()), None);
+    let k2 = h.insert(Node::Xform(m2.to_f32().into()), Some(k1));
+    let k3 = h.insert(Node::Xform(m3.to_f32().into()), Some(k2));
+
+    g.update(n1);
+    h.update(k1);
+
+    let eye = Vec3::new(1e7, -4e6, 3e6);
+    let m = (m2 * m3).to_f32();
+    assert_eq!(g.world_rel(n1, &eye), Mat4::from(1.0));
+    assert_eq!(
+        g.world_rel(n3, &eye),
+        Mat4::from(Affine3::from(m2 * m3).to_f32())
+    );
+
+    // `f32` worlds lose the fractional part far from the origin.
+    let w = h.world_rel(k3, &eye);
+    assert!((w[3][0] - m[3][0]).abs() > 0.1 || (w[3][1] - m[3][1]).abs() > 0.1);
+}
+
+#[test]
+fn rebase_bbox() {
+    let bb0 = Bbox::new(Vec3::new(0.5, 0.0, -0.25), Vec3::from(1.0));
+    let t = Mat4::<f64>::translation(1e7 + 0.125, -3e6, 2e5);
+    let origin = Vec3::new(1e7, -3e6 - 2.0, 2e5);
+    let bb = bb0.transform(&t.rebase(&origin));
+    assert_eq!(bb, bb0.displace_by(Vec3::new(0.125, 2.0, 0.0)));
+
+    let t = Affine3::from(t);
+    assert_eq!(bb, bb0.transform(&t.rebase(&origin)));
+
+    let t = Mat4::<f32>::translation(1.0, 2.0, 3.0);
+    let bb = bb0.transform(&t.rebase(&Vec3::from(1.0)));
+    assert_eq!(bb, bb0.displace_by(Vec3::new(0.0, 1.0, 2.0)));
+}
+
+fn bvh_shapes(n: usize, rand: &mut impl FnMut() -> f32) -> Vec<Shape> {
+    (0..n)
+        .map(|i| {
+            let c = Vec3::new(
+                rand() * 200.0 - 100.0,
+                rand() * 20.0,
+                rand() * 200.0 - 100.0,
+            );
+            let e = Vec3::new(rand() + 0.1, rand() * 2.0 + 0.1, rand() + 0.1);
+            match i % 5 {
+                0 => Shape::Bbox(Bbox::new(c, e)),
+                1 => Shape::Sphere(Sphere::new(c, e[0])),
+                2 => Shape::Obb(Obb::new(c, e, Mat3::rotation(rand() * 3.0, &e))),
+                3 => Shape::Capsule(Capsule::new(c, c + e, e[2])),
+                _ if i % 50 == 4 => Shape::None,
+                _ => Shape::Bbox(Bbox::new(c, e * 3.0)),
+            }
+        })
+        .collect()
+}
+
+/// Checks every `Bvh` query against a linear scan of `shapes`.
+fn bvh_check(bvh: &Bvh, shapes: &[Option<Shape>]) {
+    let ids = || (0..shapes.len()).filter_map(|i| shapes[i].map(|x| (i, x)));
+    assert_eq!(bvh.len(), ids().count());
+    assert_eq!(bvh.is_empty(), ids().count() == 0);
+    for (i, x) in ids() {
+        assert_eq!(bvh.shape(NodeId(i)), Some(x));
+    }
+    let sorted = |mut v: Vec<usize>| {
+        v.sort();
+        v
+    };
+
+    let view = Mat4::look_at(
+        &Vec3::new(0.0, 5.0, 0.0),
+        &Vec3::new(-20.0, 10.0, -20.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+    );
+    let proj = Mat4::perspective(1.0, 1.5, 1.0, 80.0);
+    let frustum = Frustum::new(&(proj * view), DepthRange::ZeroToOne);
+    let mut found = vec![];
+    bvh.query_frustum(&frustum, |n, x| {
+        assert_eq!(
+            x,
+            Shape::intersects_frustum(&shapes[n.0].unwrap(), &Mat4::from(1.0), &frustum)
+        );
+        found.push(n.0);
+    });
+    let expected: Vec<_> = ids()
+        .filter(|(_, x)| x.intersects_frustum(&Mat4::from(1.0), &frustum) != Intersection::Outside)
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(sorted(found), expected);
+
+    let sphere = Sphere::new(Vec3::new(10.0, 5.0, -20.0), 30.0);
+    let mut found = vec![];
+    bvh.query_sphere(sphere, |n| found.push(n.0));
+    let expected: Vec<_> = ids()
+        .filter(|(_, x)| match *x {
+            Shape::Bbox(y) => sphere.intersects_bbox(y),
+            Shape::Sphere(y) => sphere.intersects(y),
+            Shape::Obb(y) => sphere.intersects_obb(y),
+            Shape::Capsule(y) => sphere.intersects_capsule(y),
+            Shape::None => false,
+        })
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(sorted(found), expected);
+
+    let bbox = Bbox::new(Vec3::new(-30.0, 0.0, 40.0), Vec3::new(40.0, 5.0, 20.0));
+    let mut found = vec![];
+    bvh.query_bbox(bbox, |n| found.push(n.0));
+    let expected: Vec<_> = ids()
+        .filter(|(_, x)| match *x {
+            Shape::Bbox(y) => bbox.intersects(y),
+            Shape::Sphere(y) => bbox.intersects_sphere(y),
+            Shape::Obb(y) => bbox.intersects_obb(y),
+            Shape::Capsule(y) => bbox.intersects_capsule(y),
+            Shape::None => false,
+        })
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(sorted(found), expected);
+
+    for ray in [
+        Ray::new(Vec3::new(-100.0, 5.0, -100.0), Vec3::new(1.0, 0.0, 1.0)),
+        Ray::new(Vec3::new(0.0, 50.0, 0.0), Vec3::new(0.1, -1.0, 0.05)),
+        Ray::new(Vec3::new(3.0, 10.0, 0.0), Vec3::new(-1.0, 0.0, 0.0)),
+    ] {
+        let hit = |x: &Shape| match *x {
+            Shape::Bbox(y) => ray.intersect_bbox(y),
+            Shape::Sphere(y) => ray.intersect_sphere(y),
+            Shape::Obb(y) => ray.intersect_obb(y),
+            Shape::Capsule(y) => ray.intersect_bbox(Bbox::from(y)),
+            Shape::None => None,
+        };
+        let mut found = vec![];
+        bvh.query_ray(&ray, |n, h| {
+            assert_eq!(Some(h), hit(&shapes[n.0].unwrap()));
+            found.push(n.0);
+        });
+        let expected: Vec<_> = ids()
+            .filter(|(_, x)| hit(x).is_some())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(sorted(found), expected);
+
+        let closest = ids()
+            .filter_map(|(i, x)| hit(&x).map(|h| (i, h.distance)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        match (bvh.closest_hit(&ray), closest) {
+            (Some((n, h)), Some((_, d))) => {
+                assert_eq!(h.distance, d);
+                assert_eq!(hit(&shapes[n.0].unwrap()), Some(h));
+            }
+            (None, None) => (),
+            _ => panic!("closest_hit mismatch"),
+        }
+    }
+}
+
+#[test]
+fn bvh() {
+    let mut rand = crate::rand_gen(7);
+    let mut bvh = Bvh::new();
+    let mut shapes = vec![None; 1000];
+    bvh_check(&bvh, &shapes);
+
+    for (i, x) in bvh_shapes(1000, &mut rand).into_iter().enumerate() {
+        bvh.update(NodeId(i), x);
+        shapes[i] = Some(x);
+    }
+    bvh_check(&bvh, &shapes);
+
+    // Small motion (refit) and teleports (reinsertion).
+    let moved = bvh_shapes(1000, &mut rand);
+    for i in (0..1000).step_by(3) {
+        let x = match (shapes[i].unwrap(), i % 2) {
+            (Shape::Bbox(y), 0) => Shape::Bbox(y.displace_by(Vec3::new(0.1, 0.0, 0.0))),
+            (Shape::Sphere(y), 0) => Shape::Sphere(y.resize_by(0.5)),
+            _ => moved[i],
+        };
+        bvh.update(NodeId(i), x);
+        shapes[i] = Some(x);
+    }
+    bvh_check(&bvh, &shapes);
+
+    for i in (0..1000).step_by(7) {
+        bvh.remove(NodeId(i));
+        shapes[i] = None;
+    }
+    bvh.remove(NodeId(0));
+    bvh.remove(NodeId(5000));
+    bvh_check(&bvh, &shapes);
+
+    bvh.rebuild();
+    bvh_check(&bvh, &shapes);
+
+    for (i, x) in bvh_shapes(100, &mut rand).into_iter().enumerate() {
+        bvh.update(NodeId(i * 7), x);
+        shapes[i * 7] = Some(x);
+    }
+    bvh_check(&bvh, &shapes);
+
+    for (i, x) in shapes.iter_mut().enumerate() {
+        bvh.remove(NodeId(i));
+        *x = None;
+    }
+    bvh_check(&bvh, &shapes);
+    bvh.rebuild();
+    bvh_check(&bvh, &shapes);
+}
+
+#[test]
+fn bvh_graph() {
+    let mut g = Graph::new();
+    let n1 = g.insert(Node::Xform(Mat4::translation(1.0, 0.0, 0.0)), None);
+    let n2 = g.insert(
+        Node::Light(
+            Light::new_white(LightType::Directional, 100.0),
+            Mat4::from(1.0),
+        ),
+        Some(n1),
+    );
+    g.update(n1);
+    assert!(g.bvh().is_empty());
+    assert!(g.bvh().shape(n2).is_none());
+    g.rebuild_bvh();
+    g.remove(n1);
+    assert!(g.bvh().is_empty());
+}
+
+#[test]
+fn bvh_drawables() {
+    crate::init();
+    let mesh = Arc::new(
+        mesh::Builder::new()
+            .set_vertex_count(3)
+            .set_semantic(
+                io::repeat(0),
+                Semantic::Position,
+                DataType::F32x3,
+                false,
+                None,
+            )
+            .unwrap()
+            .push_primitive(Topology::Triangle)
+            .unwrap()
+            .create()
+            .unwrap(),
+    );
+    let sph = Sphere::new(Vec3::default(), 1.0);
+    let cap = Capsule::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.5);
+    let bb = Bbox::new_origin(Vec3::from(1.0));
+
+    let mut g = Graph::new();
+    let n1 = g.insert(Node::Xform(Mat4::translation(10.0, 0.0, 0.0)), None);
+    let n2 = g.insert(
+        Node::Drawable(
+            Drawable::new(Arc::clone(&mesh), Shape::Sphere(sph)),
+            Mat4::from(1.0),
+        ),
+        Some(n1),
+    );
+    let n3 = g.insert(
+        Node::Drawable(
+            Drawable::new(Arc::clone(&mesh), Shape::Capsule(cap)),
+            Mat4::translation(0.0, 5.0, 0.0),
+        ),
+        Some(n1),
+    );
+    let n4 = g.insert(
+        Node::Drawable(
+            Drawable::new(Arc::clone(&mesh), Shape::Bbox(bb)),
+            Mat4::translation(-10.0, 0.0, 0.0),
+        ),
+        None,
+    );
+    assert!(g.bvh().is_empty());
+    assert!(g.bvh().shape(n2).is_none());
+
+    g.update(n1);
+    g.update(n4);
+    assert_eq!(g.bvh().len(), 3);
+    assert!(g.bvh().shape(n1).is_none());
+    let t = Mat4::translation(10.0, 0.0, 0.0);
+    assert_eq!(g.bvh().shape(n2), Some(Shape::Sphere(sph.transform(&t))));
+    let t = Mat4::translation(10.0, 5.0, 0.0);
+    assert_eq!(g.bvh().shape(n3), Some(Shape::Capsule(cap.transform(&t))));
+    let t = Mat4::translation(-10.0, 0.0, 0.0);
+    assert_eq!(g.bvh().shape(n4), Some(Shape::Bbox(bb.transform(&t))));
+
+    let query = |g: &Graph, sph: Sphere, bb: Bbox| {
+        let (mut s, mut b) = (vec![], vec![]);
+        g.bvh().query_sphere(sph, |x| s.push(x.0));
+        g.bvh().query_bbox(bb, |x| b.push(x.0));
+        s.sort();
+        b.sort();
+        (s, b)
+    };
+    let (s, b) = query(
+        &g,
+        Sphere::new(Vec3::new(10.0, 1.5, 0.0), 0.75),
+        Bbox::new(Vec3::new(9.0, 2.5, 0.0), Vec3::from(2.5)),
+    );
+    assert_eq!(s, [n2.0]);
+    assert_eq!(b, [n2.0, n3.0]);
+
+    let ray = Ray::new(Vec3::new(10.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let mut hits = vec![];
+    g.bvh()
+        .query_ray(&ray, |x, hit| hits.push((x.0, hit.distance)));
+    hits.sort_by_key(|x| x.0);
+    assert_eq!(hits, [(n2.0, 4.0), (n3.0, 9.5)]);
+    let (x, hit) = g.bvh().closest_hit(&ray).unwrap();
+    assert_eq!(x.0, n2.0);
+    assert_eq!(hit.distance, 4.0);
+    assert_eq!(hit.normal, Vec3::new(0.0, -1.0, 0.0));
+
+    // Inside the capsule's bounding box, but past its
+    // rounded end.
+    let ray = Ray::new(Vec3::new(11.45, 0.0, 0.45), Vec3::new(0.0, 1.0, 0.0));
+    assert!(g.bvh().closest_hit(&ray).is_none());
+    g.bvh().query_ray(&ray, |_, _| panic!());
+    let ray = Ray::new(Vec3::new(11.2, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    let (x, hit) = g.bvh().closest_hit(&ray).unwrap();
+    assert_eq!(x.0, n3.0);
+    assert!((hit.distance - (5.0 - 0.21f32.sqrt())).abs() <= 1e-5);
+
+    // Moving the parent moves both drawables.
+    *g.local_mut(n1) = Mat4::translation(30.0, 0.0, 0.0);
+    g.update(n1);
+    g.rebuild_bvh();
+    assert_eq!(g.bvh().len(), 3);
+    let t = Mat4::translation(30.0, 0.0, 0.0);
+    assert_eq!(g.bvh().shape(n2), Some(Shape::Sphere(sph.transform(&t))));
+    let t = Mat4::translation(30.0, 5.0, 0.0);
+    assert_eq!(g.bvh().shape(n3), Some(Shape::Capsule(cap.transform(&t))));
+    let (s, b) = query(
+        &g,
+        Sphere::new(Vec3::new(10.0, 1.5, 0.0), 0.75),
+        Bbox::new(Vec3::new(9.0, 2.5, 0.0), Vec3::from(2.5)),
+    );
+    assert!(s.is_empty() && b.is_empty());
+    let (s, b) = query(
+        &g,
+        Sphere::new(Vec3::new(30.0, 1.5, 0.0), 0.75),
+        Bbox::new(Vec3::new(-10.0, 0.0, 0.0), Vec3::from(0.5)),
+    );
+    assert_eq!(s, [n2.0]);
+    assert_eq!(b, [n4.0]);
+    let ray = Ray::new(Vec3::new(30.0, 20.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    let (x, hit) = g.bvh().closest_hit(&ray).unwrap();
+    assert_eq!(x.0, n3.0);
+    assert_eq!(hit.distance, 14.5);
+    let ray = Ray::new(Vec3::new(10.0, -5.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert!(g.bvh().closest_hit(&ray).is_none());
+
+    g.remove(n1);
+    assert_eq!(g.bvh().len(), 1);
+    assert!(g.bvh().shape(n2).is_none());
+    assert!(g.bvh().shape(n3).is_none());
+    assert!(g.bvh().shape(n4).is_some());
+}
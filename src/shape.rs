@@ -1,6 +1,9 @@
 //! Bounding shapes.
 
-use crate::linear::{Mat3, Mat4, Vec3, Vec4};
+use crate::linear::{Mat3, Mat4, Vec2, Vec3, Vec4};
+
+mod convex;
+pub use crate::shape::convex::{closest_points, intersects, penetration, Contact, Hull, Support};
 
 /// Bounding box.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -23,6 +26,26 @@ impl Bbox {
         Self::new(Vec3::default(), half_extent)
     }
 
+    /// Creates the smallest bounding box that contains
+    /// every point in `points`.
+    ///
+    /// NOTE: One must ensure that `points` is not empty.
+    pub fn from_points(points: &[Vec3<f32>]) -> Self {
+        debug_assert!(!points.is_empty());
+        let mut min = Vec3::from(f32::INFINITY);
+        let mut max = Vec3::from(f32::NEG_INFINITY);
+        for p in points {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+        Self {
+            center: (min + max) / 2.0,
+            half_extent: (max - min) / 2.0,
+        }
+    }
+
     /// Displaces the bounding box by offsetting its center.
     pub fn displace_by(self, offset: Vec3<f32>) -> Self {
         Self {
@@ -115,6 +138,17 @@ impl Bbox {
 
         (p - sphere.center).length() < sphere.radius
     }
+
+    /// Checks whether a bounding box intersects an oriented
+    /// bounding box.
+    pub fn intersects_obb(&self, obb: Obb) -> bool {
+        obb.intersects_bbox(*self)
+    }
+
+    /// Checks whether a bounding box intersects a capsule.
+    pub fn intersects_capsule(&self, capsule: Capsule) -> bool {
+        capsule.intersects_bbox(*self)
+    }
 }
 
 impl From<Sphere> for Bbox {
@@ -145,6 +179,25 @@ impl Sphere {
         Self::new(Vec3::default(), radius)
     }
 
+    /// Creates a sphere that contains every point in `points`.
+    ///
+    /// The sphere is first approximated using Ritter's
+    /// algorithm and then refined into the minimal sphere
+    /// using Welzl's algorithm. The approximation is kept if
+    /// the refinement fails due to degenerate input.
+    ///
+    /// NOTE: One must ensure that `points` is not empty.
+    pub fn from_points(points: &[Vec3<f32>]) -> Self {
+        debug_assert!(!points.is_empty());
+        let ritter = ritter_sphere(points);
+        let welzl = welzl_sphere(points);
+        if welzl.radius < ritter.radius && points.iter().all(|p| welzl.encloses(p)) {
+            welzl
+        } else {
+            ritter
+        }
+    }
+
     /// Displaces the sphere by offsetting its center.
     pub fn displace_by(self, offset: Vec3<f32>) -> Self {
         Self {
@@ -161,6 +214,20 @@ impl Sphere {
         }
     }
 
+    /// Transforms the sphere.
+    ///
+    /// The radius is scaled by the largest scale factor
+    /// of `xform`.
+    pub fn transform(self, xform: &Mat4<f32>) -> Self {
+        let ul = Mat3::from(xform);
+        let s = ul[0].length().max(ul[1].length()).max(ul[2].length());
+        let c = xform * Vec4::new(self.center[0], self.center[1], self.center[2], 1.0);
+        Self {
+            center: Vec3::from(c),
+            radius: self.radius * s,
+        }
+    }
+
     /// Returns the center.
     pub fn center(&self) -> Vec3<f32> {
         self.center
@@ -171,6 +238,12 @@ impl Sphere {
         self.radius
     }
 
+    /// Checks whether a sphere contains a point, allowing
+    /// for rounding errors.
+    fn encloses(&self, point: &Vec3<f32>) -> bool {
+        (point - &self.center).length() <= self.radius * (1.0 + 1e-5) + 1e-6
+    }
+
     /// Checks whether a sphere contains a point.
     pub fn contains(&self, point: Vec3<f32>) -> bool {
         (point - self.center).length() < self.radius
@@ -185,6 +258,17 @@ impl Sphere {
     pub fn intersects_bbox(&self, bbox: Bbox) -> bool {
         bbox.intersects_sphere(*self)
     }
+
+    /// Checks whether a sphere intersects an oriented
+    /// bounding box.
+    pub fn intersects_obb(&self, obb: Obb) -> bool {
+        obb.intersects_sphere(*self)
+    }
+
+    /// Checks whether a sphere intersects a capsule.
+    pub fn intersects_capsule(&self, capsule: Capsule) -> bool {
+        capsule.intersects_sphere(*self)
+    }
 }
 
 impl From<Bbox> for Sphere {
@@ -197,6 +281,126 @@ impl From<Bbox> for Sphere {
     }
 }
 
+/// Computes an approximate bounding sphere using
+/// Ritter's algorithm.
+fn ritter_sphere(points: &[Vec3<f32>]) -> Sphere {
+    let farthest = |from: &Vec3<f32>| {
+        points
+            .iter()
+            .copied()
+            .max_by(|a, b| (a - from).length().total_cmp(&(b - from).length()))
+            .unwrap()
+    };
+    let a = farthest(&points[0]);
+    let b = farthest(&a);
+    let mut center = (a + b) / 2.0;
+    let mut radius = (b - a).length() / 2.0;
+    for p in points {
+        let d = (p - &center).length();
+        if d > radius {
+            let r = (radius + d) / 2.0;
+            center += (p - &center) * ((r - radius) / d);
+            radius = r;
+        }
+    }
+    Sphere { center, radius }
+}
+
+/// Computes the minimal bounding sphere using Welzl's
+/// algorithm (move-to-front variant).
+fn welzl_sphere(points: &[Vec3<f32>]) -> Sphere {
+    // NOTE: The algorithm's expected running time relies on
+    // a random order, but mesh data is often sorted.
+    let mut points = points.to_vec();
+    let mut seed = 0x9e37_79b9u32;
+    for i in (1..points.len()).rev() {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        points.swap(i, seed as usize % (i + 1));
+    }
+    let n = points.len();
+    welzl_mtf(&mut points, n, &mut Vec::with_capacity(4))
+}
+
+/// Computes the minimal sphere that contains `points[..n]`
+/// and has `boundary` on its surface.
+fn welzl_mtf(points: &mut [Vec3<f32>], n: usize, boundary: &mut Vec<Vec3<f32>>) -> Sphere {
+    let mut sphere = sphere_from_boundary(boundary);
+    if boundary.len() == 4 {
+        return sphere;
+    }
+    for i in 0..n {
+        let p = points[i];
+        if !sphere.encloses(&p) {
+            boundary.push(p);
+            sphere = welzl_mtf(points, i, boundary);
+            boundary.pop();
+            points[..=i].rotate_right(1);
+        }
+    }
+    sphere
+}
+
+/// Computes the smallest sphere that has every point of
+/// `boundary` (up to four) on its surface.
+fn sphere_from_boundary(boundary: &[Vec3<f32>]) -> Sphere {
+    match *boundary {
+        [] => Sphere {
+            center: Vec3::default(),
+            radius: -1.0,
+        },
+        [a] => Sphere {
+            center: a,
+            radius: 0.0,
+        },
+        [a, b] => Sphere {
+            center: (a + b) / 2.0,
+            radius: (b - a).length() / 2.0,
+        },
+        [a, b, c] => {
+            let (ab, ac) = (b - a, c - a);
+            let n = ab.cross(&ac);
+            let n2 = n.dot(&n);
+            if n2 <= f32::EPSILON * ab.dot(&ab) * ac.dot(&ac) {
+                // Collinear.
+                return [[a, b], [a, c], [b, c]]
+                    .map(|x| sphere_from_boundary(&x))
+                    .into_iter()
+                    .max_by(|x, y| x.radius.total_cmp(&y.radius))
+                    .unwrap();
+            }
+            let off = (n.cross(&ab) * ac.dot(&ac) + ac.cross(&n) * ab.dot(&ab)) / (2.0 * n2);
+            Sphere {
+                center: a + off,
+                radius: off.length(),
+            }
+        }
+        [a, b, c, d] => {
+            let (ab, ac, ad) = (b - a, c - a, d - a);
+            let m = Mat3::new(ab.into(), ac.into(), ad.into()).transpose();
+            let det = ab.dot(&ac.cross(&ad));
+            let scale = ab.length() * ac.length() * ad.length();
+            if det.abs() <= f32::EPSILON * scale {
+                // Coplanar.
+                return [[a, b, c], [a, b, d], [a, c, d], [b, c, d]]
+                    .map(|x| sphere_from_boundary(&x))
+                    .into_iter()
+                    .filter(|x| boundary.iter().all(|p| x.encloses(p)))
+                    .min_by(|x, y| x.radius.total_cmp(&y.radius))
+                    .unwrap_or_else(|| ritter_sphere(boundary));
+            }
+            let rhs = Vec3::new(ab.dot(&ab), ac.dot(&ac), ad.dot(&ad)) / 2.0;
+            let off = m.invert() * rhs;
+            Sphere {
+                center: a + off,
+                radius: off.length(),
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
 /// Infinite plane.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Plane {
@@ -267,6 +471,861 @@ impl From<Vec4<f32>> for Plane {
     }
 }
 
+/// Oriented bounding box.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Obb {
+    center: Vec3<f32>,
+    half_extent: Vec3<f32>,
+    // Orthonormal basis whose columns are
+    // the box's local axes.
+    rotation: Mat3<f32>,
+}
+
+impl Obb {
+    /// Creates a new oriented bounding box.
+    ///
+    /// NOTE: One must ensure that `rotation` is a
+    /// rotation matrix.
+    pub fn new(center: Vec3<f32>, half_extent: Vec3<f32>, rotation: Mat3<f32>) -> Self {
+        Self {
+            center,
+            half_extent,
+            rotation,
+        }
+    }
+
+    /// Transforms the oriented bounding box.
+    ///
+    /// Scale factors are applied to the half extent. If `xform`
+    /// has shear, the result is only an approximation.
+    pub fn transform(self, xform: &Mat4<f32>) -> Self {
+        let ul = Mat3::from(xform) * self.rotation;
+        let mut half_extent = self.half_extent;
+        let mut rotation = ul;
+        for i in 0..3 {
+            let len = ul[i].length();
+            half_extent[i] *= len;
+            rotation[i] = ul[i] / len;
+        }
+        let c = xform * Vec4::new(self.center[0], self.center[1], self.center[2], 1.0);
+        Self {
+            center: Vec3::from(c),
+            half_extent,
+            rotation,
+        }
+    }
+
+    /// Returns the center.
+    pub fn center(&self) -> Vec3<f32> {
+        self.center
+    }
+
+    /// Returns the half extent.
+    pub fn half_extent(&self) -> Vec3<f32> {
+        self.half_extent
+    }
+
+    /// Returns the rotation.
+    pub fn rotation(&self) -> Mat3<f32> {
+        self.rotation
+    }
+
+    /// Converts a point into the box's local space.
+    fn local(&self, point: Vec3<f32>) -> Vec3<f32> {
+        self.rotation.transpose() * (point - self.center)
+    }
+
+    /// Computes the radius of the box's projection onto `axis`.
+    fn project(&self, axis: &Vec3<f32>) -> f32 {
+        (0..3)
+            .map(|i| self.half_extent[i] * self.rotation[i].dot(axis).abs())
+            .sum()
+    }
+
+    /// Checks whether an oriented bounding box contains a point.
+    pub fn contains(&self, point: Vec3<f32>) -> bool {
+        let p = self.local(point);
+        (0..3).all(|i| p[i].abs() <= self.half_extent[i])
+    }
+
+    /// Checks whether an oriented bounding box intersects another.
+    pub fn intersects(&self, other: Obb) -> bool {
+        // Separating axis test.
+        let r = self.rotation.transpose() * other.rotation;
+        let t = self.local(other.center);
+        let (a, b) = (self.half_extent, other.half_extent);
+        // NOTE: The epsilon avoids false negatives when
+        // edges are (nearly) parallel.
+        let mut abs_r = Mat3::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                abs_r[i][j] = r[i][j].abs() + 1e-6;
+            }
+        }
+        // `r[j][i]` is the dot product of `self`'s axis `i`
+        // and `other`'s axis `j`.
+        for i in 0..3 {
+            let rb = b[0] * abs_r[0][i] + b[1] * abs_r[1][i] + b[2] * abs_r[2][i];
+            if t[i].abs() > a[i] + rb {
+                return false;
+            }
+        }
+        for j in 0..3 {
+            let ra = a[0] * abs_r[j][0] + a[1] * abs_r[j][1] + a[2] * abs_r[j][2];
+            let d = t[0] * r[j][0] + t[1] * r[j][1] + t[2] * r[j][2];
+            if d.abs() > ra + b[j] {
+                return false;
+            }
+        }
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = a[i1] * abs_r[j][i2] + a[i2] * abs_r[j][i1];
+                let rb = b[j1] * abs_r[j2][i] + b[j2] * abs_r[j1][i];
+                let d = t[i2] * r[j][i1] - t[i1] * r[j][i2];
+                if d.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Checks whether an oriented bounding box intersects a
+    /// bounding box.
+    pub fn intersects_bbox(&self, bbox: Bbox) -> bool {
+        self.intersects(Obb::from(bbox))
+    }
+
+    /// Checks whether an oriented bounding box intersects
+    /// a sphere.
+    pub fn intersects_sphere(&self, sphere: Sphere) -> bool {
+        let p = self.local(sphere.center);
+        box_dist2(&p, &self.half_extent) < sphere.radius * sphere.radius
+    }
+
+    /// Checks whether an oriented bounding box intersects
+    /// a plane.
+    pub fn intersects_plane(&self, plane: Plane) -> bool {
+        let n = plane.n();
+        let len = n.length();
+        let d = (n.dot(&self.center) + plane.coef[3]) / len;
+        d.abs() <= self.project(&n) / len
+    }
+
+    /// Checks whether an oriented bounding box intersects
+    /// a capsule.
+    pub fn intersects_capsule(&self, capsule: Capsule) -> bool {
+        let a = self.local(capsule.a);
+        let b = self.local(capsule.b);
+        segment_box_dist2(&a, &b, &self.half_extent) < capsule.radius * capsule.radius
+    }
+}
+
+impl From<Bbox> for Obb {
+    /// Converts from a `Bbox` into an axis-aligned `Obb`.
+    fn from(bbox: Bbox) -> Self {
+        Self {
+            center: bbox.center,
+            half_extent: bbox.half_extent,
+            rotation: Mat3::from(1.0),
+        }
+    }
+}
+
+impl From<Obb> for Bbox {
+    /// Converts from an `Obb` into its enclosing `Bbox`.
+    fn from(obb: Obb) -> Self {
+        let mut half_extent = Vec3::default();
+        for i in 0..3 {
+            for j in 0..3 {
+                half_extent[j] += (obb.rotation[i][j] * obb.half_extent[i]).abs();
+            }
+        }
+        Self {
+            center: obb.center,
+            half_extent,
+        }
+    }
+}
+
+impl From<Obb> for Sphere {
+    /// Converts from an `Obb` into its enclosing `Sphere`.
+    fn from(obb: Obb) -> Self {
+        Self {
+            center: obb.center,
+            radius: obb.half_extent.length(),
+        }
+    }
+}
+
+/// Capsule.
+///
+/// This is the set of points within `radius` of the
+/// segment `[a, b]`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Capsule {
+    a: Vec3<f32>,
+    b: Vec3<f32>,
+    radius: f32,
+}
+
+impl Capsule {
+    /// Creates a new capsule.
+    pub fn new(a: Vec3<f32>, b: Vec3<f32>, radius: f32) -> Self {
+        Self { a, b, radius }
+    }
+
+    /// Transforms the capsule.
+    ///
+    /// The radius is scaled by the largest scale factor
+    /// of `xform`.
+    pub fn transform(self, xform: &Mat4<f32>) -> Self {
+        let ul = Mat3::from(xform);
+        let s = ul[0].length().max(ul[1].length()).max(ul[2].length());
+        let f = |p: Vec3<f32>| Vec3::from(xform * Vec4::new(p[0], p[1], p[2], 1.0));
+        Self {
+            a: f(self.a),
+            b: f(self.b),
+            radius: self.radius * s,
+        }
+    }
+
+    /// Returns the segment's endpoints.
+    pub fn segment(&self) -> (Vec3<f32>, Vec3<f32>) {
+        (self.a, self.b)
+    }
+
+    /// Returns the radius.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Checks whether a capsule contains a point.
+    pub fn contains(&self, point: Vec3<f32>) -> bool {
+        let p = closest_on_segment(&point, &self.a, &self.b);
+        (point - p).length() < self.radius
+    }
+
+    /// Checks whether a capsule intersects another.
+    pub fn intersects(&self, other: Capsule) -> bool {
+        let r = self.radius + other.radius;
+        segment_dist2(&self.a, &self.b, &other.a, &other.b) < r * r
+    }
+
+    /// Checks whether a capsule intersects a bounding box.
+    pub fn intersects_bbox(&self, bbox: Bbox) -> bool {
+        Obb::from(bbox).intersects_capsule(*self)
+    }
+
+    /// Checks whether a capsule intersects a sphere.
+    pub fn intersects_sphere(&self, sphere: Sphere) -> bool {
+        let p = closest_on_segment(&sphere.center, &self.a, &self.b);
+        (sphere.center - p).length() < self.radius + sphere.radius
+    }
+
+    /// Checks whether a capsule intersects a plane.
+    pub fn intersects_plane(&self, plane: Plane) -> bool {
+        let (da, db) = (plane.signed_distance(self.a), plane.signed_distance(self.b));
+        da * db <= 0.0 || da.abs().min(db.abs()) <= self.radius
+    }
+
+    /// Checks whether a capsule intersects an oriented
+    /// bounding box.
+    pub fn intersects_obb(&self, obb: Obb) -> bool {
+        obb.intersects_capsule(*self)
+    }
+}
+
+impl From<Capsule> for Bbox {
+    /// Converts from a `Capsule` into its enclosing `Bbox`.
+    fn from(capsule: Capsule) -> Self {
+        let mut half_extent = (capsule.b - capsule.a) / 2.0;
+        for i in 0..3 {
+            half_extent[i] = half_extent[i].abs() + capsule.radius;
+        }
+        Self {
+            center: (capsule.a + capsule.b) / 2.0,
+            half_extent,
+        }
+    }
+}
+
+impl From<Capsule> for Sphere {
+    /// Converts from a `Capsule` into its enclosing `Sphere`.
+    fn from(capsule: Capsule) -> Self {
+        Self {
+            center: (capsule.a + capsule.b) / 2.0,
+            radius: (capsule.b - capsule.a).length() / 2.0 + capsule.radius,
+        }
+    }
+}
+
+/// Computes the point on the segment `[a, b]` that lies
+/// closest to `point`.
+fn closest_on_segment(point: &Vec3<f32>, a: &Vec3<f32>, b: &Vec3<f32>) -> Vec3<f32> {
+    let ab = b - a;
+    let len2 = ab.dot(&ab);
+    if len2 == 0.0 {
+        return *a;
+    }
+    let t = ((point - a).dot(&ab) / len2).clamp(0.0, 1.0);
+    a + &(ab * t)
+}
+
+/// Computes the squared distance between the segments
+/// `[p1, q1]` and `[p2, q2]`.
+fn segment_dist2(p1: &Vec3<f32>, q1: &Vec3<f32>, p2: &Vec3<f32>, q2: &Vec3<f32>) -> f32 {
+    let (d1, d2, r) = (q1 - p1, q2 - p2, p1 - p2);
+    let (a, e, f) = (d1.dot(&d1), d2.dot(&d2), d2.dot(&r));
+    let (s, t) = if a == 0.0 && e == 0.0 {
+        (0.0, 0.0)
+    } else if a == 0.0 {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e == 0.0 {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let den = a * e - b * b;
+            let s = if den > 0.0 {
+                ((b * f - c * e) / den).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    let v = (p1 + &(d1 * s)) - (p2 + &(d2 * t));
+    v.dot(&v)
+}
+
+/// Computes the squared distance between a point and an
+/// origin-centered box.
+fn box_dist2(point: &Vec3<f32>, half_extent: &Vec3<f32>) -> f32 {
+    (0..3)
+        .map(|i| {
+            let d = (point[i].abs() - half_extent[i]).max(0.0);
+            d * d
+        })
+        .sum()
+}
+
+/// Computes the squared distance between the segment `[a, b]`
+/// and an origin-centered box.
+fn segment_box_dist2(a: &Vec3<f32>, b: &Vec3<f32>, half_extent: &Vec3<f32>) -> f32 {
+    // NOTE: The squared distance along the segment is a
+    // piecewise quadratic function of `t`, whose pieces are
+    // delimited by the points where the segment crosses the
+    // planes of the box's faces. Each piece is minimized in
+    // closed form.
+    let ab = b - a;
+    let mut ts = [0f32; 8];
+    let mut n = 0;
+    for i in 0..3 {
+        if ab[i] == 0.0 {
+            continue;
+        }
+        for x in [-half_extent[i], half_extent[i]] {
+            let t = (x - a[i]) / ab[i];
+            if t > 0.0 && t < 1.0 {
+                ts[n] = t;
+                n += 1;
+            }
+        }
+    }
+    ts[n] = 0.0;
+    ts[n + 1] = 1.0;
+    let ts = &mut ts[..n + 2];
+    ts.sort_unstable_by(f32::total_cmp);
+
+    let mut dist2 = f32::INFINITY;
+    for w in ts.windows(2) {
+        // Within a piece, each axis is either inside the slab
+        // (no contribution) or past one of its faces.
+        let mid = a + &(ab * ((w[0] + w[1]) * 0.5));
+        let (mut num, mut den) = (0.0, 0.0);
+        for i in 0..3 {
+            if mid[i] > half_extent[i] {
+                num -= ab[i] * (a[i] - half_extent[i]);
+                den += ab[i] * ab[i];
+            } else if mid[i] < -half_extent[i] {
+                num -= ab[i] * (a[i] + half_extent[i]);
+                den += ab[i] * ab[i];
+            }
+        }
+        let t = if den > 0.0 {
+            (num / den).clamp(w[0], w[1])
+        } else {
+            w[0]
+        };
+        dist2 = dist2.min(box_dist2(&(a + &(ab * t)), half_extent));
+    }
+    dist2
+}
+
+/// Ray.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Ray {
+    origin: Vec3<f32>,
+    dir: Vec3<f32>,
+}
+
+/// Intersection of a [`Ray`] and a shape.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Hit {
+    /// Distance from the ray's origin.
+    pub distance: f32,
+    /// Normalized surface normal at the hit point.
+    pub normal: Vec3<f32>,
+}
+
+impl Ray {
+    /// Creates a new ray.
+    ///
+    /// This function normalizes `dir`.
+    pub fn new(origin: Vec3<f32>, dir: Vec3<f32>) -> Self {
+        Self {
+            origin,
+            dir: dir.normalize(),
+        }
+    }
+
+    /// Creates a new ray that passes through a given point in
+    /// normalized device coordinates.
+    ///
+    /// The ray starts at the near plane and points away from it.
+    pub fn unproject(ndc: Vec2<f32>, inv_view_proj: &Mat4<f32>, depth: DepthRange) -> Self {
+        // NOTE: The far plane may be at infinity, so the
+        // second point lies halfway through the depth range.
+        let (near, mid) = match depth {
+            DepthRange::ZeroToOne => (0.0, 0.5),
+            DepthRange::OneToZero => (1.0, 0.5),
+            DepthRange::NegOneToOne => (-1.0, 0.0),
+        };
+        let unproj = |z| {
+            let p = inv_view_proj * Vec4::new(ndc[0], ndc[1], z, 1.0);
+            Vec3::from(p) / p[3]
+        };
+        let origin = unproj(near);
+        Self::new(origin, unproj(mid) - origin)
+    }
+
+    /// Returns the origin.
+    pub fn origin(&self) -> Vec3<f32> {
+        self.origin
+    }
+
+    /// Returns the normalized direction.
+    pub fn dir(&self) -> Vec3<f32> {
+        self.dir
+    }
+
+    /// Computes the point at a given distance from the origin.
+    pub fn at(&self, distance: f32) -> Vec3<f32> {
+        self.origin + self.dir * distance
+    }
+
+    /// Computes the intersection with a bounding box.
+    ///
+    /// If the origin is inside the box, the hit is where the
+    /// ray leaves it.
+    pub fn intersect_bbox(&self, bbox: Bbox) -> Option<Hit> {
+        let mut near = (f32::NEG_INFINITY, 0);
+        let mut far = (f32::INFINITY, 0);
+        for i in 0..3 {
+            let min = bbox.center[i] - bbox.half_extent[i];
+            let max = bbox.center[i] + bbox.half_extent[i];
+            if self.dir[i] == 0.0 {
+                if self.origin[i] < min || self.origin[i] > max {
+                    return None;
+                }
+                continue;
+            }
+            let inv = 1.0 / self.dir[i];
+            let (t0, t1) = ((min - self.origin[i]) * inv, (max - self.origin[i]) * inv);
+            let (t0, t1) = if t0 <= t1 { (t0, t1) } else { (t1, t0) };
+            if t0 > near.0 {
+                near = (t0, i);
+            }
+            if t1 < far.0 {
+                far = (t1, i);
+            }
+        }
+        if near.0 > far.0 || far.0 < 0.0 {
+            return None;
+        }
+        let ((distance, i), sign) = if near.0 >= 0.0 {
+            (near, -1.0)
+        } else {
+            (far, 1.0)
+        };
+        let mut normal = Vec3::default();
+        normal[i] = if self.dir[i] < 0.0 { -sign } else { sign };
+        Some(Hit { distance, normal })
+    }
+
+    /// Computes the intersection with an oriented bounding box.
+    ///
+    /// If the origin is inside the box, the hit is where the
+    /// ray leaves it.
+    pub fn intersect_obb(&self, obb: Obb) -> Option<Hit> {
+        let inv = obb.rotation.transpose();
+        let local = Self {
+            origin: inv * (self.origin - obb.center),
+            dir: inv * self.dir,
+        };
+        local
+            .intersect_bbox(Bbox::new_origin(obb.half_extent))
+            .map(|hit| Hit {
+                distance: hit.distance,
+                normal: obb.rotation * hit.normal,
+            })
+    }
+
+    /// Computes the intersection with a sphere.
+    ///
+    /// If the origin is inside the sphere, the hit is where the
+    /// ray leaves it.
+    pub fn intersect_sphere(&self, sphere: Sphere) -> Option<Hit> {
+        let oc = self.origin - sphere.center;
+        let b = oc.dot(&self.dir);
+        let c = oc.dot(&oc) - sphere.radius * sphere.radius;
+        let disc = b * b - c;
+        if disc < 0.0 {
+            return None;
+        }
+        let sq = disc.sqrt();
+        let distance = if -b - sq >= 0.0 { -b - sq } else { -b + sq };
+        if distance < 0.0 {
+            return None;
+        }
+        let normal = (self.at(distance) - sphere.center) / sphere.radius;
+        Some(Hit { distance, normal })
+    }
+
+    /// Computes the intersection with a capsule.
+    ///
+    /// If the origin is inside the capsule, the hit is where
+    /// the ray leaves it.
+    pub fn intersect_capsule(&self, capsule: Capsule) -> Option<Hit> {
+        // NOTE: The capsule is convex, so the intervals along
+        // the ray that lie in either cap or in the cylinder
+        // merge into a single interval.
+        let r2 = capsule.radius * capsule.radius;
+        let cap = |c: Vec3<f32>| {
+            let oc = self.origin - c;
+            let b = oc.dot(&self.dir);
+            let disc = b * b - (oc.dot(&oc) - r2);
+            (disc >= 0.0).then(|| (-b - disc.sqrt(), -b + disc.sqrt()))
+        };
+        let cylinder = || {
+            let d = capsule.b - capsule.a;
+            let dd = d.dot(&d);
+            if dd == 0.0 {
+                return None;
+            }
+            let m = self.origin - capsule.a;
+            let (md, nd) = (m.dot(&d), self.dir.dot(&d));
+            let w = m - d * (md / dd);
+            let u = self.dir - d * (nd / dd);
+            let (a, b, c) = (u.dot(&u), w.dot(&u), w.dot(&w) - r2);
+            let (mut t0, mut t1) = if a == 0.0 {
+                if c > 0.0 {
+                    return None;
+                }
+                (f32::NEG_INFINITY, f32::INFINITY)
+            } else {
+                let disc = b * b - a * c;
+                if disc < 0.0 {
+                    return None;
+                }
+                let sq = disc.sqrt();
+                ((-b - sq) / a, (-b + sq) / a)
+            };
+            if nd == 0.0 {
+                if md < 0.0 || md > dd {
+                    return None;
+                }
+            } else {
+                let (s0, s1) = (-md / nd, (dd - md) / nd);
+                t0 = t0.max(s0.min(s1));
+                t1 = t1.min(s0.max(s1));
+            }
+            (t0 <= t1).then_some((t0, t1))
+        };
+        let (t0, t1) = [cap(capsule.a), cap(capsule.b), cylinder()]
+            .into_iter()
+            .flatten()
+            .reduce(|x, y| (x.0.min(y.0), x.1.max(y.1)))?;
+        if t1 < 0.0 {
+            return None;
+        }
+        let distance = if t0 >= 0.0 { t0 } else { t1 };
+        let p = self.at(distance);
+        let normal = (p - closest_on_segment(&p, &capsule.a, &capsule.b)) / capsule.radius;
+        Some(Hit { distance, normal })
+    }
+
+    /// Computes the intersection with a plane.
+    ///
+    /// The normal of the hit faces the ray's origin.
+    pub fn intersect_plane(&self, plane: Plane) -> Option<Hit> {
+        let n = plane.n();
+        let den = n.dot(&self.dir);
+        if den == 0.0 {
+            return None;
+        }
+        let distance = -(n.dot(&self.origin) + plane.coef[3]) / den;
+        if distance < 0.0 {
+            return None;
+        }
+        let normal = if den < 0.0 { n } else { -n };
+        Some(Hit {
+            distance,
+            normal: normal.normalize(),
+        })
+    }
+
+    /// Computes the intersection with a triangle.
+    ///
+    /// Both sides of the triangle are considered. The normal of
+    /// the hit is the geometric normal given by the winding of
+    /// `[a, b, c]` (counter-clockwise).
+    pub fn intersect_triangle(&self, a: Vec3<f32>, b: Vec3<f32>, c: Vec3<f32>) -> Option<Hit> {
+        // Möller-Trumbore.
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.dir.cross(&e2);
+        let det = e1.dot(&p);
+        if det.abs() <= f32::EPSILON * e1.length() * e2.length() {
+            return None;
+        }
+        let inv = 1.0 / det;
+        let t = self.origin - a;
+        let u = t.dot(&p) * inv;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = t.cross(&e1);
+        let v = self.dir.dot(&q) * inv;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let distance = e2.dot(&q) * inv;
+        if distance < 0.0 {
+            return None;
+        }
+        Some(Hit {
+            distance,
+            normal: e1.cross(&e2).normalize(),
+        })
+    }
+}
+
+/// Depth range of a projection's clip space.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum DepthRange {
+    /// Near plane at zero and far plane at one.
+    ZeroToOne,
+    /// Near plane at one and far plane at zero (i.e., reversed-Z).
+    OneToZero,
+    /// Near plane at minus one and far plane at one.
+    ///
+    /// This is the range produced by `Mat4::inf_perspective`.
+    NegOneToOne,
+}
+
+/// Result of a containment test.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Intersection {
+    /// Completely outside.
+    Outside,
+    /// Partially inside.
+    Intersecting,
+    /// Completely inside.
+    Inside,
+}
+
+/// View frustum.
+///
+/// Planes are normalized and point inwards.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Frustum {
+    // Left, right, bottom, top, near and far,
+    // in this order.
+    planes: [Plane; 6],
+    // The far plane is omitted if it is at infinity.
+    len: usize,
+}
+
+impl Frustum {
+    /// Creates a new frustum from a view-projection matrix.
+    ///
+    /// Infinite projections (e.g., `Mat4::inf_perspective`) have
+    /// no far plane.
+    pub fn new(view_proj: &Mat4<f32>, depth: DepthRange) -> Self {
+        let m = view_proj.transpose();
+        let (near, far) = match depth {
+            DepthRange::ZeroToOne => (m[2], m[3] - m[2]),
+            DepthRange::OneToZero => (m[3] - m[2], m[2]),
+            DepthRange::NegOneToOne => (m[3] + m[2], m[3] - m[2]),
+        };
+        let coef = [
+            m[3] + m[0],
+            m[3] - m[0],
+            m[3] + m[1],
+            m[3] - m[1],
+            near,
+            far,
+        ];
+        let mut planes = [Plane::from(Vec4::default()); 6];
+        let mut len = 0;
+        for c in coef {
+            let n = Vec3::from(c).length();
+            // NOTE: A plane at infinity has a null normal.
+            if n > f32::EPSILON * c[3].abs() {
+                planes[len] = Plane::from(c / n);
+                len += 1;
+            }
+        }
+        Self { planes, len }
+    }
+
+    /// Returns the frustum's planes.
+    ///
+    /// The order is left, right, bottom, top, near and (unless
+    /// the frustum is infinite) far.
+    pub fn planes(&self) -> &[Plane] {
+        &self.planes[..self.len]
+    }
+
+    /// Returns whether the far plane is at infinity.
+    pub fn is_infinite(&self) -> bool {
+        self.len < 6
+    }
+
+    /// Computes the frustum's corners, or `None` if the frustum
+    /// is infinite.
+    ///
+    /// The corner at index `i` lies on the right plane if bit 0
+    /// of `i` is set (left plane otherwise), on the top plane if
+    /// bit 1 is set (bottom plane otherwise) and on the far plane
+    /// if bit 2 is set (near plane otherwise).
+    pub fn corners(&self) -> Option<[Vec3<f32>; 8]> {
+        if self.is_infinite() {
+            return None;
+        }
+        let mut corners = [Vec3::default(); 8];
+        for (i, x) in corners.iter_mut().enumerate() {
+            let a = &self.planes[i & 1];
+            let b = &self.planes[2 + (i >> 1 & 1)];
+            let c = &self.planes[4 + (i >> 2 & 1)];
+            let (na, nb, nc) = (a.n(), b.n(), c.n());
+            let (bc, ca, ab) = (nb.cross(&nc), nc.cross(&na), na.cross(&nb));
+            *x = -(bc * a.coef[3] + ca * b.coef[3] + ab * c.coef[3]) / na.dot(&bc);
+        }
+        Some(corners)
+    }
+
+    /// Checks whether a frustum contains a point.
+    pub fn contains(&self, point: Vec3<f32>) -> bool {
+        self.planes()
+            .iter()
+            .all(|x| x.n().dot(&point) + x.coef[3] >= 0.0)
+    }
+
+    /// Checks whether a frustum intersects a bounding box.
+    ///
+    /// This test is conservative: boxes near the frustum's edges
+    /// may be reported as intersecting while being outside.
+    pub fn intersects_bbox(&self, bbox: Bbox) -> Intersection {
+        let mut res = Intersection::Inside;
+        for i in self.planes() {
+            let n = i.n();
+            let r = n[0].abs() * bbox.half_extent[0]
+                + n[1].abs() * bbox.half_extent[1]
+                + n[2].abs() * bbox.half_extent[2];
+            let d = n.dot(&bbox.center) + i.coef[3];
+            if d < -r {
+                return Intersection::Outside;
+            }
+            if d < r {
+                res = Intersection::Intersecting;
+            }
+        }
+        res
+    }
+
+    /// Checks whether a frustum intersects an oriented
+    /// bounding box.
+    ///
+    /// This test is conservative: boxes near the frustum's edges
+    /// may be reported as intersecting while being outside.
+    pub fn intersects_obb(&self, obb: Obb) -> Intersection {
+        let mut res = Intersection::Inside;
+        for i in self.planes() {
+            let n = i.n();
+            let r = obb.project(&n);
+            let d = n.dot(&obb.center) + i.coef[3];
+            if d < -r {
+                return Intersection::Outside;
+            }
+            if d < r {
+                res = Intersection::Intersecting;
+            }
+        }
+        res
+    }
+
+    /// Checks whether a frustum intersects a capsule.
+    ///
+    /// This test is conservative: capsules near the frustum's
+    /// edges may be reported as intersecting while being outside.
+    pub fn intersects_capsule(&self, capsule: Capsule) -> Intersection {
+        let mut res = Intersection::Inside;
+        for i in self.planes() {
+            let n = i.n();
+            let da = n.dot(&capsule.a) + i.coef[3];
+            let db = n.dot(&capsule.b) + i.coef[3];
+            if da.max(db) < -capsule.radius {
+                return Intersection::Outside;
+            }
+            if da.min(db) < capsule.radius {
+                res = Intersection::Intersecting;
+            }
+        }
+        res
+    }
+
+    /// Checks whether a frustum intersects a sphere.
+    ///
+    /// This test is conservative: spheres near the frustum's
+    /// edges may be reported as intersecting while being outside.
+    pub fn intersects_sphere(&self, sphere: Sphere) -> Intersection {
+        let mut res = Intersection::Inside;
+        for i in self.planes() {
+            let d = i.n().dot(&sphere.center) + i.coef[3];
+            if d < -sphere.radius {
+                return Intersection::Outside;
+            }
+            if d < sphere.radius {
+                res = Intersection::Intersecting;
+            }
+        }
+        res
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -570,6 +1629,28 @@ mod tests {
         assert!(!sph.contains(Vec3::new(0.0, -0.3333, 0.25)));
     }
 
+    #[test]
+    fn sphere_transform() {
+        let sph0 = Sphere::new(Vec3::new(1.0, -2.0, 0.5), 2.0);
+        assert_eq!(sph0.transform(&Mat4::from(1.0)), sph0);
+
+        let t = Mat4::translation(-1.0, 3.0, 0.25);
+        assert_eq!(
+            sph0.transform(&t),
+            sph0.displace_by(Vec3::new(-1.0, 3.0, 0.25))
+        );
+
+        let s = Mat4::scale(0.5, -3.0, 2.0);
+        let sph = sph0.transform(&(t * s));
+        assert_eq!(sph.center(), Vec3::new(-0.5, 9.0, 1.25));
+        assert_eq!(sph.radius(), 6.0);
+
+        let r = Mat4::rotation_z(std::f32::consts::FRAC_PI_2);
+        let sph = sph0.transform(&r);
+        assert!((sph.center() - Vec3::new(2.0, 1.0, 0.5)).length() <= 1e-6);
+        assert!((sph.radius() - 2.0).abs() <= 1e-6);
+    }
+
     #[test]
     fn plane_contains() {
         let pln = Plane::new(0.0, 1.0, 0.0, 0.0);
@@ -873,4 +1954,714 @@ mod tests {
         assert_eq!(bb.center()[1], sph.center()[1]);
         assert_eq!(bb.center()[2], sph.center()[2]);
     }
+
+    #[test]
+    fn obb() {
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let obb = Obb::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(2.0, 1.0, 0.5), rot);
+        assert_eq!(obb.center(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(obb.half_extent(), Vec3::new(2.0, 1.0, 0.5));
+        assert_eq!(obb.rotation(), rot);
+
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(obb.contains(Vec3::new(1.0, 2.0, 3.0)));
+        assert!(obb.contains(Vec3::new(1.0 + 1.9 * s, 2.0 + 1.9 * s, 3.4)));
+        assert!(!obb.contains(Vec3::new(1.0 + 2.1 * s, 2.0 + 2.1 * s, 3.0)));
+        assert!(!obb.contains(Vec3::new(1.0 + 1.1 * s, 2.0 - 1.1 * s, 3.0)));
+        assert!(!obb.contains(Vec3::new(1.0, 2.0, 3.6)));
+    }
+
+    #[test]
+    fn obb_transform() {
+        let bb = Bbox::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 2.0, 3.0));
+        let m = Mat4::translation(0.0, 5.0, 0.0)
+            * Mat4::rotation_z(std::f32::consts::FRAC_PI_2)
+            * Mat4::scale(2.0, 1.0, 1.0);
+        let obb = Obb::from(bb).transform(&m);
+        assert!((obb.center() - Vec3::new(0.0, 7.0, 0.0)).length() <= 1e-6);
+        assert!((obb.half_extent() - Vec3::new(2.0, 2.0, 3.0)).length() <= 1e-6);
+        assert!((obb.rotation()[0] - Vec3::new(0.0, 1.0, 0.0)).length() <= 1e-6);
+        assert!((obb.rotation()[1] - Vec3::new(-1.0, 0.0, 0.0)).length() <= 1e-6);
+
+        // Agrees with the `Bbox` transform.
+        let bb = bb.transform(&m);
+        let from = Bbox::from(obb);
+        assert!((from.center() - bb.center()).length() <= 1e-6);
+        assert!((from.half_extent() - bb.half_extent()).length() <= 1e-6);
+    }
+
+    #[test]
+    fn obb_intersects() {
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let obb = Obb::new(Vec3::from(0.0), Vec3::new(2.0, 0.25, 0.25), rot);
+
+        // `Obb`.
+        assert!(obb.intersects(obb));
+        let other = Obb::new(Vec3::new(1.0, 1.0, 0.0), Vec3::from(0.25), Mat3::from(1.0));
+        assert!(obb.intersects(other));
+        assert!(other.intersects(obb));
+        let other = Obb::new(Vec3::new(1.0, -1.0, 0.0), Vec3::from(0.25), Mat3::from(1.0));
+        assert!(!obb.intersects(other));
+        assert!(!other.intersects(obb));
+        let rot2 = Mat3::rotation(-std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let other = Obb::new(Vec3::from(0.0), Vec3::new(2.0, 0.25, 0.25), rot2);
+        assert!(obb.intersects(other));
+        let other = Obb::new(Vec3::new(0.0, 0.0, 0.6), Vec3::new(2.0, 0.25, 0.25), rot2);
+        assert!(!obb.intersects(other));
+        // Separated only by an edge-edge axis.
+        let rot_x = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(1.0, 0.0, 0.0));
+        let rot_y = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 1.0, 0.0));
+        let a = Obb::new(Vec3::from(0.0), Vec3::from(1.0), rot_x);
+        let b = Obb::new(
+            Vec3::new(0.0, 0.0, 2.0 * 2f32.sqrt() + 0.01),
+            Vec3::from(1.0),
+            rot_y,
+        );
+        assert!(!a.intersects(b));
+        let b = Obb::new(
+            Vec3::new(0.0, 0.0, 2.0 * 2f32.sqrt() - 0.01),
+            Vec3::from(1.0),
+            rot_y,
+        );
+        assert!(a.intersects(b));
+
+        // `Bbox`.
+        let bb = Bbox::new(Vec3::new(1.0, 1.0, 0.0), Vec3::from(0.25));
+        assert!(obb.intersects_bbox(bb));
+        assert!(bb.intersects_obb(obb));
+        let bb = Bbox::new(Vec3::new(1.0, -1.0, 0.0), Vec3::from(0.25));
+        assert!(!obb.intersects_bbox(bb));
+        assert!(!bb.intersects_obb(obb));
+        // Overlaps the enclosing `Bbox` only.
+        assert!(Bbox::from(obb).intersects(bb));
+
+        // `Sphere`.
+        let sph = Sphere::new(Vec3::new(-1.0, -1.0, 0.0), 0.5);
+        assert!(obb.intersects_sphere(sph));
+        assert!(sph.intersects_obb(obb));
+        let sph = Sphere::new(Vec3::new(-1.0, 1.0, 0.0), 1.0);
+        assert!(!obb.intersects_sphere(sph));
+        assert!(!sph.intersects_obb(obb));
+        assert!(Sphere::from(obb).intersects(sph));
+
+        // `Plane`.
+        let pl = Plane::new_norm(Vec3::new(1.0, -1.0, 0.0), Vec3::new(0.3, 0.0, 0.0));
+        assert!(obb.intersects_plane(pl));
+        let pl = Plane::new_unnorm(Vec3::new(2.0, -2.0, 0.0), Vec3::new(0.4, 0.0, 0.0));
+        assert!(!obb.intersects_plane(pl));
+        let pl = Plane::new_norm(Vec3::new(1.0, 1.0, 0.0), Vec3::new(2.7, 0.0, 0.0));
+        assert!(obb.intersects_plane(pl));
+        let pl = Plane::new_norm(Vec3::new(1.0, 1.0, 0.0), Vec3::new(2.9, 0.0, 0.0));
+        assert!(!obb.intersects_plane(pl));
+    }
+
+    #[test]
+    fn capsule() {
+        let cap = Capsule::new(Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0), 0.5);
+        assert_eq!(
+            cap.segment(),
+            (Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+        );
+        assert_eq!(cap.radius(), 0.5);
+        assert!(cap.contains(Vec3::from(0.0)));
+        assert!(cap.contains(Vec3::new(0.4, 0.9, 0.0)));
+        assert!(cap.contains(Vec3::new(0.0, 1.4, 0.0)));
+        assert!(!cap.contains(Vec3::new(0.0, 1.6, 0.0)));
+        assert!(!cap.contains(Vec3::new(0.4, 1.4, 0.0)));
+        assert!(!cap.contains(Vec3::new(0.0, 0.0, 0.6)));
+
+        let m = Mat4::translation(1.0, 0.0, 0.0)
+            * Mat4::rotation_x(std::f32::consts::FRAC_PI_2)
+            * Mat4::scale(1.0, 1.0, 3.0);
+        let cap = cap.transform(&m);
+        let (a, b) = cap.segment();
+        assert!((a - Vec3::new(1.0, 0.0, -1.0)).length() <= 1e-6);
+        assert!((b - Vec3::new(1.0, 0.0, 1.0)).length() <= 1e-6);
+        assert_eq!(cap.radius(), 1.5);
+
+        let bb = Bbox::from(cap);
+        assert_eq!(bb.center(), Vec3::new(1.0, 0.0, 0.0));
+        assert!((bb.half_extent() - Vec3::new(1.5, 1.5, 2.5)).length() <= 1e-6);
+        let sph = Sphere::from(cap);
+        assert_eq!(sph.center(), Vec3::new(1.0, 0.0, 0.0));
+        assert!((sph.radius() - 2.5).abs() <= 1e-6);
+    }
+
+    #[test]
+    fn capsule_intersects() {
+        let cap = Capsule::new(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.5);
+
+        // `Capsule`.
+        assert!(cap.intersects(cap));
+        let other = Capsule::new(Vec3::new(0.0, 0.9, -1.0), Vec3::new(0.0, 0.9, 1.0), 0.5);
+        assert!(cap.intersects(other));
+        assert!(other.intersects(cap));
+        let other = Capsule::new(Vec3::new(0.0, 1.1, -1.0), Vec3::new(0.0, 1.1, 1.0), 0.5);
+        assert!(!cap.intersects(other));
+        let other = Capsule::new(Vec3::new(1.9, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), 0.5);
+        assert!(cap.intersects(other));
+        let other = Capsule::new(Vec3::new(2.1, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), 0.5);
+        assert!(!cap.intersects(other));
+        // Parallel segments.
+        let other = Capsule::new(Vec3::new(-3.0, 0.9, 0.0), Vec3::new(3.0, 0.9, 0.0), 0.5);
+        assert!(cap.intersects(other));
+        let other = Capsule::new(Vec3::new(-3.0, 1.1, 0.0), Vec3::new(3.0, 1.1, 0.0), 0.5);
+        assert!(!cap.intersects(other));
+        // Degenerate segments.
+        let other = Capsule::new(Vec3::new(0.5, 0.9, 0.0), Vec3::new(0.5, 0.9, 0.0), 0.5);
+        assert!(cap.intersects(other));
+        assert!(other.intersects(cap));
+
+        // `Sphere`.
+        let sph = Sphere::new(Vec3::new(1.5, 0.5, 0.0), 0.3);
+        assert!(cap.intersects_sphere(sph));
+        assert!(sph.intersects_capsule(cap));
+        let sph = Sphere::new(Vec3::new(1.5, 0.5, 0.0), 0.2);
+        assert!(!cap.intersects_sphere(sph));
+        assert!(!sph.intersects_capsule(cap));
+
+        // `Bbox`.
+        let bb = Bbox::new(Vec3::new(0.0, 0.0, 0.9), Vec3::from(0.5));
+        assert!(cap.intersects_bbox(bb));
+        assert!(bb.intersects_capsule(cap));
+        let bb = Bbox::new(Vec3::new(0.0, 0.0, 1.1), Vec3::from(0.5));
+        assert!(!cap.intersects_bbox(bb));
+        assert!(!bb.intersects_capsule(cap));
+        // Near a corner.
+        let bb = Bbox::new(Vec3::new(1.75, 0.75, 0.0), Vec3::from(0.25));
+        assert!(!cap.intersects_bbox(bb));
+        assert!(Bbox::from(cap).intersects(bb));
+        // Crossing without touching the endpoints.
+        let cap2 = Capsule::new(Vec3::new(-5.0, 0.0, 0.0), Vec3::new(5.0, 0.0, 0.0), 0.1);
+        assert!(cap2.intersects_bbox(Bbox::new(Vec3::from(0.0), Vec3::from(0.5))));
+
+        // `Obb`.
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let obb = Obb::new(Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.8, 0.1, 0.1), rot);
+        assert!(cap.intersects_obb(obb));
+        assert!(obb.intersects_capsule(cap));
+        let obb = Obb::new(Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.1, 0.5, 0.1), rot);
+        assert!(!cap.intersects_obb(obb));
+        assert!(!obb.intersects_capsule(cap));
+
+        // `Plane`.
+        let pl = Plane::new_norm(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.4, 0.0));
+        assert!(cap.intersects_plane(pl));
+        let pl = Plane::new_norm(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.6, 0.0));
+        assert!(!cap.intersects_plane(pl));
+        let pl = Plane::new_unnorm(Vec3::new(2.0, 0.0, 0.0), Vec3::new(1.4, 0.0, 0.0));
+        assert!(cap.intersects_plane(pl));
+        let pl = Plane::new_norm(Vec3::new(1.0, 1.0, 0.0), Vec3::new(0.2, 0.0, 0.0));
+        assert!(cap.intersects_plane(pl));
+    }
+
+    #[test]
+    fn segment_box_distance() {
+        let he = Vec3::new(1.0, 0.5, 2.0);
+        let check = |a: Vec3<f32>, b: Vec3<f32>, dist2: f32| {
+            let x = segment_box_dist2(&a, &b, &he);
+            assert!((x - dist2).abs() <= 1e-5, "{x} != {dist2}");
+            let y = segment_box_dist2(&b, &a, &he);
+            assert!((x - y).abs() <= 1e-5, "{x} != {y}");
+        };
+        // Crossing.
+        check(Vec3::new(-3.0, 0.0, 0.0), Vec3::new(3.0, 0.0, 0.0), 0.0);
+        // Parallel to a face.
+        check(Vec3::new(-3.0, 1.5, 0.0), Vec3::new(3.0, 1.5, 0.0), 1.0);
+        // Past an edge.
+        check(Vec3::new(2.0, 1.5, -5.0), Vec3::new(2.0, 1.5, 5.0), 2.0);
+        // Diagonal, closest to a corner.
+        check(Vec3::new(0.0, 3.5, 2.0), Vec3::new(4.0, -0.5, 2.0), 2.0);
+        // Degenerate.
+        check(Vec3::new(2.0, 0.0, 3.0), Vec3::new(2.0, 0.0, 3.0), 2.0);
+
+        let mut next = crate::rand_gen(7);
+        let mut rand = || next() * 8.0 - 4.0;
+        for _ in 0..200 {
+            let a = Vec3::new(rand(), rand(), rand());
+            let b = Vec3::new(rand(), rand(), rand());
+            let ab = b - a;
+            let dist2 = (0..=4096)
+                .map(|i| box_dist2(&(a + ab * (i as f32 / 4096.0)), &he))
+                .fold(f32::INFINITY, f32::min);
+            let x = segment_box_dist2(&a, &b, &he);
+            assert!(x <= dist2 + 1e-5 && x >= dist2 - 1e-2, "{x} != {dist2}");
+        }
+    }
+
+    fn points_samples() -> Vec<Vec<Vec3<f32>>> {
+        let mut next = crate::rand_gen(1);
+        let mut rand = || next() * 2.0 - 1.0;
+        let cloud: Vec<_> = (0..500)
+            .map(|_| Vec3::new(rand() * 3.0 + 1.0, rand(), rand() * 0.5 - 2.0))
+            .collect();
+        let line: Vec<_> = (0..50).map(|i| Vec3::new(i as f32, 0.0, 0.0)).collect();
+        let circle: Vec<_> = (0..64)
+            .map(|i| {
+                let t = std::f32::consts::TAU * i as f32 / 64.0;
+                Vec3::new(t.cos(), 5.0, t.sin())
+            })
+            .collect();
+        let sorted: Vec<_> = (0..1000)
+            .map(|i| Vec3::new((i % 10) as f32, (i / 10 % 10) as f32, (i / 100) as f32))
+            .collect();
+        vec![
+            vec![Vec3::new(1.0, 2.0, 3.0)],
+            vec![Vec3::from(1.0); 10],
+            cloud,
+            line,
+            circle,
+            sorted,
+        ]
+    }
+
+    #[test]
+    fn bbox_from_points() {
+        let bb = Bbox::from_points(&[Vec3::new(1.0, 2.0, 3.0)]);
+        assert_eq!(bb, Bbox::new(Vec3::new(1.0, 2.0, 3.0), Vec3::from(0.0)));
+        let bb = Bbox::from_points(&[
+            Vec3::new(-1.0, 2.0, 0.0),
+            Vec3::new(3.0, -2.0, 1.0),
+            Vec3::new(0.0, 0.0, 5.0),
+        ]);
+        assert_eq!(
+            bb,
+            Bbox::new(Vec3::new(1.0, 0.0, 2.5), Vec3::new(2.0, 2.0, 2.5))
+        );
+        for pts in points_samples() {
+            // NOTE: `center ± half_extent` may be off by an ulp.
+            let bb = Bbox::from_points(&pts).resize_by(Vec3::from(1e-6));
+            assert!(pts.iter().all(|&p| bb.contains(p)));
+        }
+    }
+
+    #[test]
+    fn sphere_from_points() {
+        let sph = Sphere::from_points(&[Vec3::new(1.0, 2.0, 3.0)]);
+        assert_eq!(sph, Sphere::new(Vec3::new(1.0, 2.0, 3.0), 0.0));
+
+        let cube: Vec<_> = (0..8)
+            .map(|i| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32))
+            .collect();
+        let sph = Sphere::from_points(&cube);
+        assert!((sph.center() - Vec3::from(0.5)).length() <= 1e-5);
+        assert!((sph.radius() - 0.75f32.sqrt()).abs() <= 1e-5);
+
+        // Ritter's approximation is not minimal here.
+        let tri = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(2.0, 3.0, 0.0),
+            Vec3::new(2.0, 1.0, 0.0),
+        ];
+        let sph = Sphere::from_points(&tri);
+        assert!((sph.center() - Vec3::new(2.0, 5.0 / 6.0, 0.0)).length() <= 1e-5);
+        assert!((sph.radius() - 13.0 / 6.0).abs() <= 1e-5);
+        assert!(sph.radius() < ritter_sphere(&tri).radius());
+
+        let sph = Sphere::from_points(&points_samples()[4]);
+        assert!((sph.center() - Vec3::new(0.0, 5.0, 0.0)).length() <= 1e-5);
+        assert!((sph.radius() - 1.0).abs() <= 1e-5);
+
+        for pts in points_samples() {
+            let sph = Sphere::from_points(&pts);
+            let ritter = ritter_sphere(&pts);
+            assert!(pts.iter().all(|p| sph.encloses(p)));
+            assert!(pts.iter().all(|p| ritter.encloses(p)));
+            assert!(sph.radius() <= ritter.radius());
+            // Minimal spheres touch at least two points.
+            let touch = pts
+                .iter()
+                .filter(|p| ((*p - &sph.center()).length() - sph.radius()).abs() <= 1e-4)
+                .count();
+            assert!(touch >= 2 || sph.radius() == 0.0);
+        }
+    }
+
+    fn frustum_samples() -> Vec<(Mat4<f32>, Frustum)> {
+        let view = Mat4::look_at(
+            &Vec3::new(0.0, 0.0, 1.0),
+            &Vec3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+        );
+        let proj = Mat4::perspective(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 100.0);
+        // Maps depth `z` to `1 - z`.
+        let rev = Mat4::new(
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, -1.0, 0.0],
+            [0.0, 0.0, 1.0, 1.0],
+        );
+        let inf = Mat4::inf_perspective(std::f32::consts::FRAC_PI_2, 2.0, 1.0);
+        vec![
+            (
+                proj * view,
+                Frustum::new(&(proj * view), DepthRange::ZeroToOne),
+            ),
+            (
+                rev * proj * view,
+                Frustum::new(&(rev * proj * view), DepthRange::OneToZero),
+            ),
+            (
+                inf * view,
+                Frustum::new(&(inf * view), DepthRange::NegOneToOne),
+            ),
+        ]
+    }
+
+    #[test]
+    fn frustum() {
+        let [(_, f0), (_, f1), (_, f2)] = &frustum_samples()[..] else {
+            unreachable!()
+        };
+        assert_eq!(f0.planes().len(), 6);
+        assert_eq!(f1.planes().len(), 6);
+        assert_eq!(f2.planes().len(), 5);
+        assert!(!f0.is_infinite() && !f1.is_infinite() && f2.is_infinite());
+        for (a, b) in f0.planes().iter().zip(f1.planes()) {
+            assert!((a.coef() - b.coef()).length() <= 1e-6);
+        }
+        for (a, b) in f0.planes()[..5].iter().zip(f2.planes()) {
+            assert!((a.coef() - b.coef()).length() <= 1e-6);
+        }
+        for i in f0.planes() {
+            assert!((i.n().length() - 1.0).abs() <= 1e-6);
+        }
+        // The near plane is `z = 1`, facing `+z`.
+        let near = f0.planes()[4].coef();
+        assert!((near - Vec4::new(0.0, 0.0, 1.0, -1.0)).length() <= 1e-6);
+    }
+
+    #[test]
+    fn frustum_corners() {
+        let [(m0, f0), (_, f1), (_, f2)] = &frustum_samples()[..] else {
+            unreachable!()
+        };
+        assert!(f2.corners().is_none());
+        let c0 = f0.corners().unwrap();
+        let c1 = f1.corners().unwrap();
+        for (i, (a, b)) in c0.iter().zip(&c1).enumerate() {
+            let (x, y, z) = (i & 1, i >> 1 & 1, i >> 2 & 1);
+            let w = [1.0, 100.0][z];
+            let p = Vec3::new([-2.0, 2.0][x] * w, [-1.0, 1.0][y] * w, w);
+            assert!((p - *a).length() <= 1e-4 * w);
+            assert!((p - *b).length() <= 1e-4 * w);
+
+            let v = m0 * Vec4::new(p[0], p[1], p[2], 1.0);
+            let ndc = Vec3::from(v) / v[3];
+            assert!((ndc[0] - [-1.0, 1.0][x]).abs() <= 1e-4);
+            assert!((ndc[1] - [-1.0, 1.0][y]).abs() <= 1e-4);
+            assert!((ndc[2] - z as f32).abs() <= 1e-4);
+        }
+    }
+
+    #[test]
+    fn frustum_contains() {
+        for (_, f) in frustum_samples() {
+            assert!(f.contains(Vec3::new(0.0, 0.0, 2.0)));
+            assert!(f.contains(Vec3::new(3.0, -1.5, 50.0)));
+            assert!(!f.contains(Vec3::new(0.0, 0.0, 0.5)));
+            assert!(!f.contains(Vec3::new(0.0, 0.0, -10.0)));
+            assert!(!f.contains(Vec3::new(5.0, 0.0, 2.0)));
+            assert!(!f.contains(Vec3::new(0.0, 3.0, 2.0)));
+            assert_eq!(f.contains(Vec3::new(0.0, 0.0, 200.0)), f.is_infinite());
+        }
+    }
+
+    #[test]
+    fn frustum_intersects() {
+        for (_, f) in frustum_samples() {
+            let bb = Bbox::new(Vec3::new(0.0, 0.0, 10.0), Vec3::from(1.0));
+            assert_eq!(f.intersects_bbox(bb), Intersection::Inside);
+            assert_eq!(f.intersects_sphere(Sphere::from(bb)), Intersection::Inside);
+
+            let bb = Bbox::new(Vec3::new(0.0, 0.0, 1.0), Vec3::from(0.5));
+            assert_eq!(f.intersects_bbox(bb), Intersection::Intersecting);
+            assert_eq!(
+                f.intersects_sphere(Sphere::from(bb)),
+                Intersection::Intersecting
+            );
+
+            let bb = Bbox::new(Vec3::new(22.0, 0.0, 10.0), Vec3::from(1.0));
+            assert_eq!(f.intersects_bbox(bb), Intersection::Intersecting);
+            assert_eq!(
+                f.intersects_sphere(Sphere::from(bb)),
+                Intersection::Intersecting
+            );
+
+            let bb = Bbox::new(Vec3::new(0.0, -30.0, 10.0), Vec3::from(1.0));
+            assert_eq!(f.intersects_bbox(bb), Intersection::Outside);
+            assert_eq!(f.intersects_sphere(Sphere::from(bb)), Intersection::Outside);
+
+            let bb = Bbox::new(Vec3::new(0.0, 0.0, -5.0), Vec3::from(1.0));
+            assert_eq!(f.intersects_bbox(bb), Intersection::Outside);
+            assert_eq!(f.intersects_sphere(Sphere::from(bb)), Intersection::Outside);
+
+            let bb = Bbox::new(Vec3::new(0.0, 0.0, 150.0), Vec3::from(1.0));
+            let sph = Sphere::new(Vec3::new(0.0, 0.0, 100.25), 0.5);
+            if f.is_infinite() {
+                assert_eq!(f.intersects_bbox(bb), Intersection::Inside);
+                assert_eq!(f.intersects_sphere(sph), Intersection::Inside);
+            } else {
+                assert_eq!(f.intersects_bbox(bb), Intersection::Outside);
+                assert_eq!(f.intersects_sphere(sph), Intersection::Intersecting);
+            }
+
+            for (c, r) in [(0.0, 1.0), (22.0, 1.0), (-30.0, 1.0)] {
+                let bb = Bbox::new(Vec3::new(c, 0.0, 10.0), Vec3::from(r));
+                assert_eq!(f.intersects_obb(Obb::from(bb)), f.intersects_bbox(bb));
+            }
+            let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 1.0, 0.0));
+            let obb = Obb::new(Vec3::new(0.0, 0.0, 1.5), Vec3::new(1.0, 0.1, 0.1), rot);
+            assert_eq!(f.intersects_obb(obb), Intersection::Intersecting);
+            let obb = Obb::new(Vec3::new(0.0, 0.0, -1.0), Vec3::new(1.0, 0.1, 0.1), rot);
+            assert_eq!(f.intersects_obb(obb), Intersection::Outside);
+
+            let cap = Capsule::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 15.0), 1.0);
+            assert_eq!(f.intersects_capsule(cap), Intersection::Inside);
+            let cap = Capsule::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, 5.0), 1.0);
+            assert_eq!(f.intersects_capsule(cap), Intersection::Intersecting);
+            let cap = Capsule::new(Vec3::new(0.0, 0.0, -5.0), Vec3::new(0.0, 0.0, -1.5), 0.4);
+            assert_eq!(f.intersects_capsule(cap), Intersection::Outside);
+        }
+    }
+
+    #[test]
+    fn ray() {
+        let ray = Ray::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, 0.0, -2.0));
+        assert_eq!(ray.origin(), Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(ray.dir(), Vec3::new(0.0, 0.0, -1.0));
+        assert_eq!(ray.at(0.0), ray.origin());
+        assert_eq!(ray.at(4.0), Vec3::new(1.0, 2.0, -1.0));
+    }
+
+    #[test]
+    fn ray_bbox() {
+        let bb = Bbox::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(1.0, 2.0, 1.0));
+
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_bbox(bb).unwrap();
+        assert_eq!(hit.distance, 4.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, -1.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 10.0, 5.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = ray.intersect_bbox(bb).unwrap();
+        assert_eq!(hit.distance, 8.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        // From inside.
+        let ray = Ray::new(Vec3::new(0.5, 0.0, 5.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = ray.intersect_bbox(bb).unwrap();
+        assert_eq!(hit.distance, 0.5);
+        assert_eq!(hit.normal, Vec3::new(1.0, 0.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(-3.0, -1.0, 3.0), Vec3::new(1.0, 0.0, 1.0));
+        let hit = ray.intersect_bbox(bb).unwrap();
+        assert!((hit.distance - 2.0 * 2f32.sqrt()).abs() <= 1e-6);
+        assert_eq!(hit.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert!(bb.contains(ray.at(hit.distance + 1e-3)));
+
+        assert!(Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0))
+            .intersect_bbox(bb)
+            .is_none());
+        assert!(Ray::new(Vec3::new(2.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+            .intersect_bbox(bb)
+            .is_none());
+        assert!(Ray::new(Vec3::default(), Vec3::new(1.0, 0.0, 1.0))
+            .intersect_bbox(bb)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_obb() {
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let obb = Obb::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 0.5), rot);
+        let ray = Ray::new(Vec3::from(0.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = ray.intersect_obb(obb).unwrap();
+        assert!((hit.distance - (5.0 - 2f32.sqrt())).abs() <= 1e-5);
+        let n = Vec3::new(-1.0, -1.0, 0.0).normalize();
+        let m = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        assert!((hit.normal - n).length() <= 1e-5 || (hit.normal - m).length() <= 1e-5);
+
+        let ray = Ray::new(Vec3::new(5.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_obb(obb).unwrap();
+        assert!((hit.distance - 0.5).abs() <= 1e-6);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, 1.0)).length() <= 1e-6);
+
+        let ray = Ray::new(Vec3::new(2.5, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        assert!(ray.intersect_obb(obb).is_none());
+        assert!(ray.intersect_bbox(Bbox::from(obb)).is_some());
+    }
+
+    #[test]
+    fn ray_sphere() {
+        let sph = Sphere::new(Vec3::new(0.0, 0.0, 5.0), 2.0);
+
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_sphere(sph).unwrap();
+        assert_eq!(hit.distance, 3.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, -1.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 1.0, 0.0));
+        let hit = ray.intersect_sphere(sph).unwrap();
+        assert_eq!(hit.distance, 2.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_sphere(sph).unwrap();
+        assert!((hit.distance - (5.0 - 3f32.sqrt())).abs() <= 1e-6);
+        assert!((hit.normal.length() - 1.0).abs() <= 1e-6);
+
+        assert!(Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0))
+            .intersect_sphere(sph)
+            .is_none());
+        assert!(Ray::new(Vec3::new(2.5, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+            .intersect_sphere(sph)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_capsule() {
+        let cap = Capsule::new(Vec3::new(-2.0, 0.0, 5.0), Vec3::new(2.0, 0.0, 5.0), 1.0);
+
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_capsule(cap).unwrap();
+        assert_eq!(hit.distance, 4.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, -1.0));
+
+        let ray = Ray::new(Vec3::new(-8.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = ray.intersect_capsule(cap).unwrap();
+        assert_eq!(hit.distance, 5.0);
+        assert_eq!(hit.normal, Vec3::new(-1.0, 0.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 0.0));
+        let hit = ray.intersect_capsule(cap).unwrap();
+        assert_eq!(hit.distance, 3.0);
+        assert_eq!(hit.normal, Vec3::new(1.0, 0.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 5.0, 5.5), Vec3::new(0.0, -1.0, 0.0));
+        let hit = ray.intersect_capsule(cap).unwrap();
+        assert!((hit.distance - (5.0 - 0.75f32.sqrt())).abs() <= 1e-5);
+        assert!((hit.normal - Vec3::new(0.0, 0.75f32.sqrt(), 0.5)).length() <= 1e-5);
+
+        // Through the rounded end, which the enclosing box
+        // contains but the capsule does not.
+        let ray = Ray::new(Vec3::new(2.9, -5.0, 4.1), Vec3::new(0.0, 1.0, 0.0));
+        assert!(ray.intersect_capsule(cap).is_none());
+        assert!(ray.intersect_bbox(Bbox::from(cap)).is_some());
+        let ray = Ray::new(Vec3::new(2.5, -5.0, 5.0), Vec3::new(0.0, 1.0, 0.0));
+        let hit = ray.intersect_capsule(cap).unwrap();
+        assert!((hit.distance - (5.0 - 0.75f32.sqrt())).abs() <= 1e-5);
+
+        assert!(Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0))
+            .intersect_capsule(cap)
+            .is_none());
+        assert!(Ray::new(Vec3::new(0.0, 1.5, 0.0), Vec3::new(0.0, 0.0, 1.0))
+            .intersect_capsule(cap)
+            .is_none());
+
+        // Degenerate segment.
+        let sph = Capsule::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 5.0), 2.0);
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(ray.intersect_capsule(sph).unwrap().distance, 3.0);
+
+        // A grid of rays, compared against sampling.
+        for x in -12..=12 {
+            for z in -8..=8 {
+                let o = Vec3::new(x as f32 * 0.25, -5.0, z as f32 * 0.2 + 5.0);
+                let ray = Ray::new(o, Vec3::new(0.3, 1.0, -0.2));
+                let t = (0..4096)
+                    .map(|i| i as f32 / 256.0)
+                    .find(|&t| cap.contains(ray.at(t)));
+                match (ray.intersect_capsule(cap), t) {
+                    (Some(hit), Some(t)) => {
+                        assert!(hit.distance <= t && hit.distance + 1.0 / 256.0 > t);
+                        let p = ray.at(hit.distance);
+                        let n = (p - closest_on_segment(&p, &cap.a, &cap.b)).normalize();
+                        assert!((hit.normal - n).length() <= 1e-4);
+                    }
+                    (None, None) => (),
+                    (hit, t) => panic!("{ray:?} {hit:?} {t:?}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ray_plane() {
+        let pln = Plane::new_norm(Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, -2.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(0.0, -1.0, 0.0));
+        let hit = ray.intersect_plane(pln).unwrap();
+        assert_eq!(hit.distance, 4.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        let ray = Ray::new(Vec3::new(0.0, -5.0, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        let hit = ray.intersect_plane(pln).unwrap();
+        assert!((hit.distance - 3.0 * 2f32.sqrt()).abs() <= 1e-5);
+        assert_eq!(hit.normal, Vec3::new(0.0, -1.0, 0.0));
+
+        let pln = Plane::new(0.0, 2.0, 0.0, 4.0);
+        let hit = ray.intersect_plane(pln).unwrap();
+        assert_eq!(hit.normal, Vec3::new(0.0, -1.0, 0.0));
+
+        assert!(Ray::new(Vec3::default(), Vec3::new(1.0, 0.0, 0.0))
+            .intersect_plane(pln)
+            .is_none());
+        assert!(Ray::new(Vec3::default(), Vec3::new(0.0, 1.0, 0.0))
+            .intersect_plane(pln)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_triangle() {
+        let (a, b, c) = (
+            Vec3::new(-1.0, -1.0, 2.0),
+            Vec3::new(1.0, -1.0, 2.0),
+            Vec3::new(0.0, 1.0, 2.0),
+        );
+
+        let ray = Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_triangle(a, b, c).unwrap();
+        assert_eq!(hit.distance, 2.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+        let hit = ray.intersect_triangle(a, c, b).unwrap();
+        assert_eq!(hit.distance, 2.0);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, -1.0));
+
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 4.0), Vec3::new(0.0, -0.5, -1.0));
+        let hit = ray.intersect_triangle(a, b, c).unwrap();
+        assert!((ray.at(hit.distance) - Vec3::new(0.0, -1.0, 2.0)).length() <= 1e-6);
+
+        assert!(Ray::new(Vec3::default(), Vec3::new(0.0, 0.0, -1.0))
+            .intersect_triangle(a, b, c)
+            .is_none());
+        assert!(Ray::new(Vec3::new(0.9, 0.9, 0.0), Vec3::new(0.0, 0.0, 1.0))
+            .intersect_triangle(a, b, c)
+            .is_none());
+        assert!(Ray::new(Vec3::default(), Vec3::new(1.0, 0.0, 0.0))
+            .intersect_triangle(a, b, c)
+            .is_none());
+    }
+
+    #[test]
+    fn ray_unproject() {
+        for (m, f) in frustum_samples() {
+            let depth = match f.planes().len() {
+                5 => DepthRange::NegOneToOne,
+                _ if m[2][2] < 0.0 => DepthRange::OneToZero,
+                _ => DepthRange::ZeroToOne,
+            };
+            let inv = m.invert();
+
+            let ray = Ray::unproject(Vec2::new(0.0, 0.0), &inv, depth);
+            assert!((ray.origin() - Vec3::new(0.0, 0.0, 1.0)).length() <= 1e-5);
+            assert!((ray.dir() - Vec3::new(0.0, 0.0, 1.0)).length() <= 1e-5);
+
+            let ray = Ray::unproject(Vec2::new(1.0, -1.0), &inv, depth);
+            assert!((ray.origin() - Vec3::new(2.0, -1.0, 1.0)).length() <= 1e-5);
+            let dir = Vec3::new(2.0, -1.0, 1.0).normalize();
+            assert!((ray.dir() - dir).length() <= 1e-5);
+        }
+    }
 }
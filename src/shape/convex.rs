@@ -0,0 +1,658 @@
+//! Convex queries based on support functions.
+
+use crate::linear::{Mat3, Mat4, Vec3, Vec4};
+use crate::shape::{Bbox, Capsule, Obb, Sphere};
+
+// Maximum number of GJK/EPA iterations.
+const MAX_ITER: usize = 64;
+
+// Tolerance used to detect convergence.
+const TOL: f32 = 1e-5;
+// Minimum relative progress for a tetrahedron to be trusted.
+const REL_TOL: f32 = 1e-3;
+
+/// Convex shape described by its support function.
+pub trait Support {
+    /// Returns a point of the shape that lies farthest
+    /// along `dir`.
+    ///
+    /// NOTE: `dir` is not necessarily normalized.
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32>;
+}
+
+impl Support for Bbox {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        let mut p = self.center;
+        for i in 0..3 {
+            p[i] += self.half_extent[i].copysign(dir[i]);
+        }
+        p
+    }
+}
+
+impl Support for Sphere {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        let len = dir.length();
+        if len > 0.0 {
+            self.center + dir * (self.radius / len)
+        } else {
+            self.center
+        }
+    }
+}
+
+impl Support for Obb {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        let mut p = self.center;
+        for i in 0..3 {
+            let s = self.half_extent[i].copysign(self.rotation[i].dot(dir));
+            p += self.rotation[i] * s;
+        }
+        p
+    }
+}
+
+impl Support for Capsule {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        let p = if self.a.dot(dir) >= self.b.dot(dir) {
+            self.a
+        } else {
+            self.b
+        };
+        Sphere::new(p, self.radius).support(dir)
+    }
+}
+
+/// Convex hull of a point cloud.
+///
+/// The hull itself is never computed, since the support
+/// function only needs its extreme points.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hull {
+    points: Box<[Vec3<f32>]>,
+}
+
+impl Hull {
+    /// Creates the convex hull of `points`.
+    ///
+    /// NOTE: One must ensure that `points` is not empty.
+    pub fn new(points: Vec<Vec3<f32>>) -> Self {
+        debug_assert!(!points.is_empty());
+        Self {
+            points: points.into_boxed_slice(),
+        }
+    }
+
+    /// Transforms the hull.
+    pub fn transform(&self, xform: &Mat4<f32>) -> Self {
+        let f = |p: &Vec3<f32>| Vec3::from(xform * Vec4::new(p[0], p[1], p[2], 1.0));
+        Self {
+            points: self.points.iter().map(f).collect(),
+        }
+    }
+
+    /// Returns the points.
+    pub fn points(&self) -> &[Vec3<f32>] {
+        &self.points
+    }
+}
+
+impl Support for Hull {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        self.points
+            .iter()
+            .copied()
+            .max_by(|a, b| a.dot(dir).total_cmp(&b.dot(dir)))
+            .unwrap()
+    }
+}
+
+impl<T: Support + ?Sized> Support for &T {
+    fn support(&self, dir: &Vec3<f32>) -> Vec3<f32> {
+        (**self).support(dir)
+    }
+}
+
+/// Vertex of the Minkowski difference `a - b`.
+#[derive(Copy, Clone, Debug)]
+struct Vertex {
+    p: Vec3<f32>,
+    a: Vec3<f32>,
+    b: Vec3<f32>,
+}
+
+fn support<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B, dir: &Vec3<f32>) -> Vertex {
+    let (a, b) = (a.support(dir), b.support(&-dir));
+    Vertex { p: a - b, a, b }
+}
+
+/// Outcome of [`gjk`].
+enum Gjk {
+    /// The shapes overlap. The simplex contains the origin
+    /// (possibly on its boundary).
+    Overlap(Vec<Vertex>),
+    /// The shapes are disjoint.
+    Disjoint(Vec3<f32>, Vec3<f32>),
+}
+
+/// Runs the GJK distance algorithm.
+fn gjk<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> Gjk {
+    let mut simplex = vec![support(a, b, &Vec3::new(1.0, 0.0, 0.0))];
+    let mut weights = vec![1.0];
+    let mut v = simplex[0].p;
+    for _ in 0..MAX_ITER {
+        let vv = v.dot(&v);
+        if vv <= TOL * TOL {
+            return Gjk::Overlap(simplex);
+        }
+        let w = support(a, b, &-v);
+        // `v` is (nearly) as close as the Minkowski
+        // difference gets to the origin.
+        let gap = vv - v.dot(&w.p);
+        if gap <= TOL * vv || simplex.iter().any(|x| x.p == w.p) {
+            break;
+        }
+        simplex.push(w);
+        let (u, s, l) = closest_on_simplex(&simplex);
+        if s.len() == 4 {
+            // NOTE: A tetrahedron spanned by a vertex that barely
+            // improves on `v` is degenerate, so rounding alone may
+            // place the origin inside it.
+            if gap <= REL_TOL * vv {
+                break;
+            }
+            return Gjk::Overlap(s);
+        }
+        if u.dot(&u) >= vv {
+            // No progress.
+            break;
+        }
+        (v, simplex, weights) = (u, s, l);
+    }
+    let mut pa = Vec3::default();
+    let mut pb = Vec3::default();
+    for (x, l) in simplex.iter().zip(&weights) {
+        pa += x.a * *l;
+        pb += x.b * *l;
+    }
+    if v.dot(&v) <= TOL * TOL {
+        Gjk::Overlap(simplex)
+    } else {
+        Gjk::Disjoint(pa, pb)
+    }
+}
+
+/// Computes the point of `simplex` that lies closest to the
+/// origin, the smallest sub-simplex that contains it and the
+/// point's barycentric coordinates in that sub-simplex.
+///
+/// A full tetrahedron is returned if it contains the origin.
+fn closest_on_simplex(simplex: &[Vertex]) -> (Vec3<f32>, Vec<Vertex>, Vec<f32>) {
+    match *simplex {
+        [a] => (a.p, vec![a], vec![1.0]),
+        [a, b] => {
+            let ab = b.p - a.p;
+            let len2 = ab.dot(&ab);
+            let t = if len2 > 0.0 {
+                (-a.p.dot(&ab) / len2).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            if t <= 0.0 {
+                (a.p, vec![a], vec![1.0])
+            } else if t >= 1.0 {
+                (b.p, vec![b], vec![1.0])
+            } else {
+                (a.p + ab * t, vec![a, b], vec![1.0 - t, t])
+            }
+        }
+        [a, b, c] => closest_on_triangle(a, b, c),
+        [a, b, c, d] => {
+            let (ab, ac, ad) = (b.p - a.p, c.p - a.p, d.p - a.p);
+            let vol = ab.cross(&ac).dot(&ad).abs();
+            let flat = vol <= TOL * ab.length() * ac.length() * ad.length();
+            let mut best: Option<(Vec3<f32>, Vec<Vertex>, Vec<f32>)> = None;
+            for (x, y, z, w) in [(a, b, c, d), (a, c, d, b), (a, d, b, c), (b, d, c, a)] {
+                let n = (y.p - x.p).cross(&(z.p - x.p));
+                // Only faces that separate the origin from the
+                // opposite vertex can hold the closest point,
+                // unless the tetrahedron is flat.
+                let side_o = -x.p.dot(&n);
+                let side_w = (w.p - x.p).dot(&n);
+                if side_o * side_w >= 0.0 && !flat {
+                    continue;
+                }
+                let r = closest_on_triangle(x, y, z);
+                if best.as_ref().is_none_or(|b| r.0.dot(&r.0) < b.0.dot(&b.0)) {
+                    best = Some(r);
+                }
+            }
+            best.unwrap_or_else(|| (Vec3::default(), simplex.to_vec(), vec![0.25; 4]))
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn closest_on_triangle(a: Vertex, b: Vertex, c: Vertex) -> (Vec3<f32>, Vec<Vertex>, Vec<f32>) {
+    // Voronoi region tests as in Ericson's Real-Time
+    // Collision Detection (5.1.5), with `p` at the origin.
+    let (ab, ac) = (b.p - a.p, c.p - a.p);
+    let ap = -a.p;
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a.p, vec![a], vec![1.0]);
+    }
+    let bp = -b.p;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b.p, vec![b], vec![1.0]);
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let t = d1 / (d1 - d3);
+        return (a.p + ab * t, vec![a, b], vec![1.0 - t, t]);
+    }
+    let cp = -c.p;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c.p, vec![c], vec![1.0]);
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let t = d2 / (d2 - d6);
+        return (a.p + ac * t, vec![a, c], vec![1.0 - t, t]);
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let t = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b.p + (c.p - b.p) * t, vec![b, c], vec![1.0 - t, t]);
+    }
+    let den = va + vb + vc;
+    if den.abs() <= f32::MIN_POSITIVE {
+        // Degenerate triangle.
+        return closest_on_simplex(&[a, b]);
+    }
+    let (v, w) = (vb / den, vc / den);
+    (
+        a.p + ab * v + ac * w,
+        vec![a, b, c],
+        vec![1.0 - v - w, v, w],
+    )
+}
+
+/// Checks whether two convex shapes intersect.
+pub fn intersects<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> bool {
+    matches!(gjk(a, b), Gjk::Overlap(_))
+}
+
+/// Computes the closest points between two convex shapes.
+///
+/// Returns the point on `a` and the point on `b`, or [`None`]
+/// if the shapes intersect.
+pub fn closest_points<A: Support + ?Sized, B: Support + ?Sized>(
+    a: &A,
+    b: &B,
+) -> Option<(Vec3<f32>, Vec3<f32>)> {
+    match gjk(a, b) {
+        Gjk::Overlap(_) => None,
+        Gjk::Disjoint(pa, pb) => Some((pa, pb)),
+    }
+}
+
+/// Penetration of two convex shapes.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Contact {
+    /// Normalized direction from the first shape
+    /// towards the second.
+    pub normal: Vec3<f32>,
+    /// Distance that the second shape must be moved along
+    /// `normal` to separate the shapes.
+    pub depth: f32,
+}
+
+/// Computes the penetration of two convex shapes using EPA.
+///
+/// Returns [`None`] if the shapes do not intersect.
+pub fn penetration<A: Support + ?Sized, B: Support + ?Sized>(a: &A, b: &B) -> Option<Contact> {
+    let mut poly = match gjk(a, b) {
+        Gjk::Overlap(x) => x,
+        Gjk::Disjoint(..) => return None,
+    };
+    if !blow_up(a, b, &mut poly) {
+        // The Minkowski difference is flat, so
+        // the shapes are merely touching.
+        let normal = match poly[..] {
+            [x, y, z, ..] => (y.p - x.p).cross(&(z.p - x.p)),
+            _ => Vec3::default(),
+        };
+        let normal = if normal.dot(&normal) > 0.0 {
+            normal.normalize()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        return Some(Contact { normal, depth: 0.0 });
+    }
+
+    let inner = (poly[0].p + poly[1].p + poly[2].p + poly[3].p) / 4.0;
+    let mut faces: Vec<Face> = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]]
+        .iter()
+        .filter_map(|&f| Face::new(&poly, f, &inner))
+        .collect();
+    let mut best = None;
+    for _ in 0..MAX_ITER {
+        let Some(face) = faces
+            .iter()
+            .min_by(|x, y| x.dist.total_cmp(&y.dist))
+            .copied()
+        else {
+            break;
+        };
+        best = Some(face);
+        let w = support(a, b, &face.n);
+        if w.p.dot(&face.n) - face.dist <= TOL * face.dist.max(1.0) {
+            break;
+        }
+        // Replace the faces that `w` can see by
+        // new faces connecting it to the horizon.
+        let mut horizon: Vec<[usize; 2]> = vec![];
+        faces.retain(|f| {
+            if f.n.dot(&(w.p - poly[f.idx[0]].p)) <= 0.0 {
+                return true;
+            }
+            for e in [
+                [f.idx[0], f.idx[1]],
+                [f.idx[1], f.idx[2]],
+                [f.idx[2], f.idx[0]],
+            ] {
+                match horizon.iter().position(|x| *x == [e[1], e[0]]) {
+                    Some(i) => {
+                        horizon.swap_remove(i);
+                    }
+                    None => horizon.push(e),
+                }
+            }
+            false
+        });
+        poly.push(w);
+        let k = poly.len() - 1;
+        for [i, j] in horizon {
+            if let Some(f) = Face::new(&poly, [i, j, k], &inner) {
+                faces.push(f);
+            }
+        }
+    }
+    best.map(|f| Contact {
+        normal: f.n,
+        depth: f.dist.max(0.0),
+    })
+}
+
+/// Face of the EPA polytope.
+#[derive(Copy, Clone, Debug)]
+struct Face {
+    idx: [usize; 3],
+    // Outward normal.
+    n: Vec3<f32>,
+    // Distance from the origin.
+    dist: f32,
+}
+
+impl Face {
+    /// Creates a face whose normal points away from `inner`.
+    fn new(poly: &[Vertex], idx: [usize; 3], inner: &Vec3<f32>) -> Option<Self> {
+        let (a, b, c) = (poly[idx[0]].p, poly[idx[1]].p, poly[idx[2]].p);
+        let n = (b - a).cross(&(c - a));
+        let len = n.length();
+        if len <= f32::MIN_POSITIVE {
+            return None;
+        }
+        let (idx, n) = if n.dot(&(a - *inner)) < 0.0 {
+            ([idx[0], idx[2], idx[1]], -n / len)
+        } else {
+            (idx, n / len)
+        };
+        Some(Self {
+            idx,
+            n,
+            dist: n.dot(&a),
+        })
+    }
+}
+
+/// Grows an overlapping GJK simplex into a tetrahedron.
+///
+/// Returns `false` if no tetrahedron with positive
+/// volume could be found.
+fn blow_up<A: Support + ?Sized, B: Support + ?Sized>(
+    a: &A,
+    b: &B,
+    simplex: &mut Vec<Vertex>,
+) -> bool {
+    let axes = [
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ];
+    let scale = |s: &[Vertex]| {
+        s.iter()
+            .map(|x| x.p.length())
+            .fold(f32::MIN_POSITIVE, f32::max)
+    };
+    if simplex.len() == 1 {
+        for d in axes.iter().flat_map(|x| [*x, -x]) {
+            let w = support(a, b, &d);
+            if (w.p - simplex[0].p).length() > TOL * scale(simplex) {
+                simplex.push(w);
+                break;
+            }
+        }
+    }
+    if simplex.len() == 2 {
+        let ab = simplex[1].p - simplex[0].p;
+        let k = (0..3)
+            .min_by(|&i, &j| ab[i].abs().total_cmp(&ab[j].abs()))
+            .unwrap();
+        let perp = ab.cross(&axes[k]);
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_3, &ab);
+        let mut d = perp;
+        for _ in 0..6 {
+            let w = support(a, b, &d);
+            let area = ab.cross(&(w.p - simplex[0].p)).length();
+            if area > TOL * ab.length() * scale(simplex) {
+                simplex.push(w);
+                break;
+            }
+            d = rot * d;
+        }
+    }
+    if simplex.len() == 3 {
+        let n = (simplex[1].p - simplex[0].p).cross(&(simplex[2].p - simplex[0].p));
+        for d in [n, -n] {
+            let w = support(a, b, &d);
+            if (w.p - simplex[0].p).dot(&n).abs() > TOL * n.length() * scale(simplex) {
+                simplex.push(w);
+                break;
+            }
+        }
+    }
+    if simplex.len() < 4 {
+        return false;
+    }
+    let [x, y, z, w] = [simplex[0].p, simplex[1].p, simplex[2].p, simplex[3].p];
+    (y - x).cross(&(z - x)).dot(&(w - x)).abs() > f32::MIN_POSITIVE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn support_fns() {
+        let bb = Bbox::new(Vec3::new(1.0, 2.0, 3.0), Vec3::new(1.0, 0.5, 0.25));
+        assert_eq!(
+            bb.support(&Vec3::new(1.0, -1.0, 1.0)),
+            Vec3::new(2.0, 1.5, 3.25)
+        );
+        let sph = Sphere::new(Vec3::new(1.0, 0.0, 0.0), 2.0);
+        assert_eq!(
+            sph.support(&Vec3::new(0.0, -3.0, 0.0)),
+            Vec3::new(1.0, -2.0, 0.0)
+        );
+        let obb = Obb::from(bb);
+        assert_eq!(
+            obb.support(&Vec3::new(-1.0, 1.0, -1.0)),
+            bb.support(&Vec3::new(-1.0, 1.0, -1.0))
+        );
+        let rot = Mat3::rotation(std::f32::consts::FRAC_PI_4, &Vec3::new(0.0, 0.0, 1.0));
+        let obb = Obb::new(Vec3::from(0.0), Vec3::new(1.0, 0.0, 0.0), rot);
+        let p = obb.support(&Vec3::new(1.0, 1.0, 0.0));
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        assert!((p - Vec3::new(s, s, 0.0)).length() <= 1e-6);
+        let cap = Capsule::new(Vec3::from(0.0), Vec3::new(0.0, 2.0, 0.0), 0.5);
+        assert_eq!(
+            cap.support(&Vec3::new(0.0, 1.0, 0.0)),
+            Vec3::new(0.0, 2.5, 0.0)
+        );
+        assert_eq!(
+            cap.support(&Vec3::new(-2.0, 0.0, 0.0)),
+            Vec3::new(-0.5, 0.0, 0.0)
+        );
+
+        let hull = Hull::new(
+            (0..8)
+                .map(|i| Vec3::new((i & 1) as f32, (i >> 1 & 1) as f32, (i >> 2) as f32))
+                .collect(),
+        );
+        assert_eq!(
+            hull.support(&Vec3::new(1.0, -1.0, 1.0)),
+            Vec3::new(1.0, 0.0, 1.0)
+        );
+        let hull = hull.transform(&Mat4::translation(1.0, 2.0, 3.0));
+        assert_eq!(
+            hull.support(&Vec3::new(-1.0, -1.0, -1.0)),
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+    }
+
+    #[test]
+    fn gjk_intersects() {
+        let mut rand = crate::rand_gen(3);
+        for _ in 0..500 {
+            let mut v = || Vec3::new(rand() * 4.0 - 2.0, rand() * 4.0 - 2.0, rand() * 4.0 - 2.0);
+            let (c0, c1, e0, e1) = (v(), v(), v(), v());
+            let abs = |x: Vec3<f32>| Vec3::new(x[0].abs(), x[1].abs(), x[2].abs());
+            let (e0, e1) = (
+                abs(e0) * 0.5 + Vec3::from(0.1),
+                abs(e1) * 0.5 + Vec3::from(0.1),
+            );
+
+            let (b0, b1) = (Bbox::new(c0, e0), Bbox::new(c1, e1));
+            assert_eq!(intersects(&b0, &b1), b0.intersects(b1));
+            let (s0, s1) = (Sphere::new(c0, e0[0]), Sphere::new(c1, e1[0]));
+            assert_eq!(intersects(&s0, &s1), s0.intersects(s1));
+            assert_eq!(intersects(&b0, &s1), b0.intersects_sphere(s1));
+            let rot = Mat3::rotation(e0[1] * 5.0, &e1);
+            let (o0, o1) = (Obb::new(c0, e0, rot), Obb::new(c1, e1, Mat3::from(1.0)));
+            assert_eq!(intersects(&o0, &o1), o0.intersects(o1));
+            let (k0, k1) = (Capsule::new(c0, c1, e0[2]), Capsule::new(e0, e1, e1[2]));
+            assert_eq!(intersects(&k0, &k1), k0.intersects(k1));
+            assert_eq!(intersects(&k0, &o1), k0.intersects_obb(o1));
+        }
+    }
+
+    #[test]
+    fn gjk_closest_points() {
+        let s0 = Sphere::new(Vec3::from(0.0), 1.0);
+        let s1 = Sphere::new(Vec3::new(4.0, 0.0, 0.0), 2.0);
+        let (pa, pb) = closest_points(&s0, &s1).unwrap();
+        assert!((pa - Vec3::new(1.0, 0.0, 0.0)).length() <= 1e-3);
+        assert!((pb - Vec3::new(2.0, 0.0, 0.0)).length() <= 1e-3);
+
+        let b0 = Bbox::new(Vec3::from(0.0), Vec3::from(1.0));
+        let b1 = Bbox::new(Vec3::new(3.0, 3.5, 0.5), Vec3::from(1.0));
+        let (pa, pb) = closest_points(&b0, &b1).unwrap();
+        assert!(((pb - pa).length() - 1.5f32.hypot(1.0)).abs() <= 1e-4);
+        assert!((pa[0] - 1.0).abs() <= 1e-4 && (pa[1] - 1.0).abs() <= 1e-4);
+
+        let k = Capsule::new(Vec3::new(-5.0, 2.0, 0.0), Vec3::new(5.0, 2.0, 0.0), 0.5);
+        let (pa, pb) = closest_points(&b0, &k).unwrap();
+        assert!(((pb - pa).length() - 0.5).abs() <= 1e-4);
+        assert!((pa[1] - 1.0).abs() <= 1e-4 && (pb[1] - 1.5).abs() <= 1e-4);
+
+        assert!(closest_points(&b0, &s0).is_none());
+
+        let mut rand = crate::rand_gen(11);
+        for _ in 0..200 {
+            let mut v = || {
+                Vec3::new(
+                    rand() * 10.0 - 5.0,
+                    rand() * 10.0 - 5.0,
+                    rand() * 10.0 - 5.0,
+                )
+            };
+            let (c0, c1) = (v(), v());
+            let (s0, s1) = (Sphere::new(c0, 1.0), Sphere::new(c1, 0.5));
+            let d = (c1 - c0).length() - 1.5;
+            match closest_points(&s0, &s1) {
+                Some((pa, pb)) => assert!(((pb - pa).length() - d).abs() <= 1e-3),
+                None => assert!(d <= 1e-3),
+            }
+        }
+    }
+
+    #[test]
+    fn epa() {
+        let s0 = Sphere::new(Vec3::from(0.0), 1.0);
+        let s1 = Sphere::new(Vec3::new(1.5, 0.0, 0.0), 1.0);
+        let c = penetration(&s0, &s1).unwrap();
+        assert!((c.depth - 0.5).abs() <= 1e-2);
+        assert!((c.normal - Vec3::new(1.0, 0.0, 0.0)).length() <= 1e-1);
+        assert!(penetration(&s0, &Sphere::new(Vec3::new(2.5, 0.0, 0.0), 1.0)).is_none());
+
+        let b0 = Bbox::new(Vec3::from(0.0), Vec3::from(1.0));
+        for (c, n, d) in [
+            (Vec3::new(0.0, -1.5, 0.2), Vec3::new(0.0, -1.0, 0.0), 0.5),
+            (Vec3::new(1.75, 0.5, 0.2), Vec3::new(1.0, 0.0, 0.0), 0.25),
+            (Vec3::new(0.1, 0.0, 1.9), Vec3::new(0.0, 0.0, 1.0), 0.1),
+            (Vec3::new(0.0, 0.0, 0.2), Vec3::new(0.0, 0.0, 1.0), 1.8),
+        ] {
+            let b1 = Bbox::new(c, Vec3::new(1.0, 1.0, 1.0));
+            let x = penetration(&b0, &b1).unwrap();
+            assert!((x.depth - d).abs() <= 1e-4);
+            assert!((x.normal - n).length() <= 1e-4);
+            // Moving `b1` by the penetration separates the boxes.
+            let b1 = b1.displace_by(x.normal * (x.depth + 1e-3));
+            assert!(!intersects(&b0, &b1));
+        }
+
+        let mut rand = crate::rand_gen(5);
+        for _ in 0..200 {
+            let mut v = || Vec3::new(rand() * 2.0 - 1.0, rand() * 2.0 - 1.0, rand() * 2.0 - 1.0);
+            let (c0, c1, axis) = (v(), v(), v());
+            let rot = Mat3::rotation(axis[0] * 3.0, &(axis + Vec3::new(0.0, 0.0, 2.0)));
+            let o = Obb::new(c0, Vec3::new(1.0, 0.5, 0.25), rot);
+            let k = Capsule::new(c1, c1 + axis, 0.3);
+            if let Some(x) = penetration(&o, &k) {
+                assert!((x.normal.length() - 1.0).abs() <= 1e-4);
+                let k = Capsule::new(
+                    k.a + x.normal * (x.depth + 1e-2),
+                    k.b + x.normal * (x.depth + 1e-2),
+                    k.radius,
+                );
+                assert!(!intersects(&o, &k));
+            } else {
+                assert!(!o.intersects_capsule(k));
+            }
+        }
+    }
+
+    #[test]
+    fn epa_touching() {
+        // Flat shapes have a flat Minkowski difference.
+        let b0 = Bbox::new(Vec3::from(0.0), Vec3::new(1.0, 1.0, 0.0));
+        let b1 = Bbox::new(Vec3::new(0.5, 0.5, 0.0), Vec3::new(1.0, 1.0, 0.0));
+        let c = penetration(&b0, &b1).unwrap();
+        assert_eq!(c.depth, 0.0);
+        assert!((c.normal[2].abs() - 1.0).abs() <= 1e-6);
+    }
+}
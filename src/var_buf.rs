@@ -4,8 +4,32 @@ use std::cmp::Ordering;
 use std::io;
 use std::ops::Range;
 use std::ptr::{self, NonNull};
-
-use crate::bit_vec::BitVec;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering::Relaxed};
+use std::sync::Mutex;
+
+use crate::bit_vec::AtomicBitVec;
+
+mod policy;
+use crate::var_buf::policy::{Buddy, Slabs};
+
+/// [`VarBuf`]'s allocation policy.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum VarPolicy {
+    /// First fit over `VarAlloc::STRIDE`-sized blocks.
+    Linear,
+    /// Entries smaller than `VarAlloc::STRIDE` are taken from
+    /// slabs of power-of-two slots, one slab per block.
+    /// Larger entries behave as in `Linear`.
+    ///
+    /// The slabs are kept under a lock, so allocations are
+    /// not lock-free as in `Linear`.
+    SizeClass,
+    /// Power-of-two blocks that are split and merged with
+    /// their buddies.
+    ///
+    /// As in `SizeClass`, allocations take a lock.
+    Buddy,
+}
 
 /// [`VarBuf`]'s allocation.
 pub trait VarAlloc {
@@ -14,6 +38,9 @@ pub trait VarAlloc {
     /// It must be a power of two.
     const STRIDE: usize = 512;
 
+    /// The policy used to place entries.
+    const POLICY: VarPolicy = VarPolicy::Linear;
+
     /// Grows the allocation to a given size in bytes.
     ///
     /// If `new_size` is less than or equal to the current size,
@@ -60,6 +87,24 @@ impl VarEntry {
     pub fn range(&self) -> Range<usize> {
         self.offset..self.offset + self.size
     }
+
+    /// Patches the entry's offset according to the moves
+    /// reported by [`VarBuf::compact`].
+    ///
+    /// `moves` must be sorted by source offset, as they are
+    /// when reported.
+    /// It returns whether the entry was moved.
+    pub fn relocate(&mut self, moves: &[VarMove]) -> bool {
+        let i = moves.partition_point(|x| x.src + x.size <= self.offset);
+        match moves.get(i) {
+            Some(x) if x.src <= self.offset => {
+                debug_assert!(self.offset + self.size <= x.src + x.size);
+                self.offset = self.offset - x.src + x.dst;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 impl PartialEq for VarEntry {
@@ -92,14 +137,144 @@ impl Ord for VarEntry {
     }
 }
 
+#[cfg(test)]
+impl VarEntry {
+    /// Creates an entry that belongs to no buffer.
+    pub(crate) fn dummy(offset: usize, size: usize) -> Self {
+        Self { offset, size }
+    }
+}
+
+/// Relocation of data performed by [`VarBuf::compact`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct VarMove {
+    src: usize,
+    dst: usize,
+    size: usize,
+}
+
+impl VarMove {
+    /// Returns the byte offset the data was moved from.
+    pub fn src(&self) -> usize {
+        self.src
+    }
+
+    /// Returns the byte offset the data was moved to.
+    pub fn dst(&self) -> usize {
+        self.dst
+    }
+
+    /// Returns the number of bytes moved.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// [`VarBuf`]'s usage statistics.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct VarStats {
+    total: usize,
+    used: usize,
+    free: usize,
+    largest_free: usize,
+    entries: usize,
+    free_runs: Vec<usize>,
+}
+
+impl VarStats {
+    /// Returns the size of the allocation, in bytes.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Returns the number of bytes held by entries,
+    /// including alignment padding.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Returns the number of bytes not held by entries.
+    pub fn free(&self) -> usize {
+        self.free
+    }
+
+    /// Returns the size of the largest contiguous free
+    /// range, in bytes.
+    pub fn largest_free(&self) -> usize {
+        self.largest_free
+    }
+
+    /// Returns the number of live entries.
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Returns the histogram of free ranges.
+    ///
+    /// The `i`th element counts ranges whose size is in
+    /// `2^i..2^(i + 1)` bytes.
+    pub fn free_runs(&self) -> &[usize] {
+        &self.free_runs
+    }
+
+    /// Checks whether an entry of `size` bytes would fit
+    /// in a free run without growing the allocation.
+    ///
+    /// Alignment is not taken into account, so an aligned
+    /// entry may not fit even if this returns `true`. Under
+    /// `VarPolicy::SizeClass`, this is conservative for
+    /// entries smaller than a block, which may still fit in
+    /// a partially used slab. Under `VarPolicy::Buddy`, the
+    /// entry needs a single free block of its rounded size,
+    /// which a free run of `size` bytes may not contain.
+    pub fn fits(&self, size: usize) -> bool {
+        size <= self.largest_free
+    }
+
+    fn add_free_run(&mut self, size: usize) {
+        if size == 0 {
+            return;
+        }
+        let i = size.ilog2() as usize;
+        if i >= self.free_runs.len() {
+            self.free_runs.resize(i + 1, 0);
+        }
+        self.free_runs[i] += 1;
+        self.largest_free = self.largest_free.max(size);
+    }
+}
+
 /// Buffer for storing data of variable size.
 #[derive(Debug)]
 pub struct VarBuf<T: VarAlloc> {
     ptr: NonNull<()>,
     alloc: T,
-    bits: BitVec<u32>,
+    bits: AtomicBitVec<AtomicU32>,
+    policy: Policy,
+    // Number of live entries.
+    entries: AtomicUsize,
+    // Number of bytes held by live entries.
+    used: AtomicUsize,
+}
+
+/// State of the [`VarPolicy`] in use.
+///
+/// `Linear` and `SizeClass` track `VarAlloc::STRIDE`-sized
+/// blocks with `VarBuf::bits`, whereas `Buddy` only uses it
+/// to track the length of the allocation.
+#[derive(Debug)]
+enum Policy {
+    Linear,
+    SizeClass(Mutex<Slabs>),
+    Buddy(Mutex<Buddy>),
 }
 
+// NOTE: Shared references only allow reading entries'
+// data and allocating/freeing entries, which updates
+// `bits` atomically and the policy's state under a lock.
+unsafe impl<T: VarAlloc + Send> Send for VarBuf<T> {}
+unsafe impl<T: VarAlloc + Sync> Sync for VarBuf<T> {}
+
 impl<T: VarAlloc> VarBuf<T> {
     const BIT_N: usize = 32;
 
@@ -112,80 +287,163 @@ impl<T: VarAlloc> VarBuf<T> {
                 let n = (size / T::STRIDE + Self::BIT_N - 1) / Self::BIT_N;
                 let size = n * Self::BIT_N * T::STRIDE;
                 if let Ok(ptr) = alloc.grow(size) {
-                    (ptr, BitVec::with_count_words(n))
+                    (ptr, AtomicBitVec::with_count_words(n))
                 } else if let Ok(ptr) = alloc.shrink(0) {
-                    (ptr, BitVec::new())
+                    (ptr, AtomicBitVec::new())
                 } else {
                     panic!("failed to set VarAlloc");
                 }
             } else {
                 (
                     alloc.grow(size).unwrap(),
-                    BitVec::with_count_words(size / T::STRIDE / Self::BIT_N),
+                    AtomicBitVec::with_count_words(size / T::STRIDE / Self::BIT_N),
                 )
             }
         } else {
-            (NonNull::dangling(), BitVec::new())
+            (NonNull::dangling(), AtomicBitVec::new())
+        };
+        let policy = match T::POLICY {
+            VarPolicy::Linear => Policy::Linear,
+            VarPolicy::SizeClass => Policy::SizeClass(Mutex::new(Slabs::new(T::STRIDE))),
+            VarPolicy::Buddy => {
+                let mut buddy = Buddy::new(T::STRIDE);
+                buddy.add_range(0, bits.len() * T::STRIDE);
+                Policy::Buddy(Mutex::new(buddy))
+            }
         };
-        Self { ptr, alloc, bits }
+        Self {
+            ptr,
+            alloc,
+            bits,
+            policy,
+            entries: AtomicUsize::new(0),
+            used: AtomicUsize::new(0),
+        }
     }
 
-    /// Allocates an entry.
-    pub fn alloc(&mut self, size: usize) -> io::Result<VarEntry> {
+    /// Allocates an entry without growing the allocation.
+    ///
+    /// The entry's offset will be a multiple of `align`,
+    /// which must be a power of two.
+    ///
+    /// Unlike `alloc`, this method can be called from
+    /// several threads at once. It returns `None` if
+    /// there is not enough free space.
+    pub fn try_alloc(&self, size: usize, align: usize) -> Option<VarEntry> {
         if size == 0 {
-            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+            return None;
+        }
+        debug_assert!(align.is_power_of_two());
+        let entry = self.place(size, align)?;
+        self.entries.fetch_add(1, Relaxed);
+        self.used.fetch_add(entry.size, Relaxed);
+        Some(entry)
+    }
+
+    /// Finds room for an entry as the policy dictates.
+    fn place(&self, size: usize, align: usize) -> Option<VarEntry> {
+        match self.policy {
+            Policy::SizeClass(ref slabs) => {
+                let mut slabs = slabs.lock().unwrap();
+                if let Some(class) = slabs.class(size, align) {
+                    return slabs.alloc(class, &self.bits).map(|offset| VarEntry {
+                        offset,
+                        size: class,
+                    });
+                }
+            }
+            Policy::Buddy(ref buddy) => {
+                let mut buddy = buddy.lock().unwrap();
+                let size = buddy.block(size, align);
+                return buddy.alloc(size).map(|offset| VarEntry { offset, size });
+            }
+            Policy::Linear => (),
         }
         // Enforce alignment at entries' boundaries.
         let size = (size + T::STRIDE - 1) & !(T::STRIDE - 1);
-        let n = size / T::STRIDE;
+        self.bits
+            .find_aligned_and_set(size / T::STRIDE, align / T::STRIDE)
+            .map(|idx| VarEntry {
+                offset: idx * T::STRIDE,
+                size,
+            })
+    }
 
-        let idx = if let Some(x) = self.bits.find_contiguous(n) {
-            x
-        } else {
-            let needed_n = (n + Self::BIT_N - 1) & !(Self::BIT_N - 1);
-            let needed_size = needed_n * T::STRIDE;
-            let cur_n = self.bits.len();
-            let cur_size = cur_n * T::STRIDE;
-
-            let min_size = cur_size + needed_size;
-            let max_size = std::cmp::max(min_size, cur_size * 2);
-
-            'done: {
-                if max_size > min_size {
-                    let size = max_size;
-                    if let Ok(ptr) = self.alloc.grow(size) {
-                        self.ptr = ptr;
-                        break 'done self
-                            .bits
-                            .grow((size - cur_size) / T::STRIDE / Self::BIT_N)
-                            .unwrap();
-                    }
+    /// Allocates an entry.
+    ///
+    /// The entry's offset will be a multiple of `align`,
+    /// which must be a power of two.
+    pub fn alloc(&mut self, size: usize, align: usize) -> io::Result<VarEntry> {
+        if size == 0 || !align.is_power_of_two() {
+            return Err(io::Error::from(io::ErrorKind::InvalidInput));
+        }
+        if let Some(x) = self.try_alloc(size, align) {
+            return Ok(x);
+        }
+
+        let cur_n = self.bits.len();
+        let cur_size = cur_n * T::STRIDE;
+
+        // Number of bytes that must be appended for
+        // the entry to fit.
+        let needed_size = match self.policy {
+            Policy::Buddy(ref buddy) => {
+                let block = buddy.lock().unwrap().block(size, align);
+                cur_size.next_multiple_of(block) - cur_size + block
+            }
+            _ => {
+                let align = align.max(T::STRIDE);
+                cur_size.next_multiple_of(align) - cur_size + size.next_multiple_of(T::STRIDE)
+            }
+        };
+        let needed_n = needed_size
+            .div_ceil(T::STRIDE)
+            .next_multiple_of(Self::BIT_N);
+        let needed_size = needed_n * T::STRIDE;
+
+        let min_size = cur_size + needed_size;
+        let max_size = std::cmp::max(min_size, cur_size * 2);
+
+        let new_size = 'done: {
+            if max_size > min_size {
+                if let Ok(ptr) = self.alloc.grow(max_size) {
+                    self.ptr = ptr;
+                    break 'done max_size;
                 }
-                // We either failed to allocate `max_size` bytes,
-                // or this value is no greater than `min_size`.
-                // In any case, try to allocate the minimum.
-                self.ptr = self.alloc.grow(min_size)?;
-                self.bits.grow(needed_n / Self::BIT_N).unwrap()
             }
+            // We either failed to allocate `max_size` bytes,
+            // or this value is no greater than `min_size`.
+            // In any case, try to allocate the minimum.
+            self.ptr = self.alloc.grow(min_size)?;
+            min_size
         };
-
-        for i in 0..n {
-            self.bits.set(idx + i);
+        self.bits
+            .grow((new_size - cur_size) / T::STRIDE / Self::BIT_N);
+        if let Policy::Buddy(ref mut buddy) = self.policy {
+            buddy.get_mut().unwrap().add_range(cur_size, new_size);
         }
-        Ok(VarEntry {
-            offset: idx * T::STRIDE,
-            size,
-        })
+
+        Ok(self.try_alloc(size, align).unwrap())
     }
 
     /// Frees a given entry.
     ///
     /// This method does not resize the allocation.
-    pub fn dealloc(&mut self, entry: VarEntry) {
-        let start = entry.offset / T::STRIDE;
-        let end = start + entry.size / T::STRIDE;
-        for i in start..end {
-            self.bits.unset(i);
+    pub fn dealloc(&self, entry: VarEntry) {
+        self.entries.fetch_sub(1, Relaxed);
+        self.used.fetch_sub(entry.size, Relaxed);
+        match self.policy {
+            Policy::SizeClass(ref slabs) if entry.size < T::STRIDE => {
+                slabs
+                    .lock()
+                    .unwrap()
+                    .dealloc(entry.offset, entry.size, &self.bits);
+            }
+            Policy::Buddy(ref buddy) => buddy.lock().unwrap().dealloc(entry.offset, entry.size),
+            _ => {
+                let start = entry.offset / T::STRIDE;
+                self.bits.unset_range(start..start + entry.size / T::STRIDE);
+            }
         }
     }
 
@@ -197,7 +455,17 @@ impl<T: VarAlloc> VarBuf<T> {
     /// It returns the number of bytes freed, which may
     /// be zero.
     pub fn trim(&mut self) -> io::Result<usize> {
-        let n = self.bits.trim();
+        let n = match self.policy {
+            Policy::Buddy(ref mut buddy) => {
+                let buddy = buddy.get_mut().unwrap();
+                let size = self.bits.len() * T::STRIDE;
+                let n = buddy.trailing_free(size) / T::STRIDE / Self::BIT_N;
+                buddy.truncate(size - n * T::STRIDE * Self::BIT_N);
+                self.bits.shrink(n);
+                n
+            }
+            _ => self.bits.trim(),
+        };
         if n == 0 {
             return Ok(0);
         }
@@ -210,11 +478,111 @@ impl<T: VarAlloc> VarBuf<T> {
             }
             Err(err) => {
                 self.bits.grow(n);
+                if let Policy::Buddy(ref mut buddy) = self.policy {
+                    buddy
+                        .get_mut()
+                        .unwrap()
+                        .add_range(new_size, new_size + freed_size);
+                }
                 Err(err)
             }
         }
     }
 
+    /// Moves every entry towards the start of the buffer
+    /// and then trims the underlying allocation.
+    ///
+    /// Each move is reported to `f` in increasing order of
+    /// source offset. Contiguous entries may be reported as
+    /// a single move, so callers should patch their entries
+    /// with `VarEntry::relocate`.
+    ///
+    /// It returns the number of bytes freed, as `trim` does.
+    /// NOTE: The moves happen even if trimming fails.
+    /// Under `VarPolicy::Buddy`, moving blocks would break
+    /// their alignment, so this method only trims.
+    pub fn compact<F: FnMut(VarMove)>(&mut self, mut f: F) -> io::Result<usize> {
+        if let Policy::Buddy(_) = self.policy {
+            return self.trim();
+        }
+        let len = self.bits.len();
+        let mut dst = 0;
+        let mut pos = 0;
+        let mut moves = vec![];
+        while let Some(beg) = self.bits.find_set_from(pos) {
+            let end = self.bits.find_from(beg).unwrap_or(len);
+            if beg != dst {
+                let m = VarMove {
+                    src: beg * T::STRIDE,
+                    dst: dst * T::STRIDE,
+                    size: (end - beg) * T::STRIDE,
+                };
+                unsafe {
+                    let ptr = self.ptr.as_ptr().cast::<u8>();
+                    ptr::copy(ptr.add(m.src), ptr.add(m.dst), m.size);
+                }
+                moves.push(m);
+                f(m);
+            }
+            dst += end - beg;
+            pos = end;
+        }
+        if let Policy::SizeClass(ref mut slabs) = self.policy {
+            slabs.get_mut().unwrap().relocate(&moves);
+        }
+        self.bits.set_range(0..dst);
+        self.bits.unset_range(dst..len);
+        self.trim()
+    }
+
+    /// Computes usage statistics.
+    ///
+    /// Space left in partially used slabs (under
+    /// `VarPolicy::SizeClass`) is not part of any free run.
+    ///
+    /// NOTE: The values may be inconsistent if entries are
+    /// allocated or freed concurrently.
+    pub fn stats(&self) -> VarStats {
+        let total = self.bits.len() * T::STRIDE;
+        let used = self.used.load(Relaxed);
+        let mut stats = VarStats {
+            total,
+            used,
+            free: total - used.min(total),
+            largest_free: 0,
+            entries: self.entries.load(Relaxed),
+            free_runs: vec![],
+        };
+        if let Policy::Buddy(ref buddy) = self.policy {
+            for x in buddy.lock().unwrap().free_runs() {
+                stats.add_free_run(x.len());
+            }
+        } else {
+            let len = self.bits.len();
+            let mut pos = 0;
+            while let Some(beg) = self.bits.find_from(pos) {
+                let end = self.bits.find_set_from(beg).unwrap_or(len);
+                stats.add_free_run((end - beg) * T::STRIDE);
+                pos = end;
+            }
+        }
+        stats
+    }
+
+    /// Returns the data of a given entry.
+    ///
+    /// NOTE: The allocation may live in memory that is slow
+    /// to read from (e.g., write-combined `gpu` memory).
+    pub fn read(&self, entry: &VarEntry) -> &[u8] {
+        debug_assert!(self.alloc.size() >= entry.offset + entry.size);
+        if entry.size == 0 {
+            return &[];
+        }
+        unsafe {
+            std::slice::from_raw_parts(self.ptr.as_ptr().cast::<u8>().add(entry.offset), entry.size)
+        }
+    }
+
     /// Copies data to a given entry.
     pub fn copy(&mut self, data: &[u8], entry: &VarEntry) {
         debug_assert_ne!(self.alloc.size(), 0);
@@ -261,479 +629,1077 @@ impl<T: VarAlloc> Drop for VarBuf<T> {
 mod tests {
     use super::*;
 
-    #[derive(Debug)]
-    struct TestAlloc(Vec<u8>);
+    macro_rules! def_test_alloc {
+        ($($t:ident, $stride:literal, $policy:ident)*) => ($(
+            #[derive(Debug)]
+            struct $t(Vec<u8>);
 
-    impl VarAlloc for TestAlloc {
-        const STRIDE: usize = 4;
+            impl VarAlloc for $t {
+                const STRIDE: usize = $stride;
+                const POLICY: VarPolicy = VarPolicy::$policy;
 
-        fn grow(&mut self, new_size: usize) -> io::Result<NonNull<()>> {
-            if new_size > self.0.len() {
-                self.0.resize(new_size, 0);
-            }
-            Ok(NonNull::new(self.0.as_mut_ptr().cast()).unwrap())
-        }
+                fn grow(&mut self, new_size: usize) -> io::Result<NonNull<()>> {
+                    if new_size > self.0.len() {
+                        self.0.resize(new_size, 0);
+                    }
+                    Ok(NonNull::new(self.0.as_mut_ptr().cast()).unwrap())
+                }
 
-        fn shrink(&mut self, new_size: usize) -> io::Result<NonNull<()>> {
-            if new_size < self.0.len() {
-                self.0.resize(new_size, 0);
+                fn shrink(&mut self, new_size: usize) -> io::Result<NonNull<()>> {
+                    if new_size < self.0.len() {
+                        self.0.resize(new_size, 0);
+                    }
+                    Ok(NonNull::new(self.0.as_mut_ptr().cast()).unwrap())
+                }
+
+                fn size(&self) -> usize {
+                    self.0.len()
+                }
             }
-            Ok(NonNull::new(self.0.as_mut_ptr().cast()).unwrap())
-        }
+        )*)
+    }
+
+    def_test_alloc! {
+        TestAlloc, 4, Linear
+        LinearAlloc, 64, Linear
+        SizeClassAlloc, 64, SizeClass
+        BuddyAlloc, 64, Buddy
+    }
 
-        fn size(&self) -> usize {
-            self.0.len()
+    // Picks the value that a test expects under `T::POLICY`.
+    fn by_policy<T: VarAlloc>(linear: usize, size_class: usize, buddy: usize) -> usize {
+        match T::POLICY {
+            VarPolicy::Linear => linear,
+            VarPolicy::SizeClass => size_class,
+            VarPolicy::Buddy => buddy,
         }
     }
 
     impl<T: VarAlloc> VarBuf<T> {
-        fn assert(&self, alloc_size: usize, rem_bits: usize) {
+        // Number of free blocks.
+        //
+        // NOTE: `VarPolicy::Buddy` does not use the bits
+        // to keep track of entries, so its blocks are only
+        // free if its free runs cover them.
+        fn rem(&self) -> usize {
+            match self.policy {
+                Policy::Buddy(ref buddy) => (buddy.lock().unwrap().free_runs().iter())
+                    .map(|x| (x.end / T::STRIDE).saturating_sub(x.start.div_ceil(T::STRIDE)))
+                    .sum(),
+                _ => self.bits.rem(),
+            }
+        }
+
+        fn assert(&self, alloc_size: usize, rem: usize) {
             assert_eq!(alloc_size, self.alloc.size());
-            assert_eq!(rem_bits, self.bits.rem());
+            assert_eq!(rem, self.rem());
             assert_eq!(self.alloc.size() / T::STRIDE, self.bits.len());
         }
+
+        fn assert_trim(&mut self, size: usize) {
+            assert_eq!(size, self.trim().unwrap());
+        }
     }
 
     impl VarEntry {
-        fn assert(&self, v: &VarBuf<TestAlloc>) {
-            assert_eq!(self.offset % TestAlloc::STRIDE, 0);
-            assert_eq!(self.size % TestAlloc::STRIDE, 0);
+        fn assert<T: VarAlloc>(&self, v: &VarBuf<T>) {
             assert_ne!(self.size, 0);
             assert!(self.range().end <= v.alloc.size());
-            let start = self.offset / TestAlloc::STRIDE;
-            let end = start + self.size / TestAlloc::STRIDE;
+            match v.policy {
+                Policy::Buddy(ref buddy) => {
+                    assert!(self.size.is_power_of_two());
+                    assert_eq!(self.offset % self.size, 0);
+                    for x in buddy.lock().unwrap().free_runs() {
+                        assert!(x.end <= self.offset || self.range().end <= x.start);
+                    }
+                    return;
+                }
+                Policy::SizeClass(_) if self.size < T::STRIDE => {
+                    assert!(self.size.is_power_of_two());
+                    assert_eq!(self.offset % self.size, 0);
+                }
+                _ => {
+                    assert_eq!(self.offset % T::STRIDE, 0);
+                    assert_eq!(self.size % T::STRIDE, 0);
+                }
+            }
+            let start = self.offset / T::STRIDE;
+            let end = self.range().end.div_ceil(T::STRIDE);
             for i in start..end {
                 assert!(v.bits.is_set(i));
             }
         }
     }
 
-    #[test]
-    fn new() {
-        let v = VarBuf::new(TestAlloc(vec![]));
+    // Scales `size`, given in bytes of `TestAlloc`, so that
+    // every test allocator uses the same number of blocks.
+    fn bytes<T: VarAlloc>(size: usize) -> usize {
+        size * T::STRIDE / TestAlloc::STRIDE
+    }
+
+    fn new_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let v = VarBuf::new(new(vec![]));
         v.assert(0, 0);
 
-        let v = VarBuf::new(TestAlloc(vec![0]));
-        v.assert(TestAlloc::STRIDE * 32, 32);
+        let v = VarBuf::new(new(vec![0]));
+        v.assert(T::STRIDE * 32, 32);
 
-        let v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE]));
-        v.assert(TestAlloc::STRIDE * 32, 32);
+        let v = VarBuf::new(new(vec![0; T::STRIDE]));
+        v.assert(T::STRIDE * 32, 32);
 
-        let v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 31]));
-        v.assert(TestAlloc::STRIDE * 32, 32);
+        let v = VarBuf::new(new(vec![0; T::STRIDE * 31]));
+        v.assert(T::STRIDE * 32, 32);
 
-        let v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 32]));
-        v.assert(TestAlloc::STRIDE * 32, 32);
+        let v = VarBuf::new(new(vec![0; T::STRIDE * 32]));
+        v.assert(T::STRIDE * 32, 32);
 
-        let v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 32 + 1]));
-        v.assert(TestAlloc::STRIDE * 64, 64)
+        let v = VarBuf::new(new(vec![0; T::STRIDE * 32 + 1]));
+        v.assert(T::STRIDE * 64, 64)
     }
 
-    #[test]
-    fn alloc0() {
-        let mut v = VarBuf::new(TestAlloc(vec![]));
+    fn alloc0_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![]));
 
-        let x = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 31);
+        let x = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(T::STRIDE * 32, 31);
         x.assert(&v);
 
-        let x = v.alloc(2).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 30);
+        let x = v.alloc(bytes::<T>(2), 1).unwrap();
+        v.assert(T::STRIDE * 32, by_policy::<T>(30, 30, 31));
         x.assert(&v);
 
-        let x = v.alloc(116).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 1);
+        let x = v.alloc(bytes::<T>(116), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(32, 32, 64),
+            by_policy::<T>(1, 1, 31),
+        );
         x.assert(&v);
 
-        let x = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 0);
+        let x = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(32, 32, 64),
+            by_policy::<T>(0, 1, 31),
+        );
         x.assert(&v);
 
-        let x = v.alloc(3).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 31);
+        let x = v.alloc(bytes::<T>(3), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(31, 0, 30),
+        );
         x.assert(&v);
     }
 
-    #[test]
-    fn alloc() {
-        let mut v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 2 * 32]));
+    fn alloc_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![0; T::STRIDE * 2 * 32]));
 
-        let x = v.alloc(2).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 63);
+        let x = v.alloc(bytes::<T>(2), 1).unwrap();
+        v.assert(T::STRIDE * 64, 63);
         x.assert(&v);
 
-        let x = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 62);
+        let x = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(62, 62, 63));
         x.assert(&v);
 
-        let x = v.alloc(20).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 57);
+        let x = v.alloc(bytes::<T>(20), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(57, 57, 55));
         x.assert(&v);
 
-        let x = v.alloc(100).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 32);
+        let x = v.alloc(bytes::<T>(100), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(32, 32, 23));
         x.assert(&v);
 
-        let x = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 31);
+        let x = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(31, 31, 22));
         x.assert(&v);
 
-        let x = v.alloc(3).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 30);
+        let x = v.alloc(bytes::<T>(3), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(30, 30, 21));
         x.assert(&v);
 
-        let x = v.alloc(25).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 23);
+        let x = v.alloc(bytes::<T>(25), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(23, 23, 13));
         x.assert(&v);
 
-        let x = v.alloc(24).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 17);
+        let x = v.alloc(bytes::<T>(24), 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(17, 17, 5));
         x.assert(&v);
 
-        let x = v.alloc(26).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 10);
+        let x = v.alloc(bytes::<T>(26), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 64, 128),
+            by_policy::<T>(10, 10, 61),
+        );
         x.assert(&v);
 
-        let x = v.alloc(41).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 63);
+        let x = v.alloc(bytes::<T>(41), 1).unwrap();
+        v.assert(T::STRIDE * 128, by_policy::<T>(63, 63, 45));
         x.assert(&v);
 
-        let x = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 62);
+        let x = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(T::STRIDE * 128, by_policy::<T>(62, 62, 44));
         x.assert(&v);
 
-        let x = v.alloc(10).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 59);
+        let x = v.alloc(bytes::<T>(10), 1).unwrap();
+        v.assert(T::STRIDE * 128, by_policy::<T>(59, 59, 40));
         x.assert(&v);
 
-        let x = v.alloc(200).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 9);
+        let x = v.alloc(bytes::<T>(200), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(9, 9, 104),
+        );
         x.assert(&v);
 
-        let x = v
-            .alloc(TestAlloc::STRIDE * VarBuf::<TestAlloc>::BIT_N * 3)
-            .unwrap();
-        v.assert(TestAlloc::STRIDE * 256, 9 + 32);
+        let x = v.alloc(T::STRIDE * VarBuf::<T>::BIT_N * 3, 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(256, 256, 512),
+            by_policy::<T>(9 + 32, 41, 232),
+        );
         x.assert(&v);
 
-        let x = v.alloc(v.alloc.size()).unwrap();
-        v.assert(TestAlloc::STRIDE * 512, 41);
+        let x = v.alloc(v.alloc.size(), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(512, 512, 1024),
+            by_policy::<T>(41, 41, 232),
+        );
         x.assert(&v);
 
-        let x = v.alloc(v.alloc.size() + 1).unwrap();
-        v.assert(TestAlloc::STRIDE * (512 + 512 + 32), 41 + 31);
+        let x = v.alloc(v.alloc.size() + 1, 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(512 + 512 + 32, 1056, 4096),
+            by_policy::<T>(41 + 31, 72, 1256),
+        );
         x.assert(&v);
 
-        let x = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 1056, 71);
+        let x = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(1056, 1056, 4096),
+            by_policy::<T>(71, 72, 1256),
+        );
         x.assert(&v);
 
-        let x = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 1056, 70);
+        let x = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(1056, 1056, 4096),
+            by_policy::<T>(70, 71, 1255),
+        );
         x.assert(&v);
 
-        let x = v.alloc(15).unwrap();
-        v.assert(TestAlloc::STRIDE * 1056, 66);
+        let x = v.alloc(bytes::<T>(15), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(1056, 1056, 4096),
+            by_policy::<T>(66, 67, 1251),
+        );
         x.assert(&v);
 
-        let x = v.alloc(v.alloc.size() - TestAlloc::STRIDE).unwrap();
-        v.assert(TestAlloc::STRIDE * (1056 * 2), 67);
+        let x = v.alloc(v.alloc.size() - T::STRIDE, 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(1056 * 2, 2112, 8192),
+            by_policy::<T>(67, 68, 1251),
+        );
         x.assert(&v);
 
-        let x = v.alloc(v.alloc.size() + v.alloc.size() / 2).unwrap();
-        v.assert(TestAlloc::STRIDE * (2112 + 2112 + 1056), 67);
+        let x = v.alloc(v.alloc.size() + v.alloc.size() / 2, 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(2112 + 2112 + 1056, 5280, 32768),
+            by_policy::<T>(67, 68, 9443),
+        );
         x.assert(&v);
 
-        let x = v.alloc(3).unwrap();
-        v.assert(TestAlloc::STRIDE * 5280, 66);
+        let x = v.alloc(bytes::<T>(3), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(5280, 5280, 32768),
+            by_policy::<T>(66, 67, 9442),
+        );
         x.assert(&v);
 
-        let x = v.alloc(16).unwrap();
-        v.assert(TestAlloc::STRIDE * 5280, 62);
+        let x = v.alloc(bytes::<T>(16), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(5280, 5280, 32768),
+            by_policy::<T>(62, 63, 9438),
+        );
         x.assert(&v);
     }
 
-    #[test]
-    fn dealloc0() {
-        let mut v = VarBuf::new(TestAlloc(vec![]));
+    fn dealloc0_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![]));
 
-        let x1 = v.alloc(1).unwrap();
-        let x2 = v.alloc(2).unwrap();
-        let x3 = v.alloc(116).unwrap();
-        let x4 = v.alloc(1).unwrap();
-        let x5 = v.alloc(3).unwrap();
+        let x1 = v.alloc(bytes::<T>(1), 1).unwrap();
+        let x2 = v.alloc(bytes::<T>(2), 1).unwrap();
+        let x3 = v.alloc(bytes::<T>(116), 1).unwrap();
+        let x4 = v.alloc(bytes::<T>(1), 1).unwrap();
+        let x5 = v.alloc(bytes::<T>(3), 1).unwrap();
 
-        v.assert(TestAlloc::STRIDE * 64, 31);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(31, 0, 30),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 64, 32);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(32, 0, 30),
+        );
         v.dealloc(x3);
-        v.assert(TestAlloc::STRIDE * 64, 61);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(61, 29, 62),
+        );
         v.dealloc(x5);
-        v.assert(TestAlloc::STRIDE * 64, 62);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(62, 30, 63),
+        );
         v.dealloc(x4);
-        v.assert(TestAlloc::STRIDE * 64, 63);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(63, 31, 63),
+        );
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 64, 64);
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 32, 64),
+            by_policy::<T>(64, 32, 64),
+        );
     }
 
-    #[test]
-    fn dealloc() {
-        let mut v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 2 * 32]));
-
-        let x1 = v.alloc(2).unwrap();
-        let x2 = v.alloc(1).unwrap();
-        let x3 = v.alloc(20).unwrap();
-        let x4 = v.alloc(100).unwrap();
-        let x5 = v.alloc(4).unwrap();
-        let x6 = v.alloc(3).unwrap();
-        let x7 = v.alloc(25).unwrap();
-        let x8 = v.alloc(24).unwrap();
-        let x9 = v.alloc(26).unwrap();
-        let x10 = v.alloc(41).unwrap();
-        let x11 = v.alloc(4).unwrap();
-        let x12 = v.alloc(10).unwrap();
-        let x13 = v.alloc(200).unwrap();
-
-        v.assert(TestAlloc::STRIDE * 128, 9);
+    fn dealloc_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![0; T::STRIDE * 2 * 32]));
+
+        let x1 = v.alloc(bytes::<T>(2), 1).unwrap();
+        let x2 = v.alloc(bytes::<T>(1), 1).unwrap();
+        let x3 = v.alloc(bytes::<T>(20), 1).unwrap();
+        let x4 = v.alloc(bytes::<T>(100), 1).unwrap();
+        let x5 = v.alloc(bytes::<T>(4), 1).unwrap();
+        let x6 = v.alloc(bytes::<T>(3), 1).unwrap();
+        let x7 = v.alloc(bytes::<T>(25), 1).unwrap();
+        let x8 = v.alloc(bytes::<T>(24), 1).unwrap();
+        let x9 = v.alloc(bytes::<T>(26), 1).unwrap();
+        let x10 = v.alloc(bytes::<T>(41), 1).unwrap();
+        let x11 = v.alloc(bytes::<T>(4), 1).unwrap();
+        let x12 = v.alloc(bytes::<T>(10), 1).unwrap();
+        let x13 = v.alloc(bytes::<T>(200), 1).unwrap();
+
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(9, 9, 104),
+        );
         v.dealloc(x11);
-        v.assert(TestAlloc::STRIDE * 128, 10);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(10, 10, 105),
+        );
         v.dealloc(x3);
-        v.assert(TestAlloc::STRIDE * 128, 15);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(15, 15, 113),
+        );
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 128, 16);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(16, 16, 113),
+        );
         v.dealloc(x12);
-        v.assert(TestAlloc::STRIDE * 128, 19);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(19, 19, 117),
+        );
         v.dealloc(x13);
-        v.assert(TestAlloc::STRIDE * 128, 69);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(69, 69, 181),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 128, 70);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(70, 70, 182),
+        );
         v.dealloc(x5);
-        v.assert(TestAlloc::STRIDE * 128, 71);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(71, 71, 183),
+        );
         v.dealloc(x9);
-        v.assert(TestAlloc::STRIDE * 128, 78);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(78, 78, 191),
+        );
         v.dealloc(x7);
-        v.assert(TestAlloc::STRIDE * 128, 85);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(85, 85, 199),
+        );
         v.dealloc(x10);
-        v.assert(TestAlloc::STRIDE * 128, 96);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(96, 96, 215),
+        );
         v.dealloc(x4);
-        v.assert(TestAlloc::STRIDE * 128, 121);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(121, 121, 247),
+        );
         v.dealloc(x6);
-        v.assert(TestAlloc::STRIDE * 128, 122);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(122, 122, 248),
+        );
         v.dealloc(x8);
-        v.assert(TestAlloc::STRIDE * 128, 128);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(128, 128, 256),
+        );
     }
 
-    #[test]
-    fn alloc_dealloc() {
-        let mut v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 2 * 32]));
-
-        let x1 = v.alloc(2).unwrap();
-        let x2 = v.alloc(1).unwrap();
-        let x3 = v.alloc(20).unwrap();
-        let x4 = v.alloc(100).unwrap();
-        let x5 = v.alloc(4).unwrap();
-        let x6 = v.alloc(3).unwrap();
-        let x7 = v.alloc(25).unwrap();
-        let x8 = v.alloc(24).unwrap();
-        let x9 = v.alloc(26).unwrap();
-        let x10 = v.alloc(41).unwrap();
-        let x11 = v.alloc(4).unwrap();
-        let x12 = v.alloc(10).unwrap();
-        let x13 = v.alloc(200).unwrap();
-
-        v.assert(TestAlloc::STRIDE * 128, 9);
+    fn alloc_dealloc_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![0; T::STRIDE * 2 * 32]));
+
+        let x1 = v.alloc(bytes::<T>(2), 1).unwrap();
+        let x2 = v.alloc(bytes::<T>(1), 1).unwrap();
+        let x3 = v.alloc(bytes::<T>(20), 1).unwrap();
+        let x4 = v.alloc(bytes::<T>(100), 1).unwrap();
+        let x5 = v.alloc(bytes::<T>(4), 1).unwrap();
+        let x6 = v.alloc(bytes::<T>(3), 1).unwrap();
+        let x7 = v.alloc(bytes::<T>(25), 1).unwrap();
+        let x8 = v.alloc(bytes::<T>(24), 1).unwrap();
+        let x9 = v.alloc(bytes::<T>(26), 1).unwrap();
+        let x10 = v.alloc(bytes::<T>(41), 1).unwrap();
+        let x11 = v.alloc(bytes::<T>(4), 1).unwrap();
+        let x12 = v.alloc(bytes::<T>(10), 1).unwrap();
+        let x13 = v.alloc(bytes::<T>(200), 1).unwrap();
+
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(9, 9, 104),
+        );
         v.dealloc(x11);
-        v.assert(TestAlloc::STRIDE * 128, 10);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(10, 10, 105),
+        );
         v.dealloc(x3);
-        v.assert(TestAlloc::STRIDE * 128, 15);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(15, 15, 113),
+        );
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 128, 16);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(16, 16, 113),
+        );
         v.dealloc(x12);
-        v.assert(TestAlloc::STRIDE * 128, 19);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(19, 19, 117),
+        );
         v.dealloc(x13);
-        v.assert(TestAlloc::STRIDE * 128, 69);
-
-        let x13 = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 68);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(69, 69, 181),
+        );
+
+        let x13 = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(68, 68, 180),
+        );
         v.dealloc(x13);
-        v.assert(TestAlloc::STRIDE * 128, 69);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(69, 69, 181),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 128, 70);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(70, 70, 182),
+        );
         v.dealloc(x5);
-        v.assert(TestAlloc::STRIDE * 128, 71);
-
-        let x5 = v.alloc(10).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 68);
-        let x1 = v.alloc(6).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 66);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(71, 71, 183),
+        );
+
+        let x5 = v.alloc(bytes::<T>(10), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(68, 68, 179),
+        );
+        let x1 = v.alloc(bytes::<T>(6), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(66, 66, 177),
+        );
 
         v.dealloc(x9);
-        v.assert(TestAlloc::STRIDE * 128, 73);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(73, 73, 185),
+        );
         v.dealloc(x7);
-        v.assert(TestAlloc::STRIDE * 128, 80);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(80, 80, 193),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 128, 82);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(82, 82, 195),
+        );
         v.dealloc(x5);
-        v.assert(TestAlloc::STRIDE * 128, 85);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(85, 85, 199),
+        );
 
-        let x1 = v.alloc(21).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 79);
+        let x1 = v.alloc(bytes::<T>(21), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(79, 79, 191),
+        );
 
         v.dealloc(x10);
-        v.assert(TestAlloc::STRIDE * 128, 90);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(90, 90, 207),
+        );
         v.dealloc(x4);
-        v.assert(TestAlloc::STRIDE * 128, 115);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(115, 115, 239),
+        );
         v.dealloc(x6);
-        v.assert(TestAlloc::STRIDE * 128, 116);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(116, 116, 240),
+        );
         v.dealloc(x8);
-        v.assert(TestAlloc::STRIDE * 128, 122);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(122, 122, 248),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 128, 128);
-
-        let x1 = v.alloc(1024).unwrap();
-        v.assert(TestAlloc::STRIDE * (128 + 256), 128);
-        let x2 = v.alloc(15).unwrap();
-        v.assert(TestAlloc::STRIDE * 384, 124);
-        let x3 = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 384, 123);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 256),
+            by_policy::<T>(128, 128, 256),
+        );
+
+        let x1 = v.alloc(bytes::<T>(1024), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128 + 256, 384, 256),
+            by_policy::<T>(128, 128, 0),
+        );
+        let x2 = v.alloc(bytes::<T>(15), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(384, 384, 512),
+            by_policy::<T>(124, 124, 252),
+        );
+        let x3 = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(384, 384, 512),
+            by_policy::<T>(123, 123, 251),
+        );
 
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 384, 127);
+        v.assert(
+            T::STRIDE * by_policy::<T>(384, 384, 512),
+            by_policy::<T>(127, 127, 255),
+        );
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 384, 383);
+        v.assert(
+            T::STRIDE * by_policy::<T>(384, 384, 512),
+            by_policy::<T>(383, 383, 511),
+        );
         v.dealloc(x3);
-        v.assert(TestAlloc::STRIDE * 384, 384);
+        v.assert(
+            T::STRIDE * by_policy::<T>(384, 384, 512),
+            by_policy::<T>(384, 384, 512),
+        );
+    }
+
+    fn try_alloc_with<T: VarAlloc + Sync>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![]));
+        assert!(v.try_alloc(bytes::<T>(1), 1).is_none());
+        let x = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.dealloc(x);
+        v.assert(T::STRIDE * 32, 32);
+        assert!(v.try_alloc(bytes::<T>(0), 1).is_none());
+        assert!(v.try_alloc(bytes::<T>(129), 1).is_none());
+        let entries: Vec<_> = std::thread::scope(|s| {
+            let v = &v;
+            let hs: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(move || {
+                        std::iter::from_fn(|| v.try_alloc(bytes::<T>(8), 1)).collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            hs.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(entries.len(), 16);
+        v.assert(T::STRIDE * 32, 0);
+        let mut offsets: Vec<_> = entries.iter().map(|x| x.offset()).collect();
+        offsets.sort_unstable();
+        assert!(offsets
+            .into_iter()
+            .eq((0..T::STRIDE * 32).step_by(bytes::<T>(8))));
+        for x in entries {
+            x.assert(&v);
+            v.dealloc(x);
+        }
+        v.assert(T::STRIDE * 32, 32);
+    }
+
+    fn compact_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(new(vec![]));
+        assert_eq!(v.compact(|_| panic!()).unwrap(), 0);
+        let mut entries: Vec<_> = (1..=24)
+            .map(|i| v.alloc(bytes::<T>(i * 3), 1).unwrap())
+            .collect();
+        v.assert(
+            T::STRIDE * by_policy::<T>(256, 256, 512),
+            by_policy::<T>(256 - 234, 22, 185),
+        );
+        for (i, x) in entries.iter().enumerate() {
+            v.copy(&vec![i as u8; x.size()], x);
+        }
+        // Drop every other entry.
+        let mut i = 0;
+        entries.retain(|x| {
+            i += 1;
+            if i % 2 == 1 {
+                v.dealloc(VarEntry {
+                    offset: x.offset,
+                    size: x.size,
+                });
+                false
+            } else {
+                true
+            }
+        });
+        let mut moves = vec![];
+        let n = v.compact(|x| moves.push(x)).unwrap();
+        assert_eq!(n, T::STRIDE * by_policy::<T>(128, 128, 160));
+        // `VarPolicy::Buddy` only trims the trailing blocks.
+        let buddy = matches!(T::POLICY, VarPolicy::Buddy);
+        assert_eq!(moves.is_empty(), buddy);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 352),
+            by_policy::<T>(128 - 120, 8, 178),
+        );
+        assert!(moves.windows(2).all(|x| x[0].src() < x[1].src()));
+        assert!(moves.iter().all(|x| x.dst() < x.src()));
+        let mut off = 0;
+        for x in &mut entries {
+            let old = x.offset();
+            assert_eq!(x.relocate(&moves), old != x.offset());
+            assert_eq!(x.offset(), if buddy { old } else { off });
+            off += x.size();
+            x.assert(&v);
+        }
+        for (x, i) in entries.iter().zip((2..=24).step_by(2)) {
+            assert!(v.read(x).iter().all(|&b| b == i - 1));
+        }
+        // Nothing left to move.
+        assert_eq!(v.compact(|_| panic!()).unwrap(), 0);
+    }
+
+    // Checks the invariants that every policy must uphold
+    // while replaying allocations of the other tests.
+    fn policy<T: VarAlloc>(alloc: fn(Vec<u8>) -> T) {
+        let mut v = VarBuf::new(alloc(vec![]));
+        let mut live: Vec<(VarEntry, usize, u8)> = vec![];
+        let sizes = [
+            2, 1, 20, 100, 4, 3, 25, 24, 26, 41, 4, 10, 200, 384, 1024, 1, 15, 3, 16,
+        ];
+        let mut seed = 0x2545f491u32;
+        let mut rand = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as usize
+        };
+        for i in 0..2000 {
+            if live.len() > 24 || (!live.is_empty() && rand() % 3 == 0) {
+                let (x, size, b) = live.swap_remove(rand() % live.len());
+                assert!(v.read(&x)[..size].iter().all(|&x| x == b));
+                v.dealloc(x);
+                continue;
+            }
+            let size = sizes[rand() % sizes.len()] * (1 + rand() % 4);
+            let align = 1 << (rand() % 9);
+            let x = v.alloc(size, align).unwrap();
+            assert_eq!(x.offset() % align, 0);
+            assert!(x.size() >= size);
+            assert!(x.range().end <= v.alloc.size());
+            for (y, ..) in &live {
+                assert!(x.range().end <= y.offset() || y.range().end <= x.offset());
+            }
+            v.copy(&vec![i as u8; size], &x);
+            live.push((x, size, i as u8));
+            if i % 97 == 0 {
+                v.trim().unwrap();
+                let s = v.stats();
+                assert_eq!(s.entries(), live.len());
+                assert_eq!(s.used(), live.iter().map(|x| x.0.size()).sum());
+                assert!(s.largest_free() <= s.free());
+            }
+        }
+        for (x, size, b) in live {
+            assert!(v.read(&x)[..size].iter().all(|&x| x == b));
+            v.dealloc(x);
+        }
+        v.trim().unwrap();
+        assert_eq!(v.alloc.size(), 0);
+        assert!(v.alloc(0, 1).is_err());
+        assert!(v.alloc(1, 3).is_err());
     }
 
     #[test]
-    fn trim() {
+    fn policies() {
+        policy(TestAlloc);
+        policy(LinearAlloc);
+        policy(SizeClassAlloc);
+        policy(BuddyAlloc);
+    }
+
+    #[test]
+    fn size_class() {
+        let mut v = VarBuf::new(SizeClassAlloc(vec![]));
+        // Small entries share blocks.
+        let xs: Vec<_> = (0..8).map(|_| v.alloc(6, 2).unwrap()).collect();
+        v.assert(64 * 32, 31);
+        assert!(xs.iter().map(|x| x.offset()).eq((0..64).step_by(8)));
+        let y = v.alloc(3, 1).unwrap();
+        assert_eq!((y.offset(), y.size()), (64, 4));
+        let z = v.alloc(64, 1).unwrap();
+        assert_eq!((z.offset(), z.size()), (128, 64));
+        v.assert(64 * 32, 29);
+        for x in xs {
+            v.dealloc(x);
+        }
+        v.assert(64 * 32, 30);
+        v.dealloc(y);
+        let mut moves = vec![];
+        assert_eq!(v.compact(|x| moves.push(x)).unwrap(), 0);
+        assert_eq!(
+            moves,
+            [VarMove {
+                src: 128,
+                dst: 0,
+                size: 64
+            }]
+        );
+        v.dealloc(VarEntry {
+            offset: 0,
+            size: 64,
+        });
+        assert_eq!(v.trim().unwrap(), 64 * 32);
+    }
+
+    #[test]
+    fn buddy() {
+        let mut v = VarBuf::new(BuddyAlloc(vec![]));
+        let x = v.alloc(3, 1).unwrap();
+        assert_eq!((x.offset(), x.size()), (0, 4));
+        let y = v.alloc(1, 1).unwrap();
+        assert_eq!((y.offset(), y.size()), (4, 1));
+        let z = v.alloc(100, 64).unwrap();
+        assert_eq!((z.offset(), z.size()), (128, 128));
+        let w = v.alloc(5, 1).unwrap();
+        assert_eq!((w.offset(), w.size()), (8, 8));
+        assert_eq!(v.alloc.size(), 64 * 32);
+        let u = v.alloc(64 * 32, 1).unwrap();
+        assert_eq!((u.offset(), u.size()), (64 * 32, 64 * 32));
+        assert_eq!(v.alloc.size(), 64 * 64);
+        v.dealloc(u);
+        assert_eq!(v.trim().unwrap(), 64 * 32);
+        for e in [x, y, z] {
+            v.dealloc(e);
+        }
+        assert_eq!(v.trim().unwrap(), 0);
+        v.dealloc(w);
+        // Everything merges back into a single block.
+        assert_eq!(v.trim().unwrap(), 64 * 32);
+    }
+
+    #[test]
+    fn stats() {
+        let mut v = VarBuf::new(TestAlloc(vec![]));
+        let s = v.stats();
+        assert_eq!((s.total(), s.used(), s.free(), s.entries()), (0, 0, 0, 0));
+        assert_eq!((s.largest_free(), s.free_runs()), (0, &[][..]));
+        assert!(!s.fits(1));
+
+        let x1 = v.alloc(4, 1).unwrap();
+        let x2 = v.alloc(9, 1).unwrap();
+        let x3 = v.alloc(40, 1).unwrap();
+        let s = v.stats();
+        assert_eq!(
+            (s.total(), s.used(), s.free(), s.entries()),
+            (128, 56, 72, 3)
+        );
+        assert_eq!(s.largest_free(), 72);
+        assert_eq!(s.free_runs(), [0, 0, 0, 0, 0, 0, 1]);
+        v.dealloc(x2);
+        let s = v.stats();
+        assert_eq!((s.used(), s.free(), s.entries()), (44, 84, 2));
+        assert_eq!(s.free_runs(), [0, 0, 0, 1, 0, 0, 1]);
+        assert!(s.fits(72) && !s.fits(73));
+        v.dealloc(x1);
+        v.dealloc(x3);
+        let s = v.stats();
+        assert_eq!((s.used(), s.free(), s.entries()), (0, 128, 0));
+        assert_eq!(s.free_runs(), [0, 0, 0, 0, 0, 0, 0, 1]);
+
+        let mut v = VarBuf::new(BuddyAlloc(vec![]));
+        let x1 = v.alloc(3, 1).unwrap();
+        let x2 = v.alloc(8, 1).unwrap();
+        let s = v.stats();
+        assert_eq!((s.total(), s.used(), s.entries()), (2048, 12, 2));
+        // 4..8 and 16..2048.
+        assert_eq!(s.largest_free(), 2032);
+        assert_eq!(s.free_runs().iter().sum::<usize>(), 2);
+        v.dealloc(x1);
+        v.dealloc(x2);
+        let s = v.stats();
+        assert_eq!((s.largest_free(), s.free(), s.entries()), (2048, 2048, 0));
+    }
+
+    fn trim_with<T: VarAlloc>(new: fn(Vec<u8>) -> T) {
         // TODO: Test that `trim` does the right thing
         // when `shrink` fails.
 
-        let mut v = VarBuf::new(TestAlloc(vec![]));
-        assert_eq!(v.trim().unwrap(), 0);
+        let mut v = VarBuf::new(new(vec![]));
+        v.assert_trim(0);
         v.assert(0, 0);
 
-        let x1 = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 31);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 32, 31);
+        let x1 = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(T::STRIDE * 32, 31);
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 32, 31);
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 32, 32);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 32);
+        v.assert(T::STRIDE * 32, 32);
+        v.assert_trim(T::STRIDE * 32);
         v.assert(0, 0);
 
-        let x1 = v.alloc(TestAlloc::STRIDE * 64).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 0);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 64, 0);
+        let x1 = v.alloc(T::STRIDE * 64, 1).unwrap();
+        v.assert(T::STRIDE * 64, 0);
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 64, 0);
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 64, 64);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 64);
+        v.assert(T::STRIDE * 64, 64);
+        v.assert_trim(T::STRIDE * 64);
         v.assert(0, 0);
 
-        let x1 = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 31);
-        let x2 = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 30);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 32, 30);
+        let x1 = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(T::STRIDE * 32, 31);
+        let x2 = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(T::STRIDE * 32, by_policy::<T>(30, 31, 31));
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 32, by_policy::<T>(30, 31, 31));
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 32, 31);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 32, 31);
+        v.assert(T::STRIDE * 32, 31);
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 32, 31);
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 32, 32);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 32);
+        v.assert(T::STRIDE * 32, 32);
+        v.assert_trim(T::STRIDE * 32);
         v.assert(0, 0);
 
-        let x1 = v.alloc(TestAlloc::STRIDE + 1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 30);
-        let x2 = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 29);
-        let x3 = v.alloc(TestAlloc::STRIDE * 2 + 1).unwrap();
-        v.assert(TestAlloc::STRIDE * 32, 26);
-        assert_eq!(v.trim().unwrap(), 0);
+        let x1 = v.alloc(T::STRIDE + 1, 1).unwrap();
+        v.assert(T::STRIDE * 32, 30);
+        let x2 = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(T::STRIDE * 32, 29);
+        let x3 = v.alloc(T::STRIDE * 2 + 1, 1).unwrap();
+        v.assert(T::STRIDE * 32, by_policy::<T>(26, 26, 25));
+        v.assert_trim(0);
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 32, 27);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 32, 27);
+        v.assert(T::STRIDE * 32, by_policy::<T>(27, 27, 26));
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 32, by_policy::<T>(27, 27, 26));
         v.dealloc(x1);
-        v.assert(TestAlloc::STRIDE * 32, 29);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 32, 29);
+        v.assert(T::STRIDE * 32, by_policy::<T>(29, 29, 28));
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 32, by_policy::<T>(29, 29, 28));
         v.dealloc(x3);
-        v.assert(TestAlloc::STRIDE * 32, 32);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 32);
+        v.assert(T::STRIDE * 32, 32);
+        v.assert_trim(T::STRIDE * 32);
         v.assert(0, 0);
 
-        let x1 = v.alloc(TestAlloc::STRIDE).unwrap();
-        let x2 = v.alloc(TestAlloc::STRIDE).unwrap();
-        let x3 = v.alloc(TestAlloc::STRIDE * 30).unwrap();
+        let x1 = v.alloc(T::STRIDE, 1).unwrap();
+        let x2 = v.alloc(T::STRIDE, 1).unwrap();
+        let x3 = v.alloc(T::STRIDE * 30, 1).unwrap();
         v.dealloc(x2);
-        v.assert(TestAlloc::STRIDE * 32, 1);
-        assert_eq!(v.trim().unwrap(), 0);
-        let x4 = v.alloc(TestAlloc::STRIDE + 1).unwrap();
-        v.assert(TestAlloc::STRIDE * 64, 31);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 64, 31);
+        v.assert(
+            T::STRIDE * by_policy::<T>(32, 32, 64),
+            by_policy::<T>(1, 1, 31),
+        );
+        v.assert_trim(0);
+        let x4 = v.alloc(T::STRIDE + 1, 1).unwrap();
+        v.assert(T::STRIDE * 64, by_policy::<T>(31, 31, 29));
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 64, by_policy::<T>(31, 31, 29));
         v.dealloc(x1);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 64, 32);
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 64, by_policy::<T>(32, 32, 30));
         v.dealloc(x3);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 64, 62);
+        v.assert_trim(T::STRIDE * by_policy::<T>(0, 0, 32));
+        v.assert(
+            T::STRIDE * by_policy::<T>(64, 64, 32),
+            by_policy::<T>(62, 62, 30),
+        );
         v.dealloc(x4);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 64);
+        v.assert_trim(T::STRIDE * by_policy::<T>(64, 64, 32));
         v.assert(0, 0);
 
-        let x1 = v.alloc(TestAlloc::STRIDE * 128).unwrap();
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 128, 0);
+        let x1 = v.alloc(T::STRIDE * 128, 1).unwrap();
+        v.assert_trim(0);
+        v.assert(T::STRIDE * 128, 0);
         v.dealloc(x1);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 128);
+        v.assert_trim(T::STRIDE * 128);
         v.assert(0, 0);
 
-        let x1 = v.alloc(TestAlloc::STRIDE * 96).unwrap();
-        let x2 = v.alloc(4).unwrap();
-        v.assert(TestAlloc::STRIDE * 192, 95);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * (96 - 32));
-        v.assert(TestAlloc::STRIDE * 128, 31);
+        let x1 = v.alloc(T::STRIDE * 96, 1).unwrap();
+        let x2 = v.alloc(bytes::<T>(4), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(192, 192, 256),
+            by_policy::<T>(95, 95, 127),
+        );
+        v.assert_trim(T::STRIDE * by_policy::<T>(96 - 32, 64, 96));
+        v.assert(T::STRIDE * by_policy::<T>(128, 128, 160), 31);
         v.dealloc(x1);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 128, 127);
-        let x3 = v.alloc(TestAlloc::STRIDE * 128).unwrap();
-        v.assert(TestAlloc::STRIDE * 256, 127);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 256, 127);
+        v.assert_trim(0);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 160),
+            by_policy::<T>(127, 127, 159),
+        );
+        let x3 = v.alloc(T::STRIDE * 128, 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(256, 256, 160),
+            by_policy::<T>(127, 127, 31),
+        );
+        v.assert_trim(0);
+        v.assert(
+            T::STRIDE * by_policy::<T>(256, 256, 160),
+            by_policy::<T>(127, 127, 31),
+        );
         v.dealloc(x3);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 128);
-        v.assert(TestAlloc::STRIDE * 128, 127);
-        let x4 = v.alloc(1).unwrap();
-        v.assert(TestAlloc::STRIDE * 128, 126);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 128, 126);
+        v.assert_trim(T::STRIDE * by_policy::<T>(128, 128, 0));
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 160),
+            by_policy::<T>(127, 127, 159),
+        );
+        let x4 = v.alloc(bytes::<T>(1), 1).unwrap();
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 160),
+            by_policy::<T>(126, 126, 158),
+        );
+        v.assert_trim(0);
+        v.assert(
+            T::STRIDE * by_policy::<T>(128, 128, 160),
+            by_policy::<T>(126, 126, 158),
+        );
         v.dealloc(x2);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * (128 - 32));
-        v.assert(TestAlloc::STRIDE * 32, 31);
+        v.assert_trim(T::STRIDE * by_policy::<T>(128 - 32, 96, 0));
+        v.assert(
+            T::STRIDE * by_policy::<T>(32, 32, 160),
+            by_policy::<T>(31, 31, 159),
+        );
         v.dealloc(x4);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 32);
+        v.assert_trim(T::STRIDE * by_policy::<T>(32, 32, 160));
         v.assert(0, 0);
 
-        let mut v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 128 + 1]));
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 160);
+        let mut v = VarBuf::new(new(vec![0; T::STRIDE * 128 + 1]));
+        v.assert_trim(T::STRIDE * 160);
         v.assert(0, 0);
 
-        let mut v = VarBuf::new(TestAlloc(vec![0; TestAlloc::STRIDE * 128 + 1]));
-        let x1 = v.alloc(TestAlloc::STRIDE * 66).unwrap();
-        let x2 = v.alloc(2).unwrap();
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * (160 - 96));
-        v.assert(TestAlloc::STRIDE * 96, 29);
+        let mut v = VarBuf::new(new(vec![0; T::STRIDE * 128 + 1]));
+        let x1 = v.alloc(T::STRIDE * 66, 1).unwrap();
+        let x2 = v.alloc(bytes::<T>(2), 1).unwrap();
+        v.assert_trim(T::STRIDE * by_policy::<T>(160 - 96, 64, 0));
+        v.assert(
+            T::STRIDE * by_policy::<T>(96, 96, 160),
+            by_policy::<T>(29, 29, 31),
+        );
         v.dealloc(x1);
-        assert_eq!(v.trim().unwrap(), 0);
-        v.assert(TestAlloc::STRIDE * 96, 95);
+        v.assert_trim(0);
+        v.assert(
+            T::STRIDE * by_policy::<T>(96, 96, 160),
+            by_policy::<T>(95, 95, 159),
+        );
         v.dealloc(x2);
-        assert_eq!(v.trim().unwrap(), TestAlloc::STRIDE * 96);
+        v.assert_trim(T::STRIDE * by_policy::<T>(96, 96, 160));
         v.assert(0, 0);
     }
+
+    #[test]
+    fn new() {
+        new_with(TestAlloc);
+        new_with(LinearAlloc);
+        new_with(SizeClassAlloc);
+        new_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn alloc0() {
+        alloc0_with(TestAlloc);
+        alloc0_with(LinearAlloc);
+        alloc0_with(SizeClassAlloc);
+        alloc0_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn alloc() {
+        alloc_with(TestAlloc);
+        alloc_with(LinearAlloc);
+        alloc_with(SizeClassAlloc);
+        alloc_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn dealloc0() {
+        dealloc0_with(TestAlloc);
+        dealloc0_with(LinearAlloc);
+        dealloc0_with(SizeClassAlloc);
+        dealloc0_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn dealloc() {
+        dealloc_with(TestAlloc);
+        dealloc_with(LinearAlloc);
+        dealloc_with(SizeClassAlloc);
+        dealloc_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn alloc_dealloc() {
+        alloc_dealloc_with(TestAlloc);
+        alloc_dealloc_with(LinearAlloc);
+        alloc_dealloc_with(SizeClassAlloc);
+        alloc_dealloc_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn try_alloc() {
+        try_alloc_with(TestAlloc);
+        try_alloc_with(LinearAlloc);
+        try_alloc_with(SizeClassAlloc);
+        try_alloc_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn compact() {
+        compact_with(TestAlloc);
+        compact_with(LinearAlloc);
+        compact_with(SizeClassAlloc);
+        compact_with(BuddyAlloc);
+    }
+
+    #[test]
+    fn trim() {
+        trim_with(TestAlloc);
+        trim_with(LinearAlloc);
+        trim_with(SizeClassAlloc);
+        trim_with(BuddyAlloc);
+    }
 }
@@ -0,0 +1,234 @@
+//! State of [`VarBuf`](super::VarBuf)'s allocation policies.
+
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Range;
+use std::sync::atomic::AtomicU32;
+
+use crate::bit_vec::AtomicBitVec;
+use crate::var_buf::VarMove;
+
+/// Slabs of fixed-size slots.
+///
+/// Each slab occupies a single `stride`-sized block of the
+/// buffer, which is claimed from (and released to) the
+/// buffer's bit vector.
+#[derive(Debug)]
+pub(super) struct Slabs {
+    stride: usize,
+    // Size of the smallest class.
+    min: usize,
+    // Slabs with free slots, per class.
+    partial: Vec<Vec<usize>>,
+    // Used slots of each slab, keyed by offset.
+    used: HashMap<usize, u64>,
+}
+
+impl Slabs {
+    pub fn new(stride: usize) -> Self {
+        let min = usize::max(stride / 64, 1);
+        let n = (stride / min).trailing_zeros() as usize;
+        Self {
+            stride,
+            min,
+            partial: vec![vec![]; n],
+            used: HashMap::new(),
+        }
+    }
+
+    /// Returns the slot size that fits `size` bytes aligned
+    /// to `align`, or `None` if it does not fit in a slab.
+    pub fn class(&self, size: usize, align: usize) -> Option<usize> {
+        let size = size.max(align).max(self.min).next_power_of_two();
+        (size < self.stride).then_some(size)
+    }
+
+    fn index(&self, class: usize) -> usize {
+        (class / self.min).trailing_zeros() as usize
+    }
+
+    fn full(&self, class: usize) -> u64 {
+        let n = self.stride / class;
+        if n == 64 {
+            !0
+        } else {
+            (1 << n) - 1
+        }
+    }
+
+    /// Allocates a `class` slot.
+    ///
+    /// It returns the slot's offset, or `None` if a new
+    /// slab is needed but `bits` has no free block.
+    pub fn alloc(&mut self, class: usize, bits: &AtomicBitVec<AtomicU32>) -> Option<usize> {
+        let (i, full) = (self.index(class), self.full(class));
+        let slab = match self.partial[i].last() {
+            Some(&x) => x,
+            None => {
+                let x = bits.find_and_set()? * self.stride;
+                self.used.insert(x, 0);
+                self.partial[i].push(x);
+                x
+            }
+        };
+        let mask = self.used.get_mut(&slab).unwrap();
+        let slot = (!*mask).trailing_zeros() as usize;
+        *mask |= 1 << slot;
+        if *mask == full {
+            self.partial[i].pop();
+        }
+        Some(slab + slot * class)
+    }
+
+    /// Frees the `class` slot at `offset`.
+    pub fn dealloc(&mut self, offset: usize, class: usize, bits: &AtomicBitVec<AtomicU32>) {
+        let (i, full) = (self.index(class), self.full(class));
+        let slab = offset & !(self.stride - 1);
+        let mask = self.used.get_mut(&slab).unwrap();
+        let was_full = *mask == full;
+        *mask &= !(1 << ((offset - slab) / class));
+        if *mask == 0 {
+            self.used.remove(&slab);
+            if let Some(j) = self.partial[i].iter().position(|&x| x == slab) {
+                self.partial[i].swap_remove(j);
+            }
+            bits.unset(slab / self.stride);
+        } else if was_full {
+            self.partial[i].push(slab);
+        }
+    }
+
+    /// Patches slab offsets after compaction.
+    pub fn relocate(&mut self, moves: &[VarMove]) {
+        let map = |x: usize| {
+            let i = moves.partition_point(|m| m.src + m.size <= x);
+            match moves.get(i) {
+                Some(m) if m.src <= x => x - m.src + m.dst,
+                _ => x,
+            }
+        };
+        self.used = self.used.drain().map(|(k, v)| (map(k), v)).collect();
+        self.partial.iter_mut().flatten().for_each(|x| *x = map(*x));
+    }
+}
+
+/// Binary buddy allocator.
+#[derive(Debug)]
+pub(super) struct Buddy {
+    // Size of the smallest block.
+    min: usize,
+    // Offsets of free blocks, per order.
+    free: Vec<BTreeSet<usize>>,
+}
+
+impl Buddy {
+    pub fn new(stride: usize) -> Self {
+        Self {
+            min: usize::max(stride / 64, 1),
+            free: vec![],
+        }
+    }
+
+    /// Returns the block size that fits `size` bytes aligned
+    /// to `align`.
+    pub fn block(&self, size: usize, align: usize) -> usize {
+        size.max(align).max(self.min).next_power_of_two()
+    }
+
+    fn order(&self, block: usize) -> usize {
+        (block / self.min).trailing_zeros() as usize
+    }
+
+    /// Allocates a block of `block` bytes.
+    pub fn alloc(&mut self, block: usize) -> Option<usize> {
+        let k = self.order(block);
+        let j = (k..self.free.len()).find(|&j| !self.free[j].is_empty())?;
+        let offset = self.free[j].pop_first().unwrap();
+        // Split until the block has the requested order,
+        // freeing the upper halves.
+        for i in (k..j).rev() {
+            self.free[i].insert(offset + (self.min << i));
+        }
+        Some(offset)
+    }
+
+    /// Frees the block of `block` bytes at `offset`,
+    /// merging it with its buddies.
+    pub fn dealloc(&mut self, mut offset: usize, block: usize) {
+        let mut k = self.order(block);
+        while k < self.free.len() && self.free[k].remove(&(offset ^ (self.min << k))) {
+            offset &= !(self.min << k);
+            k += 1;
+        }
+        if k >= self.free.len() {
+            self.free.resize(k + 1, BTreeSet::new());
+        }
+        self.free[k].insert(offset);
+    }
+
+    /// Frees the range `beg..end`.
+    pub fn add_range(&mut self, mut beg: usize, end: usize) {
+        while beg < end {
+            // Largest block aligned at `beg`.
+            let mut block = if beg == 0 {
+                1 << (usize::BITS - 1 - end.leading_zeros())
+            } else {
+                beg & beg.wrapping_neg()
+            };
+            while beg + block > end {
+                block >>= 1;
+            }
+            self.dealloc(beg, block);
+            beg += block;
+        }
+    }
+
+    /// Returns the number of free bytes that precede `end`.
+    pub fn trailing_free(&self, mut end: usize) -> usize {
+        let cap = end;
+        'outer: while end > 0 {
+            for (k, x) in self.free.iter().enumerate() {
+                let block = self.min << k;
+                if block <= end && (end - block).is_multiple_of(block) && x.contains(&(end - block))
+                {
+                    end -= block;
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+        cap - end
+    }
+
+    /// Returns the ranges of contiguous free blocks,
+    /// in increasing order.
+    pub fn free_runs(&self) -> Vec<Range<usize>> {
+        let mut blocks: Vec<_> = (self.free.iter().enumerate())
+            .flat_map(|(k, x)| x.iter().map(move |&x| (x, self.min << k)))
+            .collect();
+        blocks.sort_unstable();
+        let mut runs: Vec<Range<usize>> = vec![];
+        for (x, size) in blocks {
+            match runs.last_mut() {
+                Some(r) if r.end == x => r.end += size,
+                _ => runs.push(x..x + size),
+            }
+        }
+        runs
+    }
+
+    /// Removes the free space at or after `end`.
+    ///
+    /// NOTE: One must ensure that no block past `end`
+    /// is in use.
+    pub fn truncate(&mut self, end: usize) {
+        let mut split = vec![];
+        for (k, x) in self.free.iter_mut().enumerate() {
+            let block = self.min << k;
+            let cut = x.split_off(&end.saturating_sub(block - 1));
+            split.extend(cut.into_iter().filter(|&x| x < end));
+        }
+        for x in split {
+            self.add_range(x, end);
+        }
+    }
+}